@@ -0,0 +1,362 @@
+use std::path::Path;
+
+use asefile::{AsepriteFile, BlendMode, Layer, Tag};
+use image::{DynamicImage, Rgba, RgbaImage};
+
+pub struct Frame {
+    pub image: DynamicImage,
+    /// How long the frame is shown for in milliseconds
+    pub duration: u32,
+    pub slices: Vec<FrameSlice>,
+    /// The user data text on each of the included cels, keyed by layer name
+    pub cel_user_data: Vec<(String, String)>,
+}
+
+impl Frame {
+    /// The duration of the frame in GBA frames, assuming 60 frames per second.
+    /// Every frame is shown for at least one frame.
+    pub fn duration_in_frames(&self) -> u16 {
+        ((self.duration * 60 + 500) / 1000).clamp(1, u16::MAX as u32) as u16
+    }
+}
+
+/// A slice as it appears on a single frame. Positions are relative to the
+/// top left of the frame.
+pub struct FrameSlice {
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub pivot: Option<(i32, i32)>,
+}
+
+/// Which layers of the aseprite file should be flattened into each frame. By
+/// default, this is all the visible layers.
+#[derive(Debug, Clone, Default)]
+pub struct LayerSelection {
+    pub include: Option<Vec<String>>,
+    pub exclude: Vec<String>,
+}
+
+impl LayerSelection {
+    fn is_default(&self) -> bool {
+        self.include.is_none() && self.exclude.is_empty()
+    }
+
+    fn includes(&self, layer: &Layer) -> bool {
+        let name = layer.name();
+
+        if self.exclude.iter().any(|excluded| excluded == name) {
+            return false;
+        }
+
+        match &self.include {
+            Some(included) => included.iter().any(|included| included == name),
+            None => layer.is_visible(),
+        }
+    }
+
+    fn check_layers_exist(&self, ase: &AsepriteFile, filename: &Path) -> Result<(), String> {
+        let layer_names: Vec<_> = (0..ase.num_layers())
+            .map(|layer| ase.layer(layer).name().to_owned())
+            .collect();
+
+        for name in self.include.iter().flatten().chain(&self.exclude) {
+            if !layer_names.contains(name) {
+                return Err(format!(
+                    "File {} has no layer called {name}, it has the layers {layer_names:?}",
+                    filename.display()
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub fn generate_from_file(
+    filename: &Path,
+    layers: &LayerSelection,
+) -> Result<(Vec<Frame>, Vec<Tag>), String> {
+    let ase = AsepriteFile::read_file(filename)
+        .map_err(|e| format!("Failed to read {}: {e}", filename.display()))?;
+    layers.check_layers_exist(&ase, filename)?;
+
+    let mut frames = Vec::new();
+    let mut tags = Vec::new();
+
+    for frame_index in 0..ase.num_frames() {
+        let frame = ase.frame(frame_index);
+
+        frames.push(Frame {
+            image: DynamicImage::ImageRgba8(flatten_frame(&ase, frame_index, layers, filename)?),
+            duration: frame.duration(),
+            slices: slices_for_frame(&ase, frame_index),
+            cel_user_data: cel_user_data_for_frame(&ase, frame_index, layers),
+        })
+    }
+
+    for tag in 0..ase.num_tags() {
+        tags.push(ase.tag(tag).clone())
+    }
+
+    Ok((frames, tags))
+}
+
+/// Slices have keys which apply from a given frame until the next key, so find
+/// the most recent key for each slice.
+fn slices_for_frame(ase: &AsepriteFile, frame_index: u32) -> Vec<FrameSlice> {
+    ase.slices()
+        .iter()
+        .filter_map(|slice| {
+            let key = slice
+                .keys
+                .iter()
+                .filter(|key| key.from_frame <= frame_index)
+                .max_by_key(|key| key.from_frame)?;
+
+            let (width, height) = key.size;
+            if width == 0 || height == 0 {
+                return None;
+            }
+
+            let (x, y) = key.origin;
+
+            Some(FrameSlice {
+                name: slice.name.clone(),
+                x,
+                y,
+                width,
+                height,
+                pivot: key
+                    .pivot
+                    .map(|(pivot_x, pivot_y)| (x + pivot_x, y + pivot_y)),
+            })
+        })
+        .collect()
+}
+
+/// Aseprite flattens the visible layers itself, so this only needs to blend
+/// the layers when some are chosen. Each cel's image already has the layer and
+/// cel opacity applied.
+fn flatten_frame(
+    ase: &AsepriteFile,
+    frame_index: u32,
+    layers: &LayerSelection,
+    filename: &Path,
+) -> Result<RgbaImage, String> {
+    if layers.is_default() {
+        return Ok(ase.frame(frame_index).image());
+    }
+
+    let mut image = RgbaImage::new(ase.width() as u32, ase.height() as u32);
+
+    for layer in (0..ase.num_layers()).map(|layer| ase.layer(layer)) {
+        if !layers.includes(&layer) {
+            continue;
+        }
+
+        if layer.blend_mode() != BlendMode::Normal {
+            return Err(format!(
+                "Layer {} in file {} uses the {:?} blend mode, but only normal blending is supported when choosing layers",
+                layer.name(),
+                filename.display(),
+                layer.blend_mode()
+            ));
+        }
+
+        let cel = layer.frame(frame_index);
+        if cel.is_empty() {
+            continue;
+        }
+
+        for (pixel, cel_pixel) in image.pixels_mut().zip(cel.image().pixels()) {
+            *pixel = blend_normal(*pixel, *cel_pixel);
+        }
+    }
+
+    Ok(image)
+}
+
+/// Aseprite's normal blend mode, so chosen layers come out the same as they
+/// would when flattened by Aseprite.
+fn blend_normal(backdrop: Rgba<u8>, src: Rgba<u8>) -> Rgba<u8> {
+    let [back_r, back_g, back_b, back_a] = backdrop.0.map(i32::from);
+    let [src_r, src_g, src_b, src_a] = src.0.map(i32::from);
+
+    if back_a == 0 {
+        return src;
+    } else if src_a == 0 {
+        return backdrop;
+    }
+
+    let alpha = src_a + back_a - mul_un8(back_a, src_a);
+    let mix = |back: i32, src: i32| (back + (src - back) * src_a / alpha) as u8;
+
+    Rgba([
+        mix(back_r, src_r),
+        mix(back_g, src_g),
+        mix(back_b, src_b),
+        alpha as u8,
+    ])
+}
+
+/// Multiplies two 8 bit values as if they were fractions of 255, rounding the
+/// same way as Aseprite.
+fn mul_un8(a: i32, b: i32) -> i32 {
+    let t = a * b + 0x80;
+    ((t >> 8) + t) >> 8
+}
+
+fn cel_user_data_for_frame(
+    ase: &AsepriteFile,
+    frame_index: u32,
+    layers: &LayerSelection,
+) -> Vec<(String, String)> {
+    (0..ase.num_layers())
+        .map(|layer| ase.layer(layer))
+        .filter(|layer| layers.includes(layer))
+        .filter_map(|layer| {
+            let cel = layer.frame(frame_index);
+            let text = cel.user_data()?.text.clone()?;
+
+            Some((layer.name().to_owned(), text))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn example_file(file_name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../agb/examples/gfx")
+            .join(file_name)
+    }
+
+    fn layer_selection(include: Option<&[&str]>, exclude: &[&str]) -> LayerSelection {
+        LayerSelection {
+            include: include.map(|names| names.iter().map(|&name| name.to_owned()).collect()),
+            exclude: exclude.iter().map(|&name| name.to_owned()).collect(),
+        }
+    }
+
+    /// The first pixel of layers.aseprite, where every cel fills the canvas
+    /// with one colour. The body is translucent blue, the sword is red with a
+    /// layer and cel opacity, and the hidden guide and multiply blended shadow
+    /// layers are green and grey.
+    fn first_pixel(layers: &LayerSelection) -> [u8; 4] {
+        let (frames, _) = generate_from_file(&example_file("layers.aseprite"), layers).unwrap();
+        frames[0].image.to_rgba8().get_pixel(0, 0).0
+    }
+
+    #[test]
+    fn slices_use_the_latest_key() {
+        let (frames, _) =
+            generate_from_file(&example_file("slices.aseprite"), &LayerSelection::default())
+                .unwrap();
+
+        let slices: Vec<Vec<_>> = frames
+            .iter()
+            .map(|frame| {
+                frame
+                    .slices
+                    .iter()
+                    .map(|slice| {
+                        (
+                            slice.name.as_str(),
+                            (slice.x, slice.y, slice.width, slice.height),
+                            slice.pivot,
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // the hand slice is hidden from the second frame, and its pivot is
+        // relative to the slice rather than the frame in the file
+        assert_eq!(
+            slices,
+            [
+                vec![
+                    ("hitbox", (1, 2, 4, 5), None),
+                    ("hand", (4, 4, 2, 2), Some((5, 4))),
+                ],
+                vec![("hitbox", (1, 2, 4, 5), None)],
+                vec![("hitbox", (2, 1, 5, 6), None)],
+            ]
+        );
+    }
+
+    #[test]
+    fn chosen_layers_blend_like_aseprite() {
+        let flattened = first_pixel(&LayerSelection::default());
+        assert_ne!(flattened, [0, 0, 255, 200]);
+
+        let chosen = first_pixel(&layer_selection(Some(&["body", "sword"]), &[]));
+        assert_eq!(chosen, flattened);
+    }
+
+    #[test]
+    fn layers_can_be_chosen() {
+        assert_eq!(
+            first_pixel(&layer_selection(Some(&["guide"]), &[])),
+            [0, 255, 0, 255]
+        );
+        assert_eq!(
+            first_pixel(&layer_selection(None, &["sword"])),
+            [0, 0, 255, 200]
+        );
+        assert_eq!(
+            first_pixel(&layer_selection(Some(&["body", "sword"]), &["sword"])),
+            [0, 0, 255, 200]
+        );
+    }
+
+    #[test]
+    fn missing_layers_are_errors() {
+        let file = example_file("layers.aseprite");
+
+        assert!(generate_from_file(&file, &layer_selection(Some(&["cape"]), &[])).is_err());
+        assert!(generate_from_file(&file, &layer_selection(None, &["cape"])).is_err());
+    }
+
+    #[test]
+    fn other_blend_modes_are_errors_when_choosing_layers() {
+        let file = example_file("layers.aseprite");
+
+        assert!(generate_from_file(&file, &layer_selection(Some(&["body"]), &[])).is_ok());
+        assert!(
+            generate_from_file(&file, &layer_selection(Some(&["body", "shadow"]), &[])).is_err()
+        );
+    }
+
+    #[test]
+    fn user_data_is_read() {
+        let (frames, tags) = generate_from_file(
+            &example_file("layers.aseprite"),
+            &layer_selection(None, &["sword"]),
+        )
+        .unwrap();
+
+        let user_data: Vec<_> = tags
+            .iter()
+            .map(|tag| {
+                tag.user_data()
+                    .and_then(|user_data| user_data.text.as_deref())
+            })
+            .collect();
+        assert_eq!(user_data, [None, Some("damage=2")]);
+
+        for frame in &frames {
+            assert_eq!(
+                frame.cel_user_data,
+                [("body".to_owned(), "hitbox".to_owned())]
+            );
+        }
+    }
+}
@@ -0,0 +1,556 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use serde::Deserialize;
+use syn::parse::{Parse, ParseStream};
+use syn::{LitStr, Token};
+
+use crate::colour::Colour;
+use crate::palette_variant::PaletteVariant;
+use crate::pinned_palettes::PinnedPalettes;
+use crate::quantise::Quantise;
+use crate::{config, font_loader, AsepriteFileOption, BackgroundGfxOption, Colours};
+
+/// The input to `include_assets!`, which is the path to the manifest
+/// optionally preceded by `crate,` for use inside agb itself.
+pub(crate) struct IncludeAssetsInput {
+    crate_prefix: syn::Ident,
+    manifest: LitStr,
+}
+
+impl Parse for IncludeAssetsInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let crate_prefix = if input.peek(Token![crate]) {
+            let _: Token![crate] = input.parse()?;
+            let _: Token![,] = input.parse()?;
+            format_ident!("crate")
+        } else {
+            format_ident!("agb")
+        };
+
+        Ok(Self {
+            crate_prefix,
+            manifest: input.parse()?,
+        })
+    }
+}
+
+/// The contents of an asset manifest such as `assets.toml`. Each section maps
+/// the name of an asset to its options.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    #[serde(default)]
+    backgrounds: BTreeMap<String, BackgroundAsset>,
+    #[serde(default)]
+    maps: BTreeMap<String, MapAsset>,
+    #[serde(default)]
+    sprites: BTreeMap<String, SpriteAsset>,
+    #[serde(default)]
+    fonts: BTreeMap<String, FontAsset>,
+    #[serde(default)]
+    sounds: BTreeMap<String, SoundAsset>,
+}
+
+/// The same as `include_background_gfx!`, with one module per background.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BackgroundAsset {
+    transparent_colour: Option<String>,
+    palettes: Option<String>,
+    quantise: Option<String>,
+    #[serde(default)]
+    palette_variants: BTreeMap<String, String>,
+    images: BTreeMap<String, ImageAsset>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ImageAsset {
+    file: String,
+    #[serde(default = "default_colours")]
+    colours: u32,
+    #[serde(default)]
+    deduplicate: bool,
+}
+
+/// A single screen image which is deduplicated into its tiles and a map of
+/// where each one goes.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MapAsset {
+    file: String,
+    #[serde(default = "default_colours")]
+    colours: u32,
+    transparent_colour: Option<String>,
+    palettes: Option<String>,
+    quantise: Option<String>,
+}
+
+/// The same as `include_aseprite!`, where the files are either paths or
+/// tables of the per file settings.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SpriteAsset {
+    palettes: Option<String>,
+    files: Vec<SpriteFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SpriteFile {
+    Path(String),
+    Options(SpriteFileOptions),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SpriteFileOptions {
+    file: String,
+    layers: Option<Vec<String>>,
+    #[serde(default)]
+    exclude_layers: Vec<String>,
+    #[serde(default)]
+    tag_prefix: String,
+    #[serde(default = "default_rotations")]
+    rotations: usize,
+    #[serde(default)]
+    palette_variants: BTreeMap<String, String>,
+    quantise: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FontAsset {
+    file: String,
+    size: Option<f32>,
+    ranges: Option<Vec<(char, char)>>,
+    #[serde(default)]
+    characters_from: Vec<String>,
+    #[serde(default)]
+    strings_from: Vec<String>,
+    cell_size: Option<(usize, usize)>,
+    characters: Option<String>,
+    baseline: Option<usize>,
+    letter_spacing: Option<usize>,
+    transparent_colour: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SoundAsset {
+    file: String,
+}
+
+fn default_colours() -> u32 {
+    16
+}
+
+fn default_rotations() -> usize {
+    1
+}
+
+/// A background from the manifest, ready to be converted by
+/// [`include_gfx_from_config`](crate::include_gfx_from_config).
+struct BackgroundConfig {
+    crate_prefix: String,
+    transparent_colour: Colour,
+    palette_variants: Vec<(syn::Ident, PaletteVariant)>,
+    quantise: Option<Quantise>,
+    palettes: Option<PinnedPalettes>,
+    images: Vec<BackgroundGfxOption>,
+}
+
+impl config::Config for BackgroundConfig {
+    fn crate_prefix(&self) -> String {
+        self.crate_prefix.clone()
+    }
+
+    fn images(&self) -> HashMap<String, &dyn config::Image> {
+        self.images
+            .iter()
+            .map(|image| (image.module_name.clone(), image as &dyn config::Image))
+            .collect()
+    }
+
+    fn transparent_colour(&self) -> Option<Colour> {
+        Some(self.transparent_colour)
+    }
+
+    fn palette_variants(&self) -> &[(syn::Ident, PaletteVariant)] {
+        &self.palette_variants
+    }
+
+    fn quantise(&self) -> Option<Quantise> {
+        self.quantise
+    }
+
+    fn pinned_palettes(&self) -> Option<&PinnedPalettes> {
+        self.palettes.as_ref()
+    }
+}
+
+impl IncludeAssetsInput {
+    pub(crate) fn generate(&self) -> syn::Result<TokenStream> {
+        let root = crate::manifest_dir()?;
+        let manifest_path = Path::new(&root).join(self.manifest.value());
+
+        let manifest = std::fs::read_to_string(&manifest_path)
+            .map_err(|e| self.error(format!("Failed to read {}: {e}", manifest_path.display())))?;
+        let manifest: Manifest =
+            toml::from_str(&manifest).map_err(|e| self.error(format!("Invalid manifest: {e}")))?;
+
+        let crate_prefix = &self.crate_prefix;
+
+        let backgrounds = manifest
+            .backgrounds
+            .iter()
+            .map(|(name, background)| self.background(name, background))
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        let maps = manifest
+            .maps
+            .iter()
+            .map(|(name, map)| self.map(name, map))
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        let sprites = manifest
+            .sprites
+            .iter()
+            .map(|(name, sprites)| self.sprites(name, sprites))
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        let fonts = manifest
+            .fonts
+            .iter()
+            .map(|(name, font)| {
+                let name = self.static_name(name)?;
+                let font = crate::font(
+                    &self.path(&font.file),
+                    &self.font_options(font)?,
+                    self.manifest.span(),
+                )?;
+
+                Ok(quote! {
+                    pub static #name: #crate_prefix::display::Font = {
+                        use #crate_prefix::display;
+                        #font
+                    };
+                })
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        let sounds = manifest
+            .sounds
+            .iter()
+            .map(|(name, sound)| {
+                let name = self.static_name(name)?;
+                let file = self.path(&sound.file);
+
+                Ok(quote! {
+                    pub static #name: &[u8] = #crate_prefix::include_wav!(#file);
+                })
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        let groups = [
+            ("backgrounds", backgrounds),
+            ("maps", maps),
+            ("sprites", sprites),
+            ("fonts", fonts),
+            ("sounds", sounds),
+        ]
+        .into_iter()
+        .filter(|(_, assets)| !assets.is_empty())
+        .map(|(group, assets)| {
+            let group = format_ident!("{group}");
+            quote! {
+                pub mod #group {
+                    #(#assets)*
+                }
+            }
+        });
+
+        let manifest_path = manifest_path.to_string_lossy();
+
+        Ok(quote! {
+            const _: &[u8] = include_bytes!(#manifest_path);
+
+            #(#groups)*
+        })
+    }
+
+    fn background(&self, name: &str, background: &BackgroundAsset) -> syn::Result<TokenStream> {
+        let images = background
+            .images
+            .iter()
+            .map(|(image_name, image)| {
+                Ok(BackgroundGfxOption {
+                    module_name: self.ident(image_name)?.to_string(),
+                    file_name: self.path(&image.file),
+                    colours: self.colours(image.colours)?,
+                    deduplicate: image.deduplicate,
+                })
+            })
+            .collect::<syn::Result<_>>()?;
+
+        let palette_variants = background
+            .palette_variants
+            .iter()
+            .map(|(variant_name, variant)| Ok((self.ident(variant_name)?, self.parse(variant)?)))
+            .collect::<syn::Result<_>>()?;
+
+        let config = BackgroundConfig {
+            crate_prefix: self.crate_prefix.to_string(),
+            transparent_colour: self
+                .transparent_colour(background.transparent_colour.as_deref())?,
+            palette_variants,
+            quantise: self.quantise(background.quantise.as_deref())?,
+            palettes: self.palettes(background.palettes.as_deref())?,
+            images,
+        };
+
+        self.background_module(name, config)
+    }
+
+    fn map(&self, name: &str, map: &MapAsset) -> syn::Result<TokenStream> {
+        let config = BackgroundConfig {
+            crate_prefix: self.crate_prefix.to_string(),
+            transparent_colour: self.transparent_colour(map.transparent_colour.as_deref())?,
+            palette_variants: Vec::new(),
+            quantise: self.quantise(map.quantise.as_deref())?,
+            palettes: self.palettes(map.palettes.as_deref())?,
+            images: vec![BackgroundGfxOption {
+                module_name: "map".to_owned(),
+                file_name: self.path(&map.file),
+                colours: self.colours(map.colours)?,
+                deduplicate: true,
+            }],
+        };
+
+        self.background_module(name, config)
+    }
+
+    fn background_module(&self, name: &str, config: BackgroundConfig) -> syn::Result<TokenStream> {
+        let root = crate::manifest_dir()?;
+
+        Ok(crate::include_gfx_from_config(
+            Box::new(config),
+            self.ident(name)?,
+            &syn::parse_quote!(pub),
+            Path::new(&root),
+        ))
+    }
+
+    fn sprites(&self, name: &str, sprites: &SpriteAsset) -> syn::Result<TokenStream> {
+        let static_name = self.static_name(name)?;
+        let files = sprites
+            .files
+            .iter()
+            .map(|file| self.sprite_file(file))
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        let palettes = self.palettes(sprites.palettes.as_deref())?;
+        let graphics = crate::aseprite_graphics(&files, palettes.as_ref())?;
+
+        Ok(crate::graphics_static(
+            &self.crate_prefix,
+            &static_name,
+            &graphics,
+        ))
+    }
+
+    fn sprite_file(&self, file: &SpriteFile) -> syn::Result<AsepriteFileOption> {
+        let options = match file {
+            SpriteFile::Path(file) => {
+                return Ok(AsepriteFileOption {
+                    file_name: self.path(file),
+                    layers: Default::default(),
+                    tag_prefix: String::new(),
+                    rotations: 1,
+                    palette_variants: Vec::new(),
+                    quantise: None,
+                })
+            }
+            SpriteFile::Options(options) => options,
+        };
+
+        if options.rotations == 0 {
+            return Err(self.error(format!("{} must have at least one rotation", options.file)));
+        }
+
+        let palette_variants = options
+            .palette_variants
+            .iter()
+            .map(|(name, variant)| Ok((name.clone(), self.parse(variant)?)))
+            .collect::<syn::Result<_>>()?;
+
+        Ok(AsepriteFileOption {
+            file_name: self.path(&options.file),
+            layers: crate::aseprite::LayerSelection {
+                include: options.layers.clone(),
+                exclude: options.exclude_layers.clone(),
+            },
+            tag_prefix: options.tag_prefix.clone(),
+            rotations: options.rotations,
+            palette_variants,
+            quantise: self.quantise(options.quantise.as_deref())?,
+        })
+    }
+
+    /// The path relative to the crate root of a file given relative to the
+    /// manifest.
+    fn path(&self, file: &str) -> String {
+        let manifest = self.manifest.value();
+        let directory = Path::new(&manifest).parent().unwrap_or(Path::new(""));
+
+        directory.join(file).to_string_lossy().into_owned()
+    }
+
+    fn error(&self, message: impl std::fmt::Display) -> syn::Error {
+        syn::Error::new(self.manifest.span(), message)
+    }
+
+    /// Parses a setting written as a string in the manifest the same way as
+    /// the macro argument it corresponds to.
+    fn parse<T: Parse>(&self, setting: &str) -> syn::Result<T> {
+        syn::parse_str(setting).map_err(|e| self.error(format!("Invalid setting {setting:?}: {e}")))
+    }
+
+    fn ident(&self, name: &str) -> syn::Result<syn::Ident> {
+        syn::parse_str(name)
+            .map_err(|_| self.error(format!("{name:?} is not a valid Rust identifier")))
+    }
+
+    /// The name of the static for an asset, such as `PLAYER` for `player`.
+    fn static_name(&self, name: &str) -> syn::Result<syn::Ident> {
+        self.ident(&name.to_uppercase())
+    }
+
+    fn colours(&self, colours: u32) -> syn::Result<Colours> {
+        match colours {
+            16 => Ok(Colours::Colours16),
+            256 => Ok(Colours::Colours256),
+            _ => Err(self.error("Number of colours must be 16 or 256")),
+        }
+    }
+
+    fn transparent_colour(&self, colour: Option<&str>) -> syn::Result<Colour> {
+        match colour {
+            Some(colour) => colour.parse().map_err(|msg: String| self.error(msg)),
+            None => Ok(Colour::from_rgb(255, 0, 255, 0)),
+        }
+    }
+
+    fn quantise(&self, quantise: Option<&str>) -> syn::Result<Option<Quantise>> {
+        quantise.map(|quantise| self.parse(quantise)).transpose()
+    }
+
+    fn font_options(&self, font: &FontAsset) -> syn::Result<font_loader::FontOptions> {
+        Ok(font_loader::FontOptions {
+            size: font.size,
+            glyphs: font_loader::Glyphs {
+                ranges: font
+                    .ranges
+                    .as_ref()
+                    .map(|ranges| ranges.iter().map(|&(start, end)| start..=end).collect()),
+                characters_from: font
+                    .characters_from
+                    .iter()
+                    .map(|file| self.path(file))
+                    .collect(),
+                strings_from: font
+                    .strings_from
+                    .iter()
+                    .map(|file| self.path(file))
+                    .collect(),
+            },
+            grid: font_loader::GridSettings {
+                cell_size: font.cell_size,
+                characters: font.characters.clone(),
+                baseline: font.baseline,
+                letter_spacing: font.letter_spacing.unwrap_or(1),
+                transparent_colour: font
+                    .transparent_colour
+                    .as_deref()
+                    .map(|colour| self.transparent_colour(Some(colour)))
+                    .transpose()?,
+            },
+        })
+    }
+
+    fn palettes(&self, palettes: Option<&str>) -> syn::Result<Option<PinnedPalettes>> {
+        palettes
+            .map(|palettes| PinnedPalettes::load(&self.path(palettes), self.manifest.span()))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sections_are_optional() {
+        let manifest: Manifest = toml::from_str(
+            r#"
+            [sounds.jump]
+            file = "sfx/jump.wav"
+            "#,
+        )
+        .unwrap();
+
+        assert!(manifest.backgrounds.is_empty());
+        assert_eq!(manifest.sounds["jump"].file, "sfx/jump.wav");
+    }
+
+    #[test]
+    fn sprite_files_can_be_paths_or_settings() {
+        let manifest: Manifest = toml::from_str(
+            r#"
+            [sprites.player]
+            files = [
+                "gfx/player.aseprite",
+                { file = "gfx/weapon.aseprite", tag_prefix = "weapon-", rotations = 8 },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        let files = &manifest.sprites["player"].files;
+        assert!(matches!(&files[0], SpriteFile::Path(path) if path == "gfx/player.aseprite"));
+        assert!(matches!(
+            &files[1],
+            SpriteFile::Options(options) if options.tag_prefix == "weapon-" && options.rotations == 8
+        ));
+    }
+
+    #[test]
+    fn unknown_settings_are_rejected() {
+        let manifest = toml::from_str::<Manifest>(
+            r#"
+            [fonts.main]
+            file = "fonts/main.ttf"
+            size = 12
+            colour = "ffffff"
+            "#,
+        );
+
+        assert!(manifest.is_err());
+    }
+
+    #[test]
+    fn invalid_names_are_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = dir.path().join("assets.toml");
+        std::fs::write(&manifest, "[maps.\"level 1\"]\nfile = \"level1.png\"\n").unwrap();
+
+        let input: IncludeAssetsInput =
+            syn::parse_str(&format!("{:?}", manifest.display())).unwrap();
+        let error = input.generate().unwrap_err();
+
+        assert!(error.to_string().contains("not a valid Rust identifier"));
+    }
+}
@@ -0,0 +1,327 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+
+use crate::colour::Colour;
+use crate::palette_variant::PaletteVariant;
+use crate::pinned_palettes::PinnedPalettes;
+use crate::quantise::{ColourLoss, Quantise};
+use crate::{config, BackgroundGfxOption, Colours, Error};
+
+/// Converts background images in the same way as `include_background_gfx!`,
+/// for use from a build script. File names are relative to the crate root.
+///
+/// ```no_run
+/// use agb_asset_converter::{BackgroundConverter, Colours};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let backgrounds = BackgroundConverter::new()
+///     .transparent_colour("2ce8f4")?
+///     .image("beach", "gfx/beach.png", Colours::Colours16, true)
+///     .convert()?;
+///
+/// let out_dir = std::env::var("OUT_DIR")?;
+/// std::fs::write(
+///     format!("{out_dir}/backgrounds.rs"),
+///     backgrounds.to_rust("backgrounds"),
+/// )?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct BackgroundConverter {
+    transparent_colour: Colour,
+    quantise: Option<Quantise>,
+    palettes: Option<PinnedPalettes>,
+    images: Vec<BackgroundGfxOption>,
+}
+
+impl Default for BackgroundConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BackgroundConverter {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            transparent_colour: Colour::from_rgb(255, 0, 255, 0),
+            quantise: None,
+            palettes: None,
+            images: Vec::new(),
+        }
+    }
+
+    /// The colour which is transparent, as a hex string such as `"ff00ff"`.
+    pub fn transparent_colour(mut self, colour: &str) -> Result<Self, Error> {
+        self.transparent_colour = colour.parse().map_err(Error::new)?;
+        Ok(self)
+    }
+
+    /// Reduces the colours of the images to fit, written the same way as the
+    /// `quantise` setting of `include_background_gfx!`, such as `"kmeans
+    /// floyd_steinberg"`.
+    pub fn quantise(mut self, quantise: &str) -> Result<Self, Error> {
+        self.quantise = Some(syn::parse_str(quantise)?);
+        Ok(self)
+    }
+
+    /// Uses the palettes in the given palette image rather than finding new
+    /// ones, in the same way as the `palettes` setting of
+    /// `include_background_gfx!`.
+    pub fn palettes(mut self, file_name: &str) -> Result<Self, Error> {
+        self.palettes = Some(PinnedPalettes::load(file_name, Span::call_site())?);
+        Ok(self)
+    }
+
+    /// Adds an image to convert. All the images share the same palettes.
+    #[must_use]
+    pub fn image(
+        mut self,
+        name: &str,
+        file_name: &str,
+        colours: Colours,
+        deduplicate: bool,
+    ) -> Self {
+        self.images.push(BackgroundGfxOption {
+            module_name: name.to_owned(),
+            file_name: file_name.to_owned(),
+            colours,
+            deduplicate,
+        });
+        self
+    }
+
+    /// Converts the images, finding the palettes and the tiles of each image.
+    pub fn convert(&self) -> Result<ConvertedBackgrounds, Error> {
+        let root = crate::manifest_dir()?;
+
+        let (palettes, mut images, colour_loss) =
+            crate::convert_backgrounds(self, Path::new(&root))?;
+        let items = crate::background_items(self, &palettes, &images);
+
+        images.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(ConvertedBackgrounds {
+            palettes: crate::rust_generator::palette_data(&palettes, |colour| colour),
+            images,
+            colour_loss,
+            items,
+        })
+    }
+}
+
+impl config::Config for BackgroundConverter {
+    fn crate_prefix(&self) -> String {
+        "agb".to_owned()
+    }
+
+    fn images(&self) -> HashMap<String, &dyn config::Image> {
+        self.images
+            .iter()
+            .map(|options| (options.module_name.clone(), options as &dyn config::Image))
+            .collect()
+    }
+
+    fn transparent_colour(&self) -> Option<Colour> {
+        Some(self.transparent_colour)
+    }
+
+    fn palette_variants(&self) -> &[(syn::Ident, PaletteVariant)] {
+        &[]
+    }
+
+    fn quantise(&self) -> Option<Quantise> {
+        self.quantise
+    }
+
+    fn pinned_palettes(&self) -> Option<&PinnedPalettes> {
+        self.palettes.as_ref()
+    }
+}
+
+/// The result of a [`BackgroundConverter`].
+pub struct ConvertedBackgrounds {
+    /// The palettes shared by all of the images, as 15-bit colours.
+    pub palettes: Vec<[u16; 16]>,
+    /// The images, sorted by name.
+    pub images: Vec<ConvertedBackground>,
+    /// How much the colours changed, if the images were quantised. A build
+    /// script can print this as a warning with `cargo:warning=`.
+    pub colour_loss: Option<ColourLoss>,
+    items: TokenStream,
+}
+
+impl ConvertedBackgrounds {
+    /// The image with the given name.
+    #[must_use]
+    pub fn image(&self, name: &str) -> Option<&ConvertedBackground> {
+        self.images.iter().find(|image| image.name == name)
+    }
+
+    /// Rust source for a module with the same contents as the one
+    /// `include_background_gfx!` generates, ready to be written to `OUT_DIR`
+    /// and included with `include!`.
+    #[must_use]
+    pub fn to_rust(&self, module_name: &str) -> String {
+        let module_name = format_ident!("{}", module_name);
+        let items = &self.items;
+
+        quote! {
+            pub mod #module_name {
+                #items
+            }
+        }
+        .to_string()
+    }
+
+    /// Writes the palettes to `palettes.bin`, and the tiles and tile settings
+    /// of each image to `<name>.tiles` and `<name>.map` in the directory. All
+    /// the values are little endian, in the format the Game Boy Advance
+    /// expects in memory.
+    pub fn write_binaries(&self, directory: impl AsRef<Path>) -> io::Result<()> {
+        let directory = directory.as_ref();
+
+        fs::write(
+            directory.join("palettes.bin"),
+            crate::u16_bytes(self.palettes.iter().flatten().copied()),
+        )?;
+
+        for image in &self.images {
+            fs::write(
+                directory.join(format!("{}.tiles", image.name)),
+                &image.tiles,
+            )?;
+            fs::write(
+                directory.join(format!("{}.map", image.name)),
+                crate::u16_bytes(image.tile_settings.iter().map(|setting| setting.raw())),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A converted background image.
+pub struct ConvertedBackground {
+    pub name: String,
+    /// The image file, which a build script should rerun if it changes.
+    pub file: PathBuf,
+    /// The width of the image in pixels.
+    pub width: usize,
+    /// The height of the image in pixels.
+    pub height: usize,
+    pub tile_format: TileFormat,
+    /// The tile data, 32 bytes per tile for [`TileFormat::FourBpp`] and 64
+    /// for [`TileFormat::EightBpp`].
+    pub tiles: Vec<u8>,
+    /// Which tile goes in each 8x8 area of the image, left to right and then
+    /// top to bottom.
+    pub tile_settings: Vec<TileSetting>,
+}
+
+/// The number of bits per pixel of a tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileFormat {
+    FourBpp,
+    EightBpp,
+}
+
+/// The same as agb's `TileSetting`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileSetting {
+    pub tile_id: u16,
+    pub hflip: bool,
+    pub vflip: bool,
+    pub palette_id: u8,
+}
+
+impl TileSetting {
+    /// The setting as it would be written to a screenblock.
+    #[must_use]
+    pub fn raw(self) -> u16 {
+        (self.tile_id & 0x3ff)
+            | (u16::from(self.hflip) << 10)
+            | (u16::from(self.vflip) << 11)
+            | (u16::from(self.palette_id) << 12)
+    }
+
+    pub(crate) fn to_tokens(self, crate_prefix: &syn::Ident) -> TokenStream {
+        let TileSetting {
+            tile_id,
+            hflip,
+            vflip,
+            palette_id,
+        } = self;
+
+        quote! {
+            #crate_prefix::display::tiled::TileSetting::new(#tile_id, #hflip, #vflip, #palette_id)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_tile_setting_matches_agb() {
+        let setting = TileSetting {
+            tile_id: 0x123,
+            hflip: true,
+            vflip: false,
+            palette_id: 5,
+        };
+
+        assert_eq!(setting.raw(), 0x123 | 1 << 10 | 5 << 12);
+    }
+
+    #[test]
+    fn converts_deduplicated_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("background.png");
+
+        // Two tiles where the second is the first flipped horizontally
+        image::RgbaImage::from_fn(16, 8, |x, _| match x {
+            0 | 15 => image::Rgba([255, 0, 0, 255]),
+            _ => image::Rgba([0, 0, 255, 255]),
+        })
+        .save(&file)
+        .unwrap();
+
+        let backgrounds = BackgroundConverter::new()
+            .image("test", file.to_str().unwrap(), Colours::Colours16, true)
+            .convert()
+            .unwrap();
+
+        let image = backgrounds.image("test").unwrap();
+        assert_eq!(image.tile_format, TileFormat::FourBpp);
+        assert_eq!(image.tiles.len(), 32);
+        assert_eq!(image.tile_settings.len(), 2);
+        assert_eq!(
+            image.tile_settings[0].tile_id,
+            image.tile_settings[1].tile_id
+        );
+        assert_ne!(image.tile_settings[0].hflip, image.tile_settings[1].hflip);
+
+        let palette = backgrounds.palettes[usize::from(image.tile_settings[0].palette_id)];
+        assert!(palette.contains(&0x001f));
+        assert!(palette.contains(&0x7c00));
+    }
+
+    #[test]
+    fn missing_images_are_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("missing.png");
+
+        let result = BackgroundConverter::new()
+            .image("test", file.to_str().unwrap(), Colours::Colours16, true)
+            .convert();
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,315 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{LitStr, Token};
+
+use crate::colour::Colour;
+use crate::image_loader::Image;
+use crate::quantise::{ColourLoss, Quantise};
+use crate::ByteString;
+
+const SCREEN_WIDTH: usize = 240;
+const SCREEN_HEIGHT: usize = 160;
+const MODE_5_WIDTH: usize = 160;
+const MODE_5_HEIGHT: usize = 128;
+
+/// Which bitmap mode the image is converted for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// 16-bit colours, up to the size of the screen
+    Mode3,
+    /// 8-bit palette indices and the palette, up to the size of the screen
+    Mode4,
+    /// 16-bit colours, up to 160x128
+    Mode5,
+}
+
+impl Mode {
+    fn max_size(self) -> (usize, usize) {
+        match self {
+            Mode::Mode3 | Mode::Mode4 => (SCREEN_WIDTH, SCREEN_HEIGHT),
+            Mode::Mode5 => (MODE_5_WIDTH, MODE_5_HEIGHT),
+        }
+    }
+}
+
+/// The input to `include_bitmap!`, such as
+/// `"gfx/title.png", mode4, quantise: median_cut`.
+pub(crate) struct BitmapInput {
+    file_name: LitStr,
+    mode: Mode,
+    quantise: Option<Quantise>,
+}
+
+impl Parse for BitmapInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let file_name: LitStr = input.parse()?;
+        let _: Token![,] = input.parse()?;
+
+        let mode_ident: syn::Ident = input.parse()?;
+        let mode = match mode_ident.to_string().as_str() {
+            "mode3" => Mode::Mode3,
+            "mode4" => Mode::Mode4,
+            "mode5" => Mode::Mode5,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    mode_ident,
+                    "Unknown bitmap mode, expected mode3, mode4 or mode5",
+                ))
+            }
+        };
+
+        let mut quantise = None;
+
+        if input.peek(Token![,]) {
+            let _: Token![,] = input.parse()?;
+        }
+
+        if !input.is_empty() {
+            let setting: syn::Ident = input.parse()?;
+            if setting != "quantise" {
+                return Err(syn::Error::new_spanned(
+                    setting,
+                    "Unknown setting, expected quantise",
+                ));
+            }
+            if mode != Mode::Mode4 {
+                return Err(syn::Error::new_spanned(
+                    setting,
+                    "quantise is only needed for mode4, since mode3 and mode5 can use any colour",
+                ));
+            }
+
+            let _: Token![:] = input.parse()?;
+            quantise = Some(input.parse()?);
+
+            if input.peek(Token![,]) {
+                let _: Token![,] = input.parse()?;
+            }
+        }
+
+        Ok(Self {
+            file_name,
+            mode,
+            quantise,
+        })
+    }
+}
+
+impl BitmapInput {
+    pub(crate) fn generate(&self) -> syn::Result<TokenStream> {
+        let root = crate::manifest_dir()?;
+        let path = Path::new(&root).join(self.file_name.value());
+        let image = Image::load_from_file(&path)
+            .map_err(|e| syn::Error::new_spanned(&self.file_name, e))?;
+
+        let (max_width, max_height) = self.mode.max_size();
+        if image.width > max_width || image.height > max_height {
+            return Err(syn::Error::new_spanned(
+                &self.file_name,
+                format!(
+                    "Image is {}x{} but the bitmap mode can show at most {max_width}x{max_height}",
+                    image.width, image.height
+                ),
+            ));
+        }
+
+        let (width, height) = (image.width, image.height);
+        let file_name = path.to_string_lossy();
+
+        let bitmap = match self.mode {
+            Mode::Mode3 | Mode::Mode5 => {
+                let colour_data = colour_data(&image);
+                let data = ByteString(&colour_data);
+                let data_type = if self.mode == Mode::Mode3 {
+                    quote!(Bitmap3Data)
+                } else {
+                    quote!(Bitmap5Data)
+                };
+
+                quote! {
+                    unsafe { #data_type::new(#width, #height, align_bytes!(u16, #data)) }
+                }
+            }
+            Mode::Mode4 => {
+                if width % 2 != 0 {
+                    return Err(syn::Error::new_spanned(
+                        &self.file_name,
+                        format!(
+                            "Image is {width} pixels wide, but mode 4 images need an even width"
+                        ),
+                    ));
+                }
+
+                let (indices, palette, colour_loss) = self.paletted_data(image)?;
+                let data = ByteString(&indices);
+                let warning = colour_loss.map(|loss| loss.warning(&self.file_name.value()));
+
+                quote! {
+                    {
+                        #warning
+                        unsafe {
+                            Bitmap4Data::new(
+                                #width,
+                                #height,
+                                align_bytes!(u16, #data),
+                                &[#(Rgb15::new(#palette)),*],
+                            )
+                        }
+                    }
+                }
+            }
+        };
+
+        Ok(quote! {
+            {
+                const _: &[u8] = include_bytes!(#file_name);
+                #bitmap
+            }
+        })
+    }
+
+    /// The palette index of every pixel and the palette they index into, and
+    /// how much the colours changed if they were quantised. Transparent pixels
+    /// use index 0, which is then left black.
+    fn paletted_data(
+        &self,
+        mut image: Image,
+    ) -> syn::Result<(Vec<u8>, Vec<u16>, Option<ColourLoss>)> {
+        let has_transparency = (0..image.height)
+            .any(|y| (0..image.width).any(|x| image.colour(x, y).is_transparent()));
+        let max_colours = if has_transparency { 255 } else { 256 };
+
+        let mut colour_loss = None;
+        if let Some(quantise) = self.quantise {
+            let (width, height) = (image.width, image.height);
+            let tiles = crate::image_tiles(&image, width, height, quantise.dither);
+            let (tiles, loss) =
+                quantise.quantise_shared(&tiles, Colour::from_rgb(0, 0, 0, 0), max_colours);
+
+            crate::set_image_tiles(&mut image, width, height, &mut tiles.into_iter());
+            colour_loss = Some(loss);
+        }
+
+        let mut palette: Vec<u16> = Vec::new();
+        if has_transparency {
+            palette.push(0);
+        }
+
+        let mut palette_indices = HashMap::new();
+        let mut indices = Vec::with_capacity(image.width * image.height);
+
+        for y in 0..image.height {
+            for x in 0..image.width {
+                let colour = image.colour(x, y);
+                if colour.is_transparent() {
+                    indices.push(0);
+                    continue;
+                }
+
+                let colour = colour.to_rgb15();
+                let index = *palette_indices.entry(colour).or_insert_with(|| {
+                    palette.push(colour);
+                    palette.len() - 1
+                });
+
+                indices.push(index);
+            }
+        }
+
+        if palette.len() > 256 {
+            return Err(syn::Error::new_spanned(
+                &self.file_name,
+                format!(
+                    "Image has {} colours but mode 4 can only use {max_colours}. Add a setting such as `quantise: median_cut` to reduce them",
+                    palette.len() - usize::from(has_transparency)
+                ),
+            ));
+        }
+
+        Ok((
+            indices.into_iter().map(|index| index as u8).collect(),
+            palette,
+            colour_loss,
+        ))
+    }
+}
+
+/// The raw 16-bit colour of every pixel, in little endian. Transparent pixels
+/// are black.
+fn colour_data(image: &Image) -> Vec<u8> {
+    (0..image.height)
+        .flat_map(|y| (0..image.width).map(move |x| image.colour(x, y)))
+        .flat_map(|colour| {
+            if colour.is_transparent() {
+                0u16
+            } else {
+                colour.to_rgb15()
+            }
+            .to_le_bytes()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mode4_input(quantise: Option<Quantise>) -> BitmapInput {
+        BitmapInput {
+            file_name: LitStr::new("test.png", proc_macro2::Span::call_site()),
+            mode: Mode::Mode4,
+            quantise,
+        }
+    }
+
+    #[test]
+    fn transparent_pixels_use_index_zero() {
+        let red = Colour::from_rgb(255, 0, 0, 255);
+        let transparent = Colour::from_rgb(0, 0, 0, 0);
+        let blue = Colour::from_rgb(0, 0, 255, 255);
+
+        let image = Image::from_colour_data(vec![red, transparent, blue, red, red, red, red, red]);
+        let (indices, palette, colour_loss) = mode4_input(None).paletted_data(image).unwrap();
+
+        assert_eq!(indices, [1, 0, 2, 1, 1, 1, 1, 1]);
+        assert_eq!(palette, [0, red.to_rgb15(), blue.to_rgb15()]);
+        assert!(colour_loss.is_none());
+    }
+
+    #[test]
+    fn transparent_pixels_are_black_in_direct_colour_modes() {
+        let red = Colour::from_rgb(255, 0, 0, 255);
+        let magenta = Colour::from_rgb(255, 0, 255, 0);
+
+        let image = Image::from_colour_data(vec![red, magenta, red, red, red, red, red, red]);
+        let red = red.to_rgb15().to_le_bytes();
+
+        assert_eq!(
+            colour_data(&image)[..6],
+            [red[0], red[1], 0, 0, red[0], red[1]]
+        );
+    }
+
+    #[test]
+    fn too_many_colours_need_quantising() {
+        let colours: Vec<_> = (0..304)
+            .map(|i| Colour::from_rgb((i % 32 * 8) as u8, (i / 32 * 8) as u8, 0, 255))
+            .collect();
+
+        assert!(mode4_input(None)
+            .paletted_data(Image::from_colour_data(colours.clone()))
+            .is_err());
+
+        let quantise = syn::parse_str::<Quantise>("median_cut").unwrap();
+        let (_, palette, colour_loss) = mode4_input(Some(quantise))
+            .paletted_data(Image::from_colour_data(colours))
+            .unwrap();
+
+        assert!(palette.len() <= 256);
+        assert!(colour_loss.unwrap().is_lossy());
+    }
+}
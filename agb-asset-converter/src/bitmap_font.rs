@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::colour::Colour;
+use crate::font_loader::{pack_pixels, FontData, GridSettings, LetterData};
+use crate::image_loader::Image;
+
+/// Loads a font drawn as a grid of equally sized cells, one glyph per cell.
+/// Each glyph is trimmed to the pixels drawn in its cell and advances by its
+/// rightmost pixel plus the letter spacing. Empty cells, such as the one for
+/// space, advance by half the cell width.
+pub(crate) fn load_grid(image: &Image, settings: &GridSettings) -> Result<FontData, String> {
+    let (cell_width, cell_height) = settings
+        .cell_size
+        .ok_or("Glyph grid fonts need a cell_size")?;
+    let characters = settings
+        .characters
+        .as_ref()
+        .ok_or("Glyph grid fonts need the characters of each cell")?;
+    let baseline = settings.baseline.unwrap_or(cell_height);
+
+    if cell_width == 0 || cell_height == 0 {
+        return Err("The cell size must not be zero".to_owned());
+    }
+
+    if image.width % cell_width != 0 || image.height % cell_height != 0 {
+        return Err(format!(
+            "The image size {}x{} is not a multiple of the cell size {cell_width}x{cell_height}",
+            image.width, image.height
+        ));
+    }
+
+    if baseline > cell_height {
+        return Err(format!(
+            "The baseline {baseline} is outside of the cell, which is {cell_height} pixels high"
+        ));
+    }
+
+    let columns = image.width / cell_width;
+    let cells = columns * (image.height / cell_height);
+
+    if characters.chars().count() > cells {
+        return Err(format!(
+            "There are {} characters but only {cells} cells",
+            characters.chars().count()
+        ));
+    }
+
+    let is_ink =
+        |colour: Colour| !colour.is_transparent() && Some(colour) != settings.transparent_colour;
+    let empty_advance = (cell_width / 2) as f32;
+
+    let mut letters = characters
+        .chars()
+        .enumerate()
+        .map(|(index, c)| {
+            let x = (index % columns) * cell_width;
+            let y = (index / columns) * cell_height;
+
+            let ink: Vec<_> = (0..cell_height)
+                .flat_map(|j| (0..cell_width).map(move |i| (i, j)))
+                .filter(|&(i, j)| is_ink(image.colour(x + i, y + j)))
+                .collect();
+
+            let letter = match bounds(&ink) {
+                Some((min_x, min_y, max_x, max_y)) => LetterData {
+                    width: max_x - min_x + 1,
+                    height: max_y - min_y + 1,
+                    xmin: min_x as i32,
+                    ymin: baseline as i32 - max_y as i32 - 1,
+                    advance_width: (max_x + 1 + settings.letter_spacing) as f32,
+                    rendered: pack_pixels((min_y..=max_y).flat_map(|j| {
+                        (min_x..=max_x).map(move |i| is_ink(image.colour(x + i, y + j)))
+                    })),
+                },
+                None => LetterData::empty(empty_advance),
+            };
+
+            (c, letter)
+        })
+        .collect::<Vec<_>>();
+
+    letters.sort_by_key(|&(c, _)| c);
+
+    if let Some(pair) = letters.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        return Err(format!(
+            "The character {:?} has more than one cell",
+            pair[0].0
+        ));
+    }
+
+    let fallback = letters
+        .iter()
+        .find(|(c, _)| *c == '?')
+        .map(|(_, letter)| letter.clone())
+        .unwrap_or_else(|| LetterData::empty(empty_advance));
+
+    Ok(FontData {
+        letters,
+        fallback,
+        kerning: Vec::new(),
+        line_height: cell_height as i32,
+        ascent: baseline as i32,
+    })
+}
+
+/// The smallest and largest x and y of the pixels.
+fn bounds(pixels: &[(usize, usize)]) -> Option<(usize, usize, usize, usize)> {
+    let min_x = pixels.iter().map(|&(x, _)| x).min()?;
+    let min_y = pixels.iter().map(|&(_, y)| y).min()?;
+    let max_x = pixels.iter().map(|&(x, _)| x).max()?;
+    let max_y = pixels.iter().map(|&(_, y)| y).max()?;
+
+    Some((min_x, min_y, max_x, max_y))
+}
+
+/// Loads a font in the text format of
+/// [BMFont](https://www.angelcode.com/products/bmfont/), returning the font
+/// and the page images it uses. Glyph pixels are the bright, opaque pixels of
+/// the pages. The glyph for character -1 is used as the fallback if there is
+/// one, and `?` otherwise.
+pub(crate) fn load_bmfont(path: &Path) -> Result<(FontData, Vec<PathBuf>), String> {
+    let content = std::fs::read(path).map_err(|e| format!("Failed to read font: {e}"))?;
+
+    if content.starts_with(b"BMF") {
+        return Err("Only the text format of BMFont files is supported".to_owned());
+    }
+
+    let content =
+        String::from_utf8(content).map_err(|_| "BMFont file is not valid UTF-8".to_owned())?;
+    let directory = path.parent().unwrap_or(Path::new(""));
+
+    let mut line_height = None;
+    let mut base = None;
+    let mut pages = HashMap::new();
+    let mut letters = Vec::new();
+    let mut kerning = Vec::new();
+    let mut fallback = None;
+
+    for line in content.lines() {
+        let (tag, values) = parse_line(line);
+
+        match tag {
+            "common" => {
+                line_height = Some(values.number("lineHeight")?);
+                base = Some(values.number("base")?);
+            }
+            "page" => {
+                let file = directory.join(values.get("file")?);
+                let image = Image::load_from_file(&file)?;
+                pages.insert(values.number("id")?, (file, image));
+            }
+            "char" => {
+                let base = base.ok_or("The common line must come before the chars")?;
+                let page = &pages
+                    .get(&values.number("page")?)
+                    .ok_or("A char uses a page which doesn't exist")?
+                    .1;
+
+                let (x, y) = (values.number("x")?, values.number("y")?);
+                let (width, height) = (values.number("width")?, values.number("height")?);
+
+                if x + width > page.width as i32 || y + height > page.height as i32 {
+                    return Err(format!("The char at {x}, {y} is outside of its page"));
+                }
+
+                let letter = LetterData {
+                    width: width as usize,
+                    height: height as usize,
+                    xmin: values.number("xoffset")?,
+                    ymin: base - height - values.number("yoffset")?,
+                    advance_width: values.number("xadvance")? as f32,
+                    rendered: pack_pixels((y..y + height).flat_map(|j| {
+                        (x..x + width).map(move |i| is_bright(page.colour(i as usize, j as usize)))
+                    })),
+                };
+
+                let id: i32 = values.number("id")?;
+                if id == -1 {
+                    fallback = Some(letter);
+                } else if let Some(c) = char::from_u32(id as u32) {
+                    letters.push((c, letter));
+                }
+            }
+            "kerning" => {
+                let first = char::from_u32(values.number("first")? as u32);
+                let second = char::from_u32(values.number("second")? as u32);
+
+                if let (Some(first), Some(second)) = (first, second) {
+                    kerning.push((first, second, values.number("amount")?));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    letters.sort_by_key(|&(c, _)| c);
+    letters.dedup_by_key(|(c, _)| *c);
+
+    let fallback = fallback
+        .or_else(|| {
+            letters
+                .iter()
+                .find(|(c, _)| *c == '?')
+                .map(|(_, letter)| letter.clone())
+        })
+        .unwrap_or_else(|| LetterData::empty(0.0));
+
+    let mut page_files: Vec<_> = pages.into_values().map(|(file, _)| file).collect();
+    page_files.sort();
+
+    let mut font = FontData {
+        letters,
+        fallback,
+        kerning,
+        line_height: line_height.ok_or("BMFont file has no common line")?,
+        ascent: base.ok_or("BMFont file has no common line")?,
+    };
+    font.retain_kerning();
+
+    Ok((font, page_files))
+}
+
+fn is_bright(colour: Colour) -> bool {
+    colour.a > 127 && colour.r.max(colour.g).max(colour.b) > 127
+}
+
+/// The `key=value` pairs of a line of a BMFont file.
+struct Values<'a>(HashMap<&'a str, &'a str>);
+
+impl Values<'_> {
+    fn get(&self, key: &str) -> Result<&str, String> {
+        self.0
+            .get(key)
+            .copied()
+            .ok_or_else(|| format!("Missing {key} in BMFont file"))
+    }
+
+    fn number(&self, key: &str) -> Result<i32, String> {
+        let value = self.get(key)?;
+        value
+            .parse()
+            .map_err(|_| format!("Expected {key} to be a number, got {value}"))
+    }
+}
+
+/// Splits a line into its tag and values, where values may be quoted.
+fn parse_line(line: &str) -> (&str, Values<'_>) {
+    let line = line.trim();
+    let (tag, mut rest) = line.split_once(' ').unwrap_or((line, ""));
+    let mut values = HashMap::new();
+
+    while let Some((key, after)) = rest.trim_start().split_once('=') {
+        let (value, remaining) = match after.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => after.split_once(' ').unwrap_or((after, "")),
+        };
+
+        values.insert(key, value);
+        rest = remaining;
+    }
+
+    (tag, Values(values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid_image() -> Image {
+        let ink = Colour::from_rgb(0, 0, 0, 255);
+        let paper = Colour::from_rgb(255, 255, 255, 0);
+
+        // Two 8x8 cells side by side: a 2x3 block at (1, 2) and an empty cell
+        let mut colours = vec![paper; 16 * 8];
+        for y in 2..5 {
+            for x in 1..3 {
+                colours[x + y * 16] = ink;
+            }
+        }
+
+        let mut image = Image::from_colour_data(colours);
+        image.width = 16;
+        image.height = 8;
+
+        image
+    }
+
+    #[test]
+    fn grid_glyphs_are_trimmed() {
+        let settings = GridSettings {
+            cell_size: Some((8, 8)),
+            characters: Some("a ".to_owned()),
+            baseline: Some(6),
+            ..Default::default()
+        };
+
+        let font = load_grid(&grid_image(), &settings).unwrap();
+
+        assert_eq!(font.letters.len(), 2);
+        assert_eq!(font.letters[0].0, ' ');
+        assert_eq!(font.letters[0].1.advance_width, 4.0);
+
+        let (c, a) = &font.letters[1];
+        assert_eq!(*c, 'a');
+        assert_eq!((a.width, a.height), (2, 3));
+        assert_eq!((a.xmin, a.ymin), (1, 1));
+        assert_eq!(a.advance_width, 4.0);
+        assert_eq!(a.rendered, [0b0011_1111]);
+    }
+
+    #[test]
+    fn grid_needs_enough_cells() {
+        let settings = GridSettings {
+            cell_size: Some((8, 8)),
+            characters: Some("abc".to_owned()),
+            ..Default::default()
+        };
+
+        assert!(load_grid(&grid_image(), &settings).is_err());
+    }
+
+    #[test]
+    fn bmfont_lines_are_parsed() {
+        let (tag, values) = parse_line(r#"page id=0 file="font 0.png" "#);
+        assert_eq!(tag, "page");
+        assert_eq!(values.number("id"), Ok(0));
+        assert_eq!(values.get("file"), Ok("font 0.png"));
+
+        let (tag, values) = parse_line("char id=65   x=3 y=4 xoffset=-1");
+        assert_eq!(tag, "char");
+        assert_eq!(values.number("id"), Ok(65));
+        assert_eq!(values.number("xoffset"), Ok(-1));
+        assert!(values.get("page").is_err());
+    }
+}
@@ -1,11 +1,17 @@
 use std::collections::HashMap;
 
-use crate::{Colour, Colours};
+use crate::{
+    palette_variant::PaletteVariant, pinned_palettes::PinnedPalettes, quantise::Quantise, Colour,
+    Colours,
+};
 
 pub(crate) trait Config {
     fn crate_prefix(&self) -> String;
     fn images(&self) -> HashMap<String, &dyn Image>;
     fn transparent_colour(&self) -> Option<Colour>;
+    fn palette_variants(&self) -> &[(syn::Ident, PaletteVariant)];
+    fn quantise(&self) -> Option<Quantise>;
+    fn pinned_palettes(&self) -> Option<&PinnedPalettes>;
 }
 
 pub(crate) trait Image {
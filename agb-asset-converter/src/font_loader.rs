@@ -0,0 +1,607 @@
+use crate::colour::Colour;
+use crate::ByteString;
+use quote::quote;
+
+use proc_macro2::TokenStream;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{LitChar, LitInt, LitStr, Token};
+
+use std::collections::{BTreeSet, HashMap};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+/// The input to `include_font!`, which is the file name and, for TTF fonts,
+/// the size, followed by optional settings such as `ranges: ['a'..='z']`.
+pub(crate) struct FontInput {
+    pub file_name: LitStr,
+    pub options: FontOptions,
+}
+
+/// The size and settings of a font. Only TTF fonts have a size, and only
+/// glyph grid images have [`GridSettings`].
+#[derive(Default)]
+pub(crate) struct FontOptions {
+    pub size: Option<f32>,
+    pub glyphs: Glyphs,
+    pub grid: GridSettings,
+}
+
+impl Parse for FontInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let file_name: LitStr = input.parse()?;
+        let mut options = FontOptions::default();
+
+        if input.peek(Token![,]) && (input.peek2(syn::LitInt) || input.peek2(syn::LitFloat)) {
+            let _: Token![,] = input.parse()?;
+
+            options.size = Some(match input.parse()? {
+                syn::Lit::Float(value) => value.base10_parse()?,
+                syn::Lit::Int(value) => value.base10_parse::<i32>()? as f32,
+                _ => unreachable!(),
+            });
+        }
+
+        while input.peek(Token![,]) {
+            let _: Token![,] = input.parse()?;
+            if input.is_empty() {
+                break;
+            }
+
+            let setting: syn::Ident = input.parse()?;
+            let _: Token![:] = input.parse()?;
+
+            match setting.to_string().as_str() {
+                "ranges" => {
+                    let content;
+                    syn::bracketed!(content in input);
+
+                    let ranges = Punctuated::<CharRange, Token![,]>::parse_terminated(&content)?;
+                    options.glyphs.ranges =
+                        Some(ranges.into_iter().map(|range| range.0).collect());
+                }
+                "characters_from" => {
+                    options.glyphs.characters_from = crate::parse_string_list(input)?;
+                }
+                "strings_from" => {
+                    options.glyphs.strings_from = crate::parse_string_list(input)?;
+                }
+                "cell_size" => {
+                    let content;
+                    syn::parenthesized!(content in input);
+
+                    let width: LitInt = content.parse()?;
+                    let _: Token![,] = content.parse()?;
+                    let height: LitInt = content.parse()?;
+
+                    options.grid.cell_size = Some((width.base10_parse()?, height.base10_parse()?));
+                }
+                "characters" => {
+                    options.grid.characters = Some(input.parse::<LitStr>()?.value());
+                }
+                "baseline" => {
+                    options.grid.baseline = Some(input.parse::<LitInt>()?.base10_parse()?);
+                }
+                "letter_spacing" => {
+                    options.grid.letter_spacing = input.parse::<LitInt>()?.base10_parse()?;
+                }
+                "transparent_colour" => {
+                    let colour: LitStr = input.parse()?;
+                    options.grid.transparent_colour = Some(
+                        colour
+                            .value()
+                            .parse()
+                            .map_err(|msg| syn::Error::new_spanned(colour, msg))?,
+                    );
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        setting,
+                        "Unknown setting, expected ranges, characters_from, strings_from, cell_size, characters, baseline, letter_spacing or transparent_colour",
+                    ))
+                }
+            }
+        }
+
+        if input.peek(Token![,]) {
+            let _: Token![,] = input.parse()?;
+        }
+
+        Ok(Self { file_name, options })
+    }
+}
+
+/// Either a single character such as `'a'` or a range such as `'a'..='z'`.
+struct CharRange(RangeInclusive<char>);
+
+impl Parse for CharRange {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let start: LitChar = input.parse()?;
+
+        if !input.peek(Token![..=]) {
+            return Ok(Self(start.value()..=start.value()));
+        }
+
+        let _: Token![..=] = input.parse()?;
+        let end: LitChar = input.parse()?;
+
+        if end.value() < start.value() {
+            return Err(syn::Error::new_spanned(end, "Range ends before it starts"));
+        }
+
+        Ok(Self(start.value()..=end.value()))
+    }
+}
+
+/// Which characters to include glyphs for. By default these are the first 128
+/// characters, which are the ASCII ones, or every glyph of a bitmap font.
+/// Giving ranges or string tables replaces the default, and the characters in
+/// the files are included as well as the ranges.
+#[derive(Default)]
+pub(crate) struct Glyphs {
+    pub ranges: Option<Vec<RangeInclusive<char>>>,
+    /// Files relative to the crate root
+    pub characters_from: Vec<String>,
+    /// String tables in the format of `include_strings!`, relative to the
+    /// crate root, where only the characters of the strings are included
+    pub strings_from: Vec<String>,
+}
+
+impl Glyphs {
+    /// Whether no ranges or files were given.
+    pub(crate) fn is_default(&self) -> bool {
+        self.ranges.is_none() && self.characters_from.is_empty() && self.strings_from.is_empty()
+    }
+
+    /// The characters to include, sorted, and the files they were read from.
+    pub(crate) fn characters(&self, root: &Path) -> Result<(Vec<char>, Vec<PathBuf>), String> {
+        let mut characters: BTreeSet<char> = match &self.ranges {
+            Some(ranges) => ranges.iter().flat_map(|range| range.clone()).collect(),
+            None if !self.strings_from.is_empty() => BTreeSet::new(),
+            None => (0..128).filter_map(char::from_u32).collect(),
+        };
+
+        let text_files = self
+            .characters_from
+            .iter()
+            .map(|file_name| root.join(file_name));
+        let string_files = self
+            .strings_from
+            .iter()
+            .map(|file_name| root.join(file_name));
+
+        for file in text_files.clone() {
+            let text = std::fs::read_to_string(&file)
+                .map_err(|e| format!("Failed to read {}: {e}", file.display()))?;
+            characters.extend(text.chars().filter(|c| !c.is_control()));
+        }
+
+        for file in string_files.clone() {
+            let strings = crate::strings::load_strings(&file)
+                .map_err(|e| format!("Failed to load {}: {e}", file.display()))?;
+            characters.extend(
+                strings
+                    .iter()
+                    .flat_map(|(_, text)| text.chars())
+                    .filter(|c| !c.is_control()),
+            );
+        }
+
+        let files = text_files.chain(string_files).collect();
+
+        Ok((characters.into_iter().collect(), files))
+    }
+}
+
+/// How to split a glyph grid image into glyphs, such as
+/// `cell_size: (8, 8), characters: " !\"#..."`.
+pub(crate) struct GridSettings {
+    pub cell_size: Option<(usize, usize)>,
+    /// The characters of the cells, left to right and then top to bottom
+    pub characters: Option<String>,
+    /// The row of each cell which glyphs sit on, which defaults to the bottom
+    /// of the cell
+    pub baseline: Option<usize>,
+    /// The gap after the rightmost pixel of each glyph
+    pub letter_spacing: usize,
+    /// A colour in the image which isn't part of the glyphs, as well as any
+    /// transparent pixels
+    pub transparent_colour: Option<Colour>,
+}
+
+impl Default for GridSettings {
+    fn default() -> Self {
+        Self {
+            cell_size: None,
+            characters: None,
+            baseline: None,
+            letter_spacing: 1,
+            transparent_colour: None,
+        }
+    }
+}
+
+/// A single glyph of a font.
+#[derive(Clone)]
+pub(crate) struct LetterData {
+    pub width: usize,
+    pub height: usize,
+    pub xmin: i32,
+    pub ymin: i32,
+    pub advance_width: f32,
+    /// One bit for each pixel, left to right and then top to bottom
+    pub rendered: Vec<u8>,
+}
+
+impl LetterData {
+    /// A glyph with nothing to draw.
+    pub(crate) fn empty(advance_width: f32) -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            xmin: 0,
+            ymin: 0,
+            advance_width,
+            rendered: Vec::new(),
+        }
+    }
+}
+
+/// Packs one bit per pixel, 8 pixels to a byte, in the format `FontLetter`
+/// uses.
+pub(crate) fn pack_pixels(pixels: impl IntoIterator<Item = bool>) -> Vec<u8> {
+    let pixels: Vec<_> = pixels.into_iter().collect();
+
+    pixels
+        .chunks(8)
+        .map(|chunk| {
+            let mut output = 0u8;
+            for (i, &value) in chunk.iter().enumerate() {
+                if value {
+                    output |= 1 << i;
+                }
+            }
+
+            output
+        })
+        .collect()
+}
+
+/// The glyphs of a font, whether from a TTF or a bitmap font.
+pub(crate) struct FontData {
+    /// Sorted by character
+    pub letters: Vec<(char, LetterData)>,
+    pub fallback: LetterData,
+    /// How far to move the second character of each pair when it follows the
+    /// first, sorted by the pair
+    pub kerning: Vec<(char, char, i32)>,
+    pub line_height: i32,
+    pub ascent: i32,
+}
+
+impl FontData {
+    /// Keeps only the glyphs for the given characters.
+    pub(crate) fn retain(&mut self, characters: &[char]) {
+        self.letters
+            .retain(|(c, _)| characters.binary_search(c).is_ok());
+        self.retain_kerning();
+    }
+
+    /// Sorts the kerning pairs and removes any which are zero or use a
+    /// character without a glyph.
+    pub(crate) fn retain_kerning(&mut self) {
+        let letters = &self.letters;
+        let has_glyph = |c: &char| letters.binary_search_by_key(c, |&(c, _)| c).is_ok();
+
+        self.kerning.retain(|(first, second, amount)| {
+            *amount != 0 && has_glyph(first) && has_glyph(second)
+        });
+        self.kerning
+            .sort_by_key(|&(first, second, _)| (first, second));
+        self.kerning
+            .dedup_by_key(|&mut (first, second, _)| (first, second));
+    }
+
+    pub(crate) fn to_tokens(&self) -> TokenStream {
+        let characters = self.letters.iter().map(|(c, _)| c);
+        let letters = self.letters.iter().map(|(_, letter)| letter_tokens(letter));
+        let fallback = letter_tokens(&self.fallback);
+        let (line_height, ascent) = (self.line_height, self.ascent);
+
+        let font = quote![
+            display::Font::new(&[#(#characters),*], &[#(#letters),*], &#fallback, #line_height, #ascent)
+        ];
+
+        if self.kerning.is_empty() {
+            return font;
+        }
+
+        let kerning = self.kerning.iter().map(|&(first, second, amount)| {
+            let amount = amount.clamp(i8::MIN.into(), i8::MAX.into()) as i8;
+            quote!((#first, #second, #amount))
+        });
+
+        quote!(#font.with_kerning(&[#(#kerning),*]))
+    }
+}
+
+fn letter_tokens(letter_data: &LetterData) -> TokenStream {
+    let data_raw = ByteString(&letter_data.rendered);
+    let height = letter_data.height as u8;
+    let width = letter_data.width as u8;
+    let xmin = letter_data.xmin as i8;
+    let ymin = letter_data.ymin as i8;
+    let advance_width = letter_data.advance_width.ceil() as u8;
+
+    quote!(
+        display::FontLetter::new(
+            #width,
+            #height,
+            #data_raw,
+            #xmin,
+            #ymin,
+            #advance_width,
+        )
+    )
+}
+
+/// Rasterises the glyphs for the characters, which must be sorted. Characters
+/// which the font doesn't have are left out, and are drawn with the font's
+/// fallback glyph instead.
+pub fn load_font(font_data: &[u8], pixels_per_em: f32, characters: &[char]) -> FontData {
+    let font = fontdue::Font::from_bytes(
+        font_data,
+        fontdue::FontSettings {
+            collection_index: 0,
+            scale: pixels_per_em,
+        },
+    )
+    .expect("Invalid font data");
+
+    let line_metrics = font.horizontal_line_metrics(pixels_per_em).unwrap();
+
+    let line_height = line_metrics.new_line_size as i32;
+    let mut ascent = line_metrics.ascent as i32;
+
+    let rasterise = |(metrics, bitmap): (fontdue::Metrics, Vec<u8>)| LetterData {
+        width: metrics.width,
+        height: metrics.height,
+        rendered: pack_pixels(bitmap.into_iter().map(|value| value > 100)),
+        xmin: metrics.xmin,
+        ymin: metrics.ymin,
+        advance_width: metrics.advance_width,
+    };
+
+    let letters: Vec<_> = characters
+        .iter()
+        .copied()
+        .filter(|&c| font.lookup_glyph_index(c) != 0)
+        .map(|c| (c, rasterise(font.rasterize(c, pixels_per_em))))
+        .collect();
+
+    let fallback = rasterise(font.rasterize_indexed(0, pixels_per_em));
+
+    let maximum_above_line = letters
+        .iter()
+        .map(|(_, letter)| letter)
+        .chain([&fallback])
+        .map(|x| (x.height as i32 + x.ymin))
+        .max()
+        .unwrap();
+
+    if (ascent - maximum_above_line) < 0 {
+        ascent = maximum_above_line;
+    }
+
+    let kerning = kerning_pairs(font_data, &font, &letters, pixels_per_em);
+
+    let mut font_data = FontData {
+        letters,
+        fallback,
+        kerning,
+        line_height,
+        ascent,
+    };
+    font_data.retain_kerning();
+
+    font_data
+}
+
+/// The kerning between the letters, scaled to the font size. This goes
+/// through the pairs listed in the font's `kern` table rather than trying
+/// every pair of letters, which would be far too slow for fonts with thousands
+/// of glyphs. As in fontdue, only the first horizontal list of pairs is used.
+fn kerning_pairs(
+    font_data: &[u8],
+    font: &fontdue::Font,
+    letters: &[(char, LetterData)],
+    pixels_per_em: f32,
+) -> Vec<(char, char, i32)> {
+    let Some(kern) = ttf_parser::Face::from_slice(font_data, 0)
+        .ok()
+        .and_then(|face| face.tables().kern)
+    else {
+        return Vec::new();
+    };
+
+    let Some(pairs) = kern
+        .subtables
+        .into_iter()
+        .find_map(|subtable| match subtable.format {
+            ttf_parser::kern::Format::Format0(table) if subtable.horizontal => Some(table.pairs),
+            _ => None,
+        })
+    else {
+        return Vec::new();
+    };
+
+    let glyphs = letters
+        .iter()
+        .map(|&(c, _)| (c, font.lookup_glyph_index(c)));
+
+    kerning_for_glyphs(
+        glyphs,
+        pairs
+            .into_iter()
+            .map(|pair| (pair.left().0, pair.right().0, pair.value)),
+        font.scale_factor(pixels_per_em),
+    )
+}
+
+/// Turns kerning between pairs of glyph indices into kerning between the
+/// characters which use those glyphs.
+fn kerning_for_glyphs(
+    glyphs: impl Iterator<Item = (char, u16)>,
+    pairs: impl Iterator<Item = (u16, u16, i16)>,
+    scale: f32,
+) -> Vec<(char, char, i32)> {
+    // several characters can share a glyph
+    let mut characters: HashMap<u16, Vec<char>> = HashMap::new();
+    for (c, glyph) in glyphs {
+        characters.entry(glyph).or_default().push(c);
+    }
+
+    let mut kerning = Vec::new();
+
+    for (first, second, value) in pairs {
+        let (Some(firsts), Some(seconds)) = (characters.get(&first), characters.get(&second))
+        else {
+            continue;
+        };
+
+        let amount = (f32::from(value) * scale).round() as i32;
+        for &first in firsts {
+            kerning.extend(seconds.iter().map(|&second| (first, second, amount)));
+        }
+    }
+
+    kerning
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_glyphs_are_ascii() {
+        let input: FontInput = syn::parse_str(r#""font.ttf", 12"#).unwrap();
+        assert!(input.options.glyphs.is_default());
+
+        let (characters, files) = input.options.glyphs.characters(Path::new("")).unwrap();
+        assert_eq!(
+            characters,
+            (0..128).filter_map(char::from_u32).collect::<Vec<_>>()
+        );
+        assert!(files.is_empty());
+    }
+
+    #[test]
+    fn ranges_and_files_choose_the_glyphs() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("characters.txt");
+        std::fs::write(&file, "c\u{e9}\u{3042}\n").unwrap();
+
+        let input: FontInput = syn::parse_str(&format!(
+            r#""font.ttf", 10.5, ranges: ['a'..='c', 'x'], characters_from: [{:?}],"#,
+            file.to_str().unwrap()
+        ))
+        .unwrap();
+        assert_eq!(input.options.size, Some(10.5));
+
+        let (characters, _) = input.options.glyphs.characters(Path::new("")).unwrap();
+        assert_eq!(characters, ['a', 'b', 'c', 'x', '\u{e9}', '\u{3042}']);
+    }
+
+    #[test]
+    fn missing_character_files_are_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("missing.txt");
+
+        let input: FontInput = syn::parse_str(&format!(
+            r#""font.ttf", 12, characters_from: [{:?}]"#,
+            file.to_str().unwrap()
+        ))
+        .unwrap();
+
+        assert!(input.options.glyphs.characters(Path::new("")).is_err());
+    }
+
+    #[test]
+    fn string_tables_replace_the_default_glyphs() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("strings.toml");
+        std::fs::write(&file, "title = \"Caf\u{e9}\"\n[menu]\nstart = \"Go!\"\n").unwrap();
+
+        let input: FontInput = syn::parse_str(&format!(
+            r#""font.ttf", 12, strings_from: [{:?}]"#,
+            file.to_str().unwrap()
+        ))
+        .unwrap();
+        assert!(!input.options.glyphs.is_default());
+
+        let (characters, files) = input.options.glyphs.characters(Path::new("")).unwrap();
+        assert_eq!(characters, ['!', 'C', 'G', 'a', 'f', 'o', '\u{e9}']);
+        assert_eq!(files, [file]);
+    }
+
+    #[test]
+    fn bitmap_fonts_have_grid_settings() {
+        let input: FontInput = syn::parse_str(
+            r#""font.png", cell_size: (8, 12), characters: "ab", baseline: 9, letter_spacing: 0"#,
+        )
+        .unwrap();
+
+        assert_eq!(input.options.size, None);
+        assert_eq!(input.options.grid.cell_size, Some((8, 12)));
+        assert_eq!(input.options.grid.characters.as_deref(), Some("ab"));
+        assert_eq!(input.options.grid.baseline, Some(9));
+        assert_eq!(input.options.grid.letter_spacing, 0);
+    }
+
+    #[test]
+    fn kerning_is_sorted_and_only_for_included_glyphs() {
+        let mut font = FontData {
+            letters: ['A', 'V', 'W'].map(|c| (c, LetterData::empty(4.0))).into(),
+            fallback: LetterData::empty(4.0),
+            kerning: vec![
+                ('W', 'A', -1),
+                ('A', 'V', -2),
+                ('A', 'W', 0),
+                ('A', 'x', -1),
+            ],
+            line_height: 8,
+            ascent: 6,
+        };
+
+        font.retain_kerning();
+        assert_eq!(font.kerning, [('A', 'V', -2), ('W', 'A', -1)]);
+
+        font.retain(&['A', 'V']);
+        assert_eq!(font.kerning, [('A', 'V', -2)]);
+    }
+
+    #[test]
+    fn unknown_settings_are_rejected() {
+        assert!(syn::parse_str::<FontInput>(r#""font.ttf", 12, glyphs: []"#).is_err());
+        assert!(syn::parse_str::<FontInput>(r#""font.ttf", 12, ranges: ['z'..='a']"#).is_err());
+    }
+
+    #[test]
+    fn kerning_pairs_are_looked_up_by_glyph() {
+        let glyphs = [('A', 1), ('V', 2), ('\u{c0}', 1), ('W', 3)];
+        let pairs = [(1, 2, -20), (3, 1, 10), (2, 9, -50)];
+
+        let mut kerning = kerning_for_glyphs(glyphs.into_iter(), pairs.into_iter(), 0.1);
+        kerning.sort_unstable();
+
+        assert_eq!(
+            kerning,
+            [
+                ('A', 'V', -2),
+                ('W', 'A', 1),
+                ('W', '\u{c0}', 1),
+                ('\u{c0}', 'V', -2)
+            ]
+        );
+    }
+}
@@ -12,16 +12,17 @@ pub(crate) struct Image {
 }
 
 impl Image {
-    pub fn load_from_file(image_path: &path::Path) -> Self {
+    pub fn load_from_file(image_path: &path::Path) -> Result<Self, String> {
         let img = if image_path.extension() == Some(OsStr::new("aseprite")) {
-            let ase =
-                asefile::AsepriteFile::read_file(image_path).expect("failed to read aseprite file");
+            let ase = asefile::AsepriteFile::read_file(image_path)
+                .map_err(|e| format!("Failed to read {}: {e}", image_path.display()))?;
             DynamicImage::ImageRgba8(ase.frame(0).image())
         } else {
-            image::open(image_path).expect("Expected image to exist")
+            image::open(image_path)
+                .map_err(|e| format!("Failed to load {}: {e}", image_path.display()))?
         };
 
-        Self::load_from_dyn_image(img)
+        Ok(Self::load_from_dyn_image(img))
     }
 
     pub fn load_from_dyn_image(img: image::DynamicImage) -> Self {
@@ -54,4 +55,8 @@ impl Image {
     pub fn colour(&self, x: usize, y: usize) -> Colour {
         self.colour_data[x + y * self.width]
     }
+
+    pub fn set_colour(&mut self, x: usize, y: usize, colour: Colour) {
+        self.colour_data[x + y * self.width] = colour;
+    }
 }
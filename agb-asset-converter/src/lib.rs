@@ -0,0 +1,1716 @@
+//! Converts images, fonts, sounds and music into the formats used by
+//! [agb](https://docs.rs/agb). This is what agb's `include_*` macros use, and
+//! it can also be used directly from a build script to write the converted
+//! assets to `OUT_DIR`, either as Rust source or as binary files.
+//!
+//! * [`BackgroundConverter`] converts background images, the same as
+//!   `include_background_gfx!`.
+//! * [`SpriteConverter`] converts aseprite files, the same as
+//!   `include_aseprite!`.
+//! * [`convert_wav`] converts wav files, the same as `include_wav!`.
+//! * `convert_xm` converts XM tracker files, the same as `include_xm!`, and
+//!   `convert_xm_with` gives the converted track itself. These need the `xm`
+//!   feature.
+//!
+//! File names are relative to the root of the crate being built, in the same
+//! way as for the macros.
+
+use palette16::{Palette16OptimisationResults, Palette16Optimiser};
+use palette256::Palette256;
+use proc_macro2::Literal;
+use proc_macro2::TokenStream;
+use syn::parse::Parse;
+use syn::Token;
+use syn::{punctuated::Punctuated, LitStr};
+
+use std::borrow::{Borrow, Cow};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::{path::Path, str};
+
+use quote::{format_ident, quote, ToTokens};
+
+mod aseprite;
+mod assets;
+mod background;
+mod bitmap;
+mod bitmap_font;
+mod colour;
+mod config;
+mod deduplicator;
+mod font_loader;
+mod image_loader;
+mod palette16;
+mod palette256;
+mod palette_variant;
+mod pinned_palettes;
+mod quantise;
+mod rotation;
+mod rust_generator;
+mod sprite;
+mod spritesheet;
+mod strings;
+mod wav;
+#[cfg(feature = "xm")]
+mod xm;
+
+pub use background::{
+    BackgroundConverter, ConvertedBackground, ConvertedBackgrounds, TileFormat, TileSetting,
+};
+pub use quantise::ColourLoss;
+pub use sprite::{ConvertedSprite, ConvertedSprites, ConvertedTag, SpriteConverter};
+pub use strings::include_strings;
+pub use wav::{convert_wav, include_wav};
+#[cfg(feature = "xm")]
+pub use xm::{convert_xm, convert_xm_with, Track};
+
+use image::GenericImageView;
+use image_loader::Image;
+
+use colour::Colour;
+
+/// An error from converting assets, such as an invalid setting or an image
+/// which doesn't fit the palettes.
+#[derive(Debug)]
+pub struct Error(String);
+
+impl Error {
+    pub(crate) fn new(message: impl fmt::Display) -> Self {
+        Self(message.to_string())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<syn::Error> for Error {
+    fn from(error: syn::Error) -> Self {
+        Self::new(error)
+    }
+}
+
+/// The values as little endian bytes.
+fn u16_bytes(values: impl Iterator<Item = u16>) -> Vec<u8> {
+    values.flat_map(u16::to_le_bytes).collect()
+}
+
+/// The number of colours a background image uses.
+#[derive(Debug, Clone, Copy)]
+pub enum Colours {
+    /// Each tile uses one of the 16 colour palettes.
+    Colours16,
+    /// Every tile uses the same 256 colour palette.
+    Colours256,
+}
+
+struct BackgroundGfxOption {
+    module_name: String,
+    file_name: String,
+    colours: Colours,
+    deduplicate: bool,
+}
+
+impl config::Image for BackgroundGfxOption {
+    fn filename(&self) -> String {
+        self.file_name.clone()
+    }
+
+    fn colours(&self) -> Colours {
+        self.colours
+    }
+
+    fn deduplicate(&self) -> bool {
+        self.deduplicate
+    }
+}
+
+impl Parse for BackgroundGfxOption {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let module_name: syn::Ident = input.parse()?;
+        let _: Token![=>] = input.parse()?;
+
+        let lookahead = input.lookahead1();
+
+        let colours = if lookahead.peek(syn::LitInt) {
+            let num_colours: syn::LitInt = input.parse()?;
+
+            match num_colours.base10_parse()? {
+                16 => Colours::Colours16,
+                256 => Colours::Colours256,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        num_colours,
+                        "Number of colours must be 16 or 256",
+                    ))
+                }
+            }
+        } else {
+            Colours::Colours16
+        };
+
+        let lookahead = input.lookahead1();
+
+        let deduplicate = if lookahead.peek(syn::Ident) {
+            let deduplicate: syn::Ident = input.parse()?;
+
+            if deduplicate == "deduplicate" {
+                true
+            } else {
+                return Err(syn::Error::new_spanned(
+                    deduplicate,
+                    "Must either be the literal deduplicate or missing",
+                ));
+            }
+        } else {
+            false
+        };
+
+        let file_name: syn::LitStr = input.parse()?;
+
+        Ok(Self {
+            module_name: module_name.to_string(),
+            file_name: file_name.value(),
+            colours,
+            deduplicate,
+        })
+    }
+}
+
+struct IncludeBackgroundGfxInput {
+    module_name: syn::Ident,
+    crate_prefix: String,
+    transparent_colour: Colour,
+    palette_variants: Vec<(syn::Ident, palette_variant::PaletteVariant)>,
+    quantise: Option<quantise::Quantise>,
+    palettes: Option<pinned_palettes::PinnedPalettes>,
+    background_gfx_options: Vec<BackgroundGfxOption>,
+}
+
+impl Parse for IncludeBackgroundGfxInput {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let lookahead = input.lookahead1();
+
+        let crate_prefix: syn::Ident = if lookahead.peek(Token![crate]) {
+            let _: Token![crate] = input.parse()?;
+            let _: Token![,] = input.parse()?;
+            format_ident!("crate")
+        } else {
+            format_ident!("agb")
+        };
+
+        let module_name: syn::Ident = input.parse()?;
+        let _: Token![,] = input.parse()?;
+
+        let lookahead = input.lookahead1();
+        let transparent_colour: Colour = if lookahead.peek(syn::LitStr) {
+            let colour_str: syn::LitStr = input.parse()?;
+            let _: Token![,] = input.parse()?;
+            colour_str
+                .value()
+                .parse()
+                .map_err(|msg| syn::Error::new_spanned(colour_str, msg))?
+        } else {
+            Colour::from_rgb(255, 0, 255, 0)
+        };
+
+        let mut palette_variants = Vec::new();
+        let mut quantise = None;
+        let mut palettes = None;
+
+        while input.peek(syn::Ident) && input.peek2(Token![:]) {
+            let setting: syn::Ident = input.parse()?;
+            let _: Token![:] = input.parse()?;
+
+            match setting.to_string().as_str() {
+                "palette_variants" => palette_variants = palette_variant::parse_variants(input)?,
+                "quantise" => quantise = Some(input.parse()?),
+                "palettes" => palettes = Some(input.parse()?),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        setting,
+                        "Unknown setting, expected palette_variants, quantise or palettes",
+                    ))
+                }
+            }
+
+            let _: Token![,] = input.parse()?;
+        }
+
+        let background_gfx_options =
+            input.parse_terminated(BackgroundGfxOption::parse, Token![,])?;
+
+        Ok(Self {
+            module_name,
+            crate_prefix: crate_prefix.to_string(),
+            transparent_colour,
+            palette_variants,
+            quantise,
+            palettes,
+            background_gfx_options: background_gfx_options.into_iter().collect(),
+        })
+    }
+}
+
+impl config::Config for IncludeBackgroundGfxInput {
+    fn crate_prefix(&self) -> String {
+        self.crate_prefix.clone()
+    }
+
+    fn images(&self) -> HashMap<String, &dyn config::Image> {
+        self.background_gfx_options
+            .iter()
+            .map(|options| (options.module_name.clone(), options as &dyn config::Image))
+            .collect()
+    }
+
+    fn transparent_colour(&self) -> Option<Colour> {
+        Some(self.transparent_colour)
+    }
+
+    fn palette_variants(&self) -> &[(syn::Ident, palette_variant::PaletteVariant)] {
+        &self.palette_variants
+    }
+
+    fn quantise(&self) -> Option<quantise::Quantise> {
+        self.quantise
+    }
+
+    fn pinned_palettes(&self) -> Option<&pinned_palettes::PinnedPalettes> {
+        self.palettes.as_ref()
+    }
+}
+
+#[doc(hidden)]
+pub fn include_background_gfx(input: TokenStream) -> TokenStream {
+    let config = match syn::parse2::<IncludeBackgroundGfxInput>(input) {
+        Ok(config) => Box::new(config),
+        Err(e) => return e.into_compile_error(),
+    };
+
+    let root = match manifest_dir() {
+        Ok(root) => root,
+        Err(e) => return e.into_compile_error(),
+    };
+
+    let module_name = config.module_name.clone();
+    include_gfx_from_config(
+        config,
+        module_name,
+        &syn::Visibility::Inherited,
+        Path::new(&root),
+    )
+}
+
+fn include_gfx_from_config(
+    config: Box<dyn config::Config>,
+    module_name: syn::Ident,
+    visibility: &syn::Visibility,
+    parent: &Path,
+) -> TokenStream {
+    let (optimisation_results, images, colour_loss) =
+        match convert_backgrounds(config.as_ref(), parent) {
+            Ok(converted) => converted,
+            Err(e) => return e.into_compile_error(),
+        };
+
+    let items = background_items(config.as_ref(), &optimisation_results, &images);
+    let warning = colour_loss.map(|loss| loss.warning(&module_name.to_string()));
+
+    quote! {
+        #visibility mod #module_name {
+            #items
+            #warning
+        }
+    }
+}
+
+/// Finds the palettes for the background images and splits each of them into
+/// tiles, along with how much the colours changed if they were quantised.
+fn convert_backgrounds(
+    config: &dyn config::Config,
+    parent: &Path,
+) -> syn::Result<(
+    Palette16OptimisationResults,
+    Vec<background::ConvertedBackground>,
+    Option<quantise::ColourLoss>,
+)> {
+    let images = config.images();
+
+    let mut loaded_images: HashMap<&String, Image> = images
+        .iter()
+        .map(|(name, settings)| {
+            let image = Image::load_from_file(&parent.join(settings.filename()))
+                .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), e))?;
+            Ok((name, image))
+        })
+        .collect::<syn::Result<_>>()?;
+
+    let mut assignment_offsets = HashMap::new();
+    let mut assignment_offset = 0;
+
+    for (name, settings) in images.iter() {
+        let image = &loaded_images[name];
+
+        if let Colours::Colours16 = settings.colours() {
+            let tile_size = 8;
+            if image.width % tile_size != 0 || image.height % tile_size != 0 {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    format!("Image {name} size not a multiple of tile size"),
+                ));
+            }
+
+            let num_tiles = image.width * image.height / 8usize.pow(2);
+            assignment_offsets.insert(name, assignment_offset);
+            assignment_offset += num_tiles;
+        }
+    }
+
+    let mut colour_loss = None;
+    let optimisation_results = if let Some(quantise) = config.quantise() {
+        let (results, loss) = quantise_background_images(
+            quantise,
+            config.pinned_palettes(),
+            &images,
+            &mut loaded_images,
+            config.transparent_colour(),
+        );
+        colour_loss = Some(loss);
+        results
+    } else if let Some(pinned) = config.pinned_palettes() {
+        pinned_background_results(pinned, &images, &loaded_images, config.transparent_colour())?
+    } else {
+        let mut optimiser = Palette16Optimiser::new(config.transparent_colour());
+        let mut palette256 = Palette256::new();
+
+        for (name, settings) in images.iter() {
+            let image = &loaded_images[name];
+
+            match settings.colours() {
+                Colours::Colours16 => {
+                    add_to_optimiser(&mut optimiser, image, 8, 8, config.transparent_colour());
+                }
+                Colours::Colours256 => {
+                    palette256.add_image(image);
+                }
+            }
+        }
+
+        palette256.extend_results(&optimiser.optimise_palettes())
+    };
+
+    let converted_images = images
+        .iter()
+        .map(|(image_name, &image)| {
+            let assignment_offset = match image.colours() {
+                Colours::Colours16 => Some(assignment_offsets[image_name]),
+                _ => None,
+            };
+
+            rust_generator::convert_image(
+                image_name,
+                &optimisation_results,
+                &loaded_images[image_name],
+                &parent.join(image.filename()),
+                assignment_offset,
+                image.deduplicate(),
+            )
+        })
+        .collect();
+
+    Ok((optimisation_results, converted_images, colour_loss))
+}
+
+/// The palettes, palette variants and images of a module generated by
+/// `include_background_gfx!`.
+fn background_items(
+    config: &dyn config::Config,
+    optimisation_results: &Palette16OptimisationResults,
+    images: &[background::ConvertedBackground],
+) -> TokenStream {
+    let image_code = images
+        .iter()
+        .map(|image| rust_generator::generate_code(image, &config.crate_prefix()));
+
+    let palette_code =
+        rust_generator::generate_palette_code(optimisation_results, &config.crate_prefix());
+
+    let palette_variant_code = config.palette_variants().iter().map(|(name, variant)| {
+        rust_generator::generate_palette_variant_code(
+            optimisation_results,
+            &config.crate_prefix(),
+            name,
+            variant,
+        )
+    });
+
+    let palette_file = config.pinned_palettes().map(|pinned| {
+        let file_name = pinned.file_name.to_string_lossy();
+        quote! {
+            const _: &[u8] = include_bytes!(#file_name);
+        }
+    });
+
+    quote! {
+        #palette_file
+
+        #palette_code
+
+        #(#palette_variant_code)*
+
+        #(#image_code)*
+    }
+}
+
+use quote::TokenStreamExt;
+struct ByteString<'a>(&'a [u8]);
+impl ToTokens for ByteString<'_> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.append(Literal::byte_string(self.0));
+    }
+}
+
+#[doc(hidden)]
+pub fn include_colours_inner(input: TokenStream) -> TokenStream {
+    let input_filename = match syn::parse2::<LitStr>(input) {
+        Ok(input_filename) => input_filename,
+        Err(e) => return e.into_compile_error(),
+    };
+    let input_filename = input_filename.value();
+
+    let root = match manifest_dir() {
+        Ok(root) => root,
+        Err(e) => return e.into_compile_error(),
+    };
+    let input_filename = Path::new(&root).join(input_filename);
+
+    let image = match Image::load_from_file(Path::new(&input_filename)) {
+        Ok(image) => image,
+        Err(e) => return syn::Error::new(proc_macro2::Span::call_site(), e).into_compile_error(),
+    };
+
+    let mut palette_data = Vec::with_capacity(image.width * image.height);
+    for y in 0..image.height {
+        for x in 0..image.width {
+            palette_data.push(image.colour(x, y).to_rgb15())
+        }
+    }
+
+    let palette_data = palette_data
+        .iter()
+        .map(|colour| quote! { Rgb15::new(#colour) });
+
+    let filename = input_filename.to_string_lossy();
+
+    quote! {
+        {
+            const _: &[u8] = include_bytes!(#filename);
+            [#(#palette_data),*]
+        }
+    }
+}
+
+#[doc(hidden)]
+pub fn include_assets(input: TokenStream) -> TokenStream {
+    let input = match syn::parse2::<assets::IncludeAssetsInput>(input) {
+        Ok(input) => input,
+        Err(e) => return e.into_compile_error(),
+    };
+
+    input
+        .generate()
+        .unwrap_or_else(syn::Error::into_compile_error)
+}
+
+#[doc(hidden)]
+pub fn include_bitmap_inner(input: TokenStream) -> TokenStream {
+    let input = match syn::parse2::<bitmap::BitmapInput>(input) {
+        Ok(input) => input,
+        Err(e) => return e.into_compile_error(),
+    };
+
+    input
+        .generate()
+        .unwrap_or_else(syn::Error::into_compile_error)
+}
+
+/// An aseprite file passed to `include_aseprite!`, optionally followed by a
+/// block of settings such as `"player.aseprite" { layers: ["body"] }`.
+struct AsepriteFileOption {
+    file_name: String,
+    layers: aseprite::LayerSelection,
+    tag_prefix: String,
+    rotations: usize,
+    palette_variants: Vec<(String, palette_variant::PaletteVariant)>,
+    quantise: Option<quantise::Quantise>,
+}
+
+impl Parse for AsepriteFileOption {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let file_name: LitStr = input.parse()?;
+
+        let mut option = Self {
+            file_name: file_name.value(),
+            layers: Default::default(),
+            tag_prefix: String::new(),
+            rotations: 1,
+            palette_variants: Vec::new(),
+            quantise: None,
+        };
+
+        if !input.peek(syn::token::Brace) {
+            return Ok(option);
+        }
+
+        let content;
+        syn::braced!(content in input);
+
+        while !content.is_empty() {
+            let setting: syn::Ident = content.parse()?;
+            let _: Token![:] = content.parse()?;
+
+            match setting.to_string().as_str() {
+                "layers" => option.layers.include = Some(parse_string_list(&content)?),
+                "exclude_layers" => option.layers.exclude = parse_string_list(&content)?,
+                "tag_prefix" => option.tag_prefix = content.parse::<LitStr>()?.value(),
+                "rotations" => {
+                    let rotations: syn::LitInt = content.parse()?;
+                    option.rotations = rotations.base10_parse()?;
+                    if option.rotations == 0 {
+                        return Err(syn::Error::new_spanned(
+                            rotations,
+                            "Must have at least one rotation",
+                        ));
+                    }
+                }
+                "palette_variants" => {
+                    option.palette_variants =
+                        palette_variant::parse_variants::<LitStr>(&content)?
+                            .into_iter()
+                            .map(|(name, variant)| (name.value(), variant))
+                            .collect();
+                }
+                "quantise" => option.quantise = Some(content.parse()?),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        setting,
+                        "Unknown setting, expected layers, exclude_layers, tag_prefix, rotations, palette_variants or quantise",
+                    ))
+                }
+            }
+
+            if content.is_empty() {
+                break;
+            }
+
+            let _: Token![,] = content.parse()?;
+        }
+
+        Ok(option)
+    }
+}
+
+fn parse_string_list(input: syn::parse::ParseStream) -> syn::Result<Vec<String>> {
+    let content;
+    syn::bracketed!(content in input);
+
+    let strings = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?;
+    Ok(strings.iter().map(LitStr::value).collect())
+}
+
+/// The input to `include_aseprite!`, which is an optional `palettes` setting
+/// followed by the aseprite files.
+struct IncludeAsepriteInput {
+    palettes: Option<pinned_palettes::PinnedPalettes>,
+    files: Punctuated<AsepriteFileOption, Token![,]>,
+}
+
+impl Parse for IncludeAsepriteInput {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut palettes = None;
+
+        while input.peek(syn::Ident) && input.peek2(Token![:]) {
+            let setting: syn::Ident = input.parse()?;
+            let _: Token![:] = input.parse()?;
+
+            match setting.to_string().as_str() {
+                "palettes" => palettes = Some(input.parse()?),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        setting,
+                        "Unknown setting, expected palettes",
+                    ))
+                }
+            }
+
+            let _: Token![,] = input.parse()?;
+        }
+
+        Ok(Self {
+            palettes,
+            files: Punctuated::parse_terminated(input)?,
+        })
+    }
+}
+
+#[doc(hidden)]
+pub fn include_aseprite_inner(input: TokenStream) -> TokenStream {
+    let input = match syn::parse2::<IncludeAsepriteInput>(input) {
+        Ok(input) => input,
+        Err(e) => return e.into_compile_error(),
+    };
+    let files: Vec<_> = input.files.into_iter().collect();
+
+    aseprite_graphics(&files, input.palettes.as_ref())
+        .unwrap_or_else(syn::Error::into_compile_error)
+}
+
+/// Loads the frames and tags of the aseprite files and generates their sprites
+/// with [`sprite_graphics`].
+fn aseprite_graphics(
+    files: &[AsepriteFileOption],
+    palettes: Option<&pinned_palettes::PinnedPalettes>,
+) -> syn::Result<TokenStream> {
+    let (frames, tags, filenames) = load_aseprite_files(files, palettes)?;
+    sprite_graphics(&frames, &tags, &filenames, palettes)
+}
+
+/// The frames and tags of the aseprite files, along with every file the
+/// sprites are made from.
+fn load_aseprite_files(
+    files: &[AsepriteFileOption],
+    palettes: Option<&pinned_palettes::PinnedPalettes>,
+) -> syn::Result<(Vec<SpriteFrame>, Vec<SpriteTag>, Vec<PathBuf>)> {
+    let mut frames = Vec::new();
+    let mut tags = Vec::new();
+
+    let root = manifest_dir()?;
+
+    let filenames: Vec<PathBuf> = files
+        .iter()
+        .map(|option| Path::new(&root).join(&option.file_name))
+        .collect();
+
+    for (filename, option) in filenames.iter().zip(files.iter()) {
+        let (file_frames, file_tags) = aseprite::generate_from_file(filename, &option.layers)
+            .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), e))?;
+
+        let rotations = (option.rotations > 1).then_some((option.rotations, file_frames.len()));
+        let palette_variants: Rc<[_]> = option.palette_variants.clone().into();
+
+        for tag in file_tags {
+            let name = format!("{}{}", option.tag_prefix, tag.name());
+
+            tags.push(SpriteTag {
+                start: tag.from_frame() as usize + frames.len(),
+                end: tag.to_frame() as usize + frames.len(),
+                direction: tag.animation_direction() as usize,
+                user_data: tag.user_data().and_then(|user_data| user_data.text.clone()),
+                rotations,
+                name,
+            });
+        }
+
+        // Each rotation of every frame follows the unrotated frames, so the
+        // frames of a tag at any one rotation are next to each other.
+        let rotated_frames: Vec<_> = (1..option.rotations)
+            .flat_map(|rotation| {
+                let turns = rotation as f64 / option.rotations as f64;
+                file_frames.iter().map(move |frame| aseprite::Frame {
+                    image: rotation::rotate(&frame.image, turns),
+                    duration: frame.duration,
+                    slices: rotation::rotate_slices(&frame.slices, frame.image.dimensions(), turns),
+                    cel_user_data: frame.cel_user_data.clone(),
+                })
+            })
+            .collect();
+
+        for frame in file_frames.into_iter().chain(rotated_frames) {
+            let width = frame.image.width();
+            let height = frame.image.height();
+            if !valid_sprite_size(width, height) {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    format!(
+                        "File {} contains sprites with size {}x{} which cannot be represented on the GameBoy Advance",
+                        filename.display(),
+                        width,
+                        height
+                    ),
+                ));
+            }
+
+            frames.push(SpriteFrame {
+                duration: frame.duration_in_frames(),
+                image: Image::load_from_dyn_image(frame.image),
+                slices: frame.slices,
+                cel_user_data: frame.cel_user_data,
+                palette_variants: palette_variants.clone(),
+                quantise: option.quantise,
+            });
+        }
+    }
+
+    let remap_filenames = files.iter().flat_map(|option| {
+        option
+            .palette_variants
+            .iter()
+            .filter_map(|(_, variant)| variant.file_name().map(Path::to_path_buf))
+    });
+    let palette_filename = palettes.iter().map(|pinned| pinned.file_name.clone());
+    let filenames: Vec<_> = filenames
+        .iter()
+        .cloned()
+        .chain(remap_filenames)
+        .chain(palette_filename)
+        .collect();
+
+    Ok((frames, tags, filenames))
+}
+
+/// A static with the sprites generated by [`sprite_graphics`], the same as
+/// the expression `include_aseprite!` expands to.
+fn graphics_static(
+    crate_prefix: &syn::Ident,
+    static_name: &syn::Ident,
+    graphics: &TokenStream,
+) -> TokenStream {
+    quote! {
+        pub static #static_name: &#crate_prefix::display::object::Graphics = {
+            #[allow(unused_imports)]
+            use #crate_prefix::display::object::{Size, Slice, Sprite, Tag, TagMap, Graphics};
+            use #crate_prefix::display::{palette16::Palette16, Rgb15};
+            use #crate_prefix::align_bytes;
+
+            #graphics
+
+            &Graphics::new(SPRITES, TAGS)
+        };
+    }
+}
+
+#[doc(hidden)]
+pub fn include_spritesheet_inner(input: TokenStream) -> TokenStream {
+    let option = match syn::parse2::<spritesheet::SpritesheetOption>(input) {
+        Ok(option) => option,
+        Err(e) => return e.into_compile_error(),
+    };
+
+    let root = match manifest_dir() {
+        Ok(root) => root,
+        Err(e) => return e.into_compile_error(),
+    };
+    let filename = Path::new(&root).join(&option.file_name);
+
+    let images = match spritesheet::generate_from_file(&filename, &option) {
+        Ok(images) => images,
+        Err(e) => return e.to_compile_error(),
+    };
+
+    let frames: Vec<_> = images
+        .into_iter()
+        .map(|image| SpriteFrame {
+            image: Image::load_from_dyn_image(image),
+            duration: 1,
+            slices: Vec::new(),
+            cel_user_data: Vec::new(),
+            palette_variants: Rc::new([]),
+            quantise: None,
+        })
+        .collect();
+
+    let tags: Vec<_> = option
+        .tags
+        .iter()
+        .map(|tag| SpriteTag {
+            name: tag.name.clone(),
+            start: tag.from_frame,
+            end: tag.to_frame,
+            direction: tag.direction,
+            user_data: None,
+            rotations: None,
+        })
+        .collect();
+
+    sprite_graphics(&frames, &tags, &[filename], None)
+        .unwrap_or_else(syn::Error::into_compile_error)
+}
+
+/// A single frame which will become a `Sprite` in the generated code.
+struct SpriteFrame {
+    image: Image,
+    duration: u16,
+    slices: Vec<aseprite::FrameSlice>,
+    cel_user_data: Vec<(String, String)>,
+    palette_variants: Rc<[(String, palette_variant::PaletteVariant)]>,
+    /// Whether the frame should have its colours reduced to fit the palettes
+    quantise: Option<quantise::Quantise>,
+}
+
+/// A named range of frames which will become a `Tag` in the generated code.
+struct SpriteTag {
+    name: String,
+    start: usize,
+    end: usize,
+    direction: usize,
+    user_data: Option<String>,
+    /// The number of rotations and the distance between each rotation of a
+    /// frame, if the frames were pre-rotated
+    rotations: Option<(usize, usize)>,
+}
+
+/// Generates the `PALETTES`, `PALETTE_VARIANTS`, `SPRITES` and `TAGS`
+/// constants shared by `include_aseprite!` and `include_spritesheet!`. If the
+/// palettes are pinned, every sprite must fit one of them.
+fn sprite_graphics(
+    frames: &[SpriteFrame],
+    tags: &[SpriteTag],
+    filenames: &[PathBuf],
+    pinned: Option<&pinned_palettes::PinnedPalettes>,
+) -> syn::Result<TokenStream> {
+    let data = sprite_data(frames, pinned)?;
+    let code = sprite_code(frames, tags, filenames, &data);
+
+    let warning = data.colour_loss.map(|loss| {
+        let names: Vec<_> = filenames
+            .iter()
+            .filter_map(|filename| filename.file_name())
+            .map(|name| name.to_string_lossy())
+            .collect();
+        loss.warning(&names.join(", "))
+    });
+
+    Ok(quote! {
+        #code
+        #warning
+    })
+}
+
+/// The palettes and tiles of the sprites, before they are turned into code.
+struct SpriteData {
+    palettes: Vec<[u16; 16]>,
+    /// The 4bpp tiles of every sprite, one after the other
+    tile_data: Vec<u8>,
+    /// The palette of each sprite
+    assignments: Vec<usize>,
+    variant_palettes: Vec<[u16; 16]>,
+    /// The name and index in `variant_palettes` of each sprite's palette
+    /// variants
+    sprite_variants: Vec<Vec<(String, usize)>>,
+    /// How much the colours changed, if any of the sprites were quantised
+    colour_loss: Option<quantise::ColourLoss>,
+}
+
+fn sprite_data(
+    frames: &[SpriteFrame],
+    pinned: Option<&pinned_palettes::PinnedPalettes>,
+) -> syn::Result<SpriteData> {
+    let transparent_colour = Colour::from_rgb(255, 0, 255, 0);
+
+    let mut optimiser = palette16::Palette16Optimiser::new(Some(transparent_colour));
+    let mut images: Vec<Cow<Image>> = Vec::new();
+    let mut tiles_to_quantise = Vec::new();
+    let mut optimised_frames = Vec::new();
+
+    for (index, frame) in frames.iter().enumerate() {
+        if let Some(quantise) = frame.quantise {
+            tiles_to_quantise.extend(image_tiles(
+                &frame.image,
+                frame.image.width,
+                frame.image.height,
+                quantise.dither,
+            ));
+        } else {
+            add_to_optimiser(
+                &mut optimiser,
+                &frame.image,
+                frame.image.width,
+                frame.image.height,
+                Some(transparent_colour),
+            );
+            optimised_frames.push(index);
+        }
+        images.push(Cow::Borrowed(&frame.image));
+    }
+
+    let mut optimised_results = match pinned {
+        Some(pinned) => optimiser.assign_to(&pinned.palettes).map_err(|frame| {
+            pinned.missing_colours_error(
+                &format!("Sprite {}", optimised_frames[frame]),
+                optimiser.palette(frame),
+            )
+        })?,
+        None => optimiser.optimise_palettes(),
+    };
+
+    // Quantised frames get palettes of their own after the ones the optimiser
+    // found, or are recoloured to fit the pinned palettes. If files ask for
+    // different methods, the first one is used to pick the palettes, but each
+    // file keeps its own dithering.
+    let mut colour_loss = None;
+    if let Some(quantise) = frames.iter().find_map(|frame| frame.quantise) {
+        let (quantised, optimised_palette_count) = match pinned {
+            Some(pinned) => (
+                quantise::Quantise::quantise_to_palettes(
+                    &tiles_to_quantise,
+                    &pinned.palettes,
+                    transparent_colour,
+                ),
+                0,
+            ),
+            None => {
+                let optimised_palette_count = optimised_results.optimised_palettes.len();
+
+                (
+                    quantise.quantise_tiles(
+                        &tiles_to_quantise,
+                        transparent_colour,
+                        quantise::MAX_PALETTES - optimised_palette_count,
+                    ),
+                    optimised_palette_count,
+                )
+            }
+        };
+
+        let mut quantised_tiles = quantised.tiles.into_iter();
+        let mut quantised_assignments = quantised.results.assignments.into_iter();
+        let mut optimised_assignments = optimised_results.assignments.into_iter();
+
+        let assignments = frames
+            .iter()
+            .zip(images.iter_mut())
+            .map(|(frame, image)| {
+                if frame.quantise.is_some() {
+                    let image = image.to_mut();
+                    let (width, height) = (image.width, image.height);
+                    set_image_tiles(image, width, height, &mut quantised_tiles);
+                    quantised_assignments.next().unwrap() + optimised_palette_count
+                } else {
+                    optimised_assignments.next().unwrap()
+                }
+            })
+            .collect();
+
+        if pinned.is_none() {
+            optimised_results
+                .optimised_palettes
+                .extend(quantised.results.optimised_palettes);
+        }
+        optimised_results.assignments = assignments;
+        colour_loss = Some(quantised.loss);
+    }
+
+    let (palettes, tile_data, assignments) = palette_tile_data(&optimised_results, &images);
+
+    // Each distinct variant palette is only generated once, however many
+    // sprites use it. Files can give the same name to different variants, so
+    // they are told apart by their colours.
+    let mut variant_palettes = Vec::new();
+    let mut variant_indices: HashMap<[u16; 16], usize> = HashMap::new();
+
+    let sprite_variants = frames
+        .iter()
+        .zip(assignments.iter())
+        .map(|(frame, &assignment)| {
+            frame
+                .palette_variants
+                .iter()
+                .map(|(name, variant)| {
+                    let colours = rust_generator::palette_colours(
+                        &optimised_results.optimised_palettes[assignment],
+                        |colour| variant.apply(colour),
+                    );
+                    let index = *variant_indices.entry(colours).or_insert_with(|| {
+                        variant_palettes.push(colours);
+                        variant_palettes.len() - 1
+                    });
+
+                    (name.clone(), index)
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(SpriteData {
+        palettes,
+        tile_data,
+        assignments,
+        variant_palettes,
+        sprite_variants,
+        colour_loss,
+    })
+}
+
+fn sprite_code(
+    frames: &[SpriteFrame],
+    tags: &[SpriteTag],
+    filenames: &[PathBuf],
+    data: &SpriteData,
+) -> TokenStream {
+    let palette_data = data.palettes.iter().map(|colours| {
+        quote! {
+            Palette16::new([
+                #(Rgb15::new(#colours)),*
+            ])
+        }
+    });
+
+    let variant_palettes = data.variant_palettes.iter().map(|colours| {
+        quote! {
+            Palette16::new([
+                #(Rgb15::new(#colours)),*
+            ])
+        }
+    });
+
+    let mut pre = 0;
+    let sprites = frames
+        .iter()
+        .zip(data.assignments.iter())
+        .zip(data.sprite_variants.iter())
+        .map(|((frame, assignment), variants)| {
+            let f = &frame.image;
+            let start: usize = pre;
+            let end: usize = pre + (f.width / 8) * (f.height / 8) * 32;
+            let data = ByteString(&data.tile_data[start..end]);
+            pre = end;
+            let width = f.width;
+            let height = f.height;
+            let duration = frame.duration;
+
+            let slices = frame.slices.iter().map(|slice| {
+                let name = &slice.name;
+                let (x, y) = (slice.x, slice.y);
+                let (slice_width, slice_height) = (slice.width as i32, slice.height as i32);
+                let pivot = slice
+                    .pivot
+                    .map(|(pivot_x, pivot_y)| quote! { .with_pivot(#pivot_x, #pivot_y) });
+
+                quote! {
+                    Slice::new(#name, #x, #y, #slice_width, #slice_height) #pivot
+                }
+            });
+
+            let cel_user_data = frame
+                .cel_user_data
+                .iter()
+                .map(|(layer, text)| quote! { (#layer, #text) });
+
+            let palette_variants = variants
+                .iter()
+                .map(|(name, index)| quote! { (#name, &PALETTE_VARIANTS[#index]) });
+
+            quote! {
+                unsafe {
+                        Sprite::new(
+                        &PALETTES[#assignment],
+                        align_bytes!(u16, #data),
+                        Size::from_width_height(#width, #height)
+                    )
+                }
+                .with_duration(#duration)
+                .with_slices(&[#(#slices),*])
+                .with_cel_user_data(&[#(#cel_user_data),*])
+                .with_palette_variants({
+                    // indexing isn't promoted, so this needs its own const
+                    const VARIANTS: &[(&str, &Palette16)] = &[#(#palette_variants),*];
+                    VARIANTS
+                })
+            }
+        });
+
+    let tags = tags.iter().map(|tag| {
+        let SpriteTag {
+            name,
+            start,
+            end,
+            direction,
+            ..
+        } = tag;
+
+        assert!(start <= end, "Tag {name} has start > end");
+
+        let user_data = tag
+            .user_data
+            .as_ref()
+            .map(|text| quote! { .with_user_data(#text) });
+
+        let rotations = tag
+            .rotations
+            .map(|(rotations, stride)| quote! { .with_rotations(#rotations, #stride) });
+
+        quote! {
+            (#name, Tag::new(SPRITES, #start, #end, #direction) #user_data #rotations)
+        }
+    });
+
+    let include_paths = filenames.iter().map(|s| {
+        let s = s.as_os_str().to_string_lossy();
+        quote! {
+            const _: &[u8] = include_bytes!(#s);
+        }
+    });
+
+    quote! {
+        #(#include_paths)*
+
+
+        const PALETTES: &[Palette16] = &[
+            #(#palette_data),*
+        ];
+
+        const PALETTE_VARIANTS: &[Palette16] = &[
+            #(#variant_palettes),*
+        ];
+
+        pub const SPRITES: &[Sprite] = &[
+            #(#sprites),*
+        ];
+
+        const TAGS: &TagMap = &TagMap::new(
+            &[
+                #(#tags),*
+            ]
+        );
+
+    }
+}
+
+fn add_to_optimiser(
+    palette_optimiser: &mut palette16::Palette16Optimiser,
+    image: &Image,
+    tile_width: usize,
+    tile_height: usize,
+    transparent_colour: Option<Colour>,
+) {
+    let tiles_x = image.width / tile_width;
+    let tiles_y = image.height / tile_height;
+
+    for y in 0..tiles_y {
+        for x in 0..tiles_x {
+            let mut palette = palette16::Palette16::new();
+
+            for j in 0..tile_height {
+                for i in 0..tile_width {
+                    let colour = image.colour(x * tile_width + i, y * tile_height + j);
+
+                    palette.add_colour(match (colour.is_transparent(), transparent_colour) {
+                        (true, Some(transparent_colour)) => transparent_colour,
+                        _ => colour,
+                    });
+                }
+            }
+
+            palette_optimiser.add_palette(palette);
+        }
+    }
+}
+
+/// Reduces the colours of the background images in place, returning the
+/// palettes in the same form as the palette optimiser does and how much the
+/// colours changed.
+fn quantise_background_images<'a>(
+    quantise: quantise::Quantise,
+    pinned: Option<&pinned_palettes::PinnedPalettes>,
+    images: &'a HashMap<String, &dyn config::Image>,
+    loaded_images: &mut HashMap<&'a String, Image>,
+    transparent_colour: Option<Colour>,
+) -> (Palette16OptimisationResults, quantise::ColourLoss) {
+    let transparent_colour = transparent_colour.unwrap_or_else(|| Colour::from_rgb(255, 0, 255, 0));
+
+    let names_with_colours = |colours: fn(Colours) -> bool| -> Vec<&String> {
+        images
+            .iter()
+            .filter(|(_, settings)| colours(settings.colours()))
+            .map(|(name, _)| name)
+            .collect()
+    };
+
+    let names16 = names_with_colours(|colours| matches!(colours, Colours::Colours16));
+    let names256 = names_with_colours(|colours| matches!(colours, Colours::Colours256));
+
+    let tiles: Vec<_> = names16
+        .iter()
+        .flat_map(|name| image_tiles(&loaded_images[name], 8, 8, quantise.dither))
+        .collect();
+
+    let quantised = match pinned {
+        Some(pinned) => {
+            quantise::Quantise::quantise_to_palettes(&tiles, &pinned.palettes, transparent_colour)
+        }
+        None => quantise.quantise_tiles(&tiles, transparent_colour, quantise::MAX_PALETTES),
+    };
+    let mut colour_loss = quantised.loss;
+
+    let mut quantised_tiles = quantised.tiles.into_iter();
+    for name in &names16 {
+        let image = loaded_images.get_mut(name).unwrap();
+        set_image_tiles(image, 8, 8, &mut quantised_tiles);
+    }
+
+    if names256.is_empty() {
+        return (quantised.results, colour_loss);
+    }
+
+    let used_colours = quantised
+        .results
+        .optimised_palettes
+        .iter()
+        .flat_map(|palette| palette.colours())
+        .collect::<std::collections::HashSet<_>>()
+        .len();
+
+    let whole_images: Vec<_> = names256
+        .iter()
+        .flat_map(|name| {
+            let image = &loaded_images[name];
+            image_tiles(image, image.width, image.height, quantise.dither)
+        })
+        .collect();
+
+    let (quantised_images, loss) = match pinned {
+        Some(pinned) => quantise::Quantise::remap_to_colours(
+            &whole_images,
+            &pinned.opaque_colours(),
+            transparent_colour,
+        ),
+        None => quantise.quantise_shared(
+            &whole_images,
+            transparent_colour,
+            255usize.saturating_sub(used_colours).max(1),
+        ),
+    };
+    colour_loss.merge(loss);
+
+    let mut palette256 = Palette256::new();
+    let mut quantised_images = quantised_images.into_iter();
+    for name in &names256 {
+        let image = loaded_images.get_mut(name).unwrap();
+        let (width, height) = (image.width, image.height);
+        set_image_tiles(image, width, height, &mut quantised_images);
+        palette256.add_image(image);
+    }
+
+    // Pinned palettes already have every colour the images were remapped to
+    if pinned.is_some() {
+        return (quantised.results, colour_loss);
+    }
+
+    (palette256.extend_results(&quantised.results), colour_loss)
+}
+
+/// Assigns each tile of the background images to the first pinned palette
+/// with all of its colours, failing if a tile doesn't fit any of them or a 256
+/// colour image uses a colour none of them have.
+fn pinned_background_results(
+    pinned: &pinned_palettes::PinnedPalettes,
+    images: &HashMap<String, &dyn config::Image>,
+    loaded_images: &HashMap<&String, Image>,
+    transparent_colour: Option<Colour>,
+) -> syn::Result<Palette16OptimisationResults> {
+    let mut optimiser = Palette16Optimiser::new(transparent_colour);
+    let mut tile_positions = Vec::new();
+    let pinned_colours = pinned.opaque_colours();
+
+    for (name, settings) in images.iter() {
+        let image = &loaded_images[name];
+
+        match settings.colours() {
+            Colours::Colours16 => {
+                add_to_optimiser(&mut optimiser, image, 8, 8, transparent_colour);
+                tile_positions.extend(
+                    (0..image.height / 8)
+                        .flat_map(|y| (0..image.width / 8).map(move |x| (name, x * 8, y * 8))),
+                );
+            }
+            Colours::Colours256 => {
+                let missing = (0..image.height)
+                    .flat_map(|y| (0..image.width).map(move |x| image.colour(x, y)))
+                    .find(|colour| !colour.is_transparent() && !pinned_colours.contains(colour));
+
+                if let Some(colour) = missing {
+                    return Err(pinned.missing_colour_error(&format!("Image {name}"), colour));
+                }
+            }
+        }
+    }
+
+    optimiser.assign_to(&pinned.palettes).map_err(|tile| {
+        let (name, x, y) = tile_positions[tile];
+        pinned.missing_colours_error(
+            &format!("The tile at ({x}, {y}) of image {name}"),
+            optimiser.palette(tile),
+        )
+    })
+}
+
+/// Splits the image into tiles for quantising, in the same order as
+/// [`add_to_optimiser`] adds them.
+fn image_tiles(
+    image: &Image,
+    tile_width: usize,
+    tile_height: usize,
+    dither: quantise::Dither,
+) -> Vec<quantise::Tile> {
+    let tiles_x = image.width / tile_width;
+    let tiles_y = image.height / tile_height;
+
+    (0..tiles_y)
+        .flat_map(|y| (0..tiles_x).map(move |x| (x, y)))
+        .map(|(x, y)| quantise::Tile {
+            width: tile_width,
+            pixels: (0..tile_height)
+                .flat_map(|j| {
+                    (0..tile_width)
+                        .map(move |i| image.colour(x * tile_width + i, y * tile_height + j))
+                })
+                .collect(),
+            dither,
+        })
+        .collect()
+}
+
+/// Writes quantised tiles back to the image, the reverse of [`image_tiles`].
+fn set_image_tiles(
+    image: &mut Image,
+    tile_width: usize,
+    tile_height: usize,
+    tiles: &mut impl Iterator<Item = quantise::Tile>,
+) {
+    let tiles_x = image.width / tile_width;
+    let tiles_y = image.height / tile_height;
+
+    for y in 0..tiles_y {
+        for x in 0..tiles_x {
+            let tile = tiles.next().expect("a quantised tile for every tile");
+
+            for (i, colour) in tile.pixels.into_iter().enumerate() {
+                image.set_colour(
+                    x * tile_width + i % tile_width,
+                    y * tile_height + i / tile_width,
+                    colour,
+                );
+            }
+        }
+    }
+}
+
+fn palette_tile_data(
+    optimiser: &Palette16OptimisationResults,
+    images: &[impl Borrow<Image>],
+) -> (Vec<[u16; 16]>, Vec<u8>, Vec<usize>) {
+    let palette_data = rust_generator::palette_data(optimiser, |colour| colour);
+
+    let mut tile_data = Vec::new();
+
+    for (image_idx, image) in images.iter().enumerate() {
+        add_image_to_tile_data(
+            &mut tile_data,
+            image.borrow(),
+            optimiser,
+            image_idx,
+            true,
+            &(0..images.len()).collect::<Vec<_>>(),
+        );
+    }
+
+    let tile_data = collapse_to_4bpp(&tile_data);
+
+    let assignments = optimiser.assignments.clone();
+
+    (palette_data, tile_data, assignments)
+}
+
+fn collapse_to_4bpp(tile_data: &[u8]) -> Vec<u8> {
+    tile_data
+        .chunks(2)
+        .map(|chunk| chunk[0] | (chunk[1] << 4))
+        .collect()
+}
+
+fn add_image_to_tile_data(
+    tile_data: &mut Vec<u8>,
+    image: &Image,
+    optimiser: &Palette16OptimisationResults,
+    assignment_offset: usize,
+    is_sprite: bool,
+    remap_index: &[usize],
+) {
+    let tile_size = 8;
+    let tiles_x = image.width / tile_size;
+    let tiles_y = image.height / tile_size;
+
+    for y in 0..tiles_y {
+        for x in 0..tiles_x {
+            let assignment = if is_sprite {
+                assignment_offset
+            } else {
+                remap_index[y * tiles_x + x] + assignment_offset
+            };
+
+            let palette_index = optimiser.assignments[assignment];
+            let palette = &optimiser.optimised_palettes[palette_index];
+
+            for inner_y in 0..tile_size / 8 {
+                for inner_x in 0..tile_size / 8 {
+                    for j in inner_y * 8..inner_y * 8 + 8 {
+                        for i in inner_x * 8..inner_x * 8 + 8 {
+                            let colour = image.colour(x * tile_size + i, y * tile_size + j);
+                            tile_data.push(palette.colour_index(colour));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn add_image_256_to_tile_data(
+    tile_data: &mut Vec<u8>,
+    image: &Image,
+    optimiser: &Palette16OptimisationResults,
+) {
+    let tile_size = 8;
+    let tiles_x = image.width / tile_size;
+    let tiles_y = image.height / tile_size;
+
+    // Palettes can have fewer than 16 colours, so the index of each colour
+    // depends on which palette it is in
+    let all_colours: Vec<_> = optimiser
+        .optimised_palettes
+        .iter()
+        .enumerate()
+        .flat_map(|(palette, p)| {
+            p.colours()
+                .enumerate()
+                .map(move |(index, colour)| (palette * 16 + index, colour))
+        })
+        .collect();
+
+    for y in 0..tiles_y {
+        for x in 0..tiles_x {
+            for inner_y in 0..tile_size / 8 {
+                for inner_x in 0..tile_size / 8 {
+                    for j in inner_y * 8..inner_y * 8 + 8 {
+                        for i in inner_x * 8..inner_x * 8 + 8 {
+                            let colour = image.colour(x * tile_size + i, y * tile_size + j);
+                            let index = all_colours
+                                .iter()
+                                .find(|(_, c)| **c == colour)
+                                .map(|&(index, _)| index)
+                                .or_else(|| colour.is_transparent().then_some(0))
+                                .unwrap();
+                            tile_data.push(index as u8);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[doc(hidden)]
+pub fn include_font(input: TokenStream) -> TokenStream {
+    let input = match syn::parse2::<font_loader::FontInput>(input) {
+        Ok(input) => input,
+        Err(e) => return e.into_compile_error(),
+    };
+
+    font(
+        &input.file_name.value(),
+        &input.options,
+        input.file_name.span(),
+    )
+    .unwrap_or_else(syn::Error::into_compile_error)
+}
+
+/// The font at the given path relative to the crate root, in the same way as
+/// [`include_font`]. TTF fonts need a size, `.fnt` files are BMFont fonts and
+/// images are glyph grids. Any errors are reported at the span.
+fn font(
+    filename: &str,
+    options: &font_loader::FontOptions,
+    span: proc_macro2::Span,
+) -> syn::Result<TokenStream> {
+    let root = manifest_dir()?;
+    let path = Path::new(&root).join(filename);
+
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    let is_bitmap_font = matches!(extension.as_deref(), Some("fnt" | "png" | "bmp"));
+
+    if is_bitmap_font && options.size.is_some() {
+        return Err(syn::Error::new(
+            span,
+            "Bitmap fonts are drawn at their own size, so don't take a size",
+        ));
+    }
+
+    let (characters, mut dependencies) = options
+        .glyphs
+        .characters(Path::new(&root))
+        .map_err(|e| syn::Error::new(span, e))?;
+
+    let (font, pages) = match extension.as_deref() {
+        Some("fnt") => bitmap_font::load_bmfont(&path).map_err(|e| syn::Error::new(span, e))?,
+        Some("png" | "bmp") => (
+            Image::load_from_file(&path)
+                .and_then(|image| bitmap_font::load_grid(&image, &options.grid))
+                .map_err(|e| syn::Error::new(span, e))?,
+            Vec::new(),
+        ),
+        _ => {
+            let size = options
+                .size
+                .ok_or_else(|| syn::Error::new(span, "TTF fonts need a size"))?;
+            let file_content = std::fs::read(&path).map_err(|e| {
+                syn::Error::new(span, format!("Failed to read {}: {e}", path.display()))
+            })?;
+
+            (
+                font_loader::load_font(&file_content, size, &characters),
+                Vec::new(),
+            )
+        }
+    };
+
+    let mut font = font;
+    if is_bitmap_font && !options.glyphs.is_default() {
+        font.retain(&characters);
+    }
+    let rendered = font.to_tokens();
+
+    dependencies.extend(pages);
+    let include_path = path.to_string_lossy();
+    let dependencies = dependencies
+        .iter()
+        .map(|file| file.to_string_lossy().into_owned());
+
+    Ok(quote!({
+        let _ = include_bytes!(#include_path);
+        #(let _ = include_bytes!(#dependencies);)*
+
+        #rendered
+    }))
+}
+
+/// The directory of the crate being built, which the asset paths are relative
+/// to.
+fn manifest_dir() -> syn::Result<String> {
+    std::env::var("CARGO_MANIFEST_DIR").map_err(|_| {
+        syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "CARGO_MANIFEST_DIR isn't set, so assets can only be converted when building with cargo",
+        )
+    })
+}
+
+fn valid_sprite_size(width: u32, height: u32) -> bool {
+    match (width, height) {
+        (8, 8) => true,
+        (16, 16) => true,
+        (32, 32) => true,
+        (64, 64) => true,
+        (16, 8) => true,
+        (32, 8) => true,
+        (32, 16) => true,
+        (64, 32) => true,
+        (8, 16) => true,
+        (8, 32) => true,
+        (16, 32) => true,
+        (32, 64) => true,
+        (_, _) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use asefile::AnimationDirection;
+
+    use super::*;
+    use palette_variant::PaletteVariant;
+
+    #[test]
+    // These directions defined in agb and have these values. This is important
+    // when outputting code for agb. If more animation directions are added then
+    // we will have to support them there.
+    fn directions_to_agb() {
+        assert_eq!(AnimationDirection::Forward as usize, 0);
+        assert_eq!(AnimationDirection::Reverse as usize, 1);
+        assert_eq!(AnimationDirection::PingPong as usize, 2);
+    }
+
+    #[test]
+    fn variants_with_the_same_name_are_kept_apart() {
+        let red = Colour::from_rgb(255, 0, 0, 255);
+        let frame = |variant| SpriteFrame {
+            image: Image::from_colour_data(vec![red; 64]),
+            duration: 0,
+            slices: Vec::new(),
+            cel_user_data: Vec::new(),
+            palette_variants: Rc::from([("hurt".to_string(), variant)]),
+            quantise: None,
+        };
+
+        let frames = [
+            frame(PaletteVariant::Flash),
+            frame(PaletteVariant::HueShift(90.0)),
+            frame(PaletteVariant::Flash),
+        ];
+        let data = sprite_data(&frames, None).unwrap();
+
+        assert_eq!(data.assignments, [0, 0, 0]);
+        assert_eq!(data.variant_palettes.len(), 2);
+        assert_eq!(data.sprite_variants[0], [("hurt".to_string(), 0)]);
+        assert_eq!(data.sprite_variants[1], [("hurt".to_string(), 1)]);
+        assert_eq!(data.sprite_variants[2], [("hurt".to_string(), 0)]);
+    }
+
+    #[test]
+    fn tags_take_the_file_prefix_and_user_data() {
+        let file =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../agb/examples/gfx/layers.aseprite");
+        let files = [
+            syn::parse_str::<AsepriteFileOption>(&format!("{:?}", file.display())).unwrap(),
+            syn::parse_str::<AsepriteFileOption>(&format!(
+                "{:?} {{ tag_prefix: \"enemy-\" }}",
+                file.display()
+            ))
+            .unwrap(),
+        ];
+
+        let (frames, tags, _) = load_aseprite_files(&files, None).unwrap();
+        assert_eq!(frames.len(), 4);
+
+        let tags: Vec<_> = tags
+            .iter()
+            .map(|tag| {
+                (
+                    tag.name.as_str(),
+                    tag.start,
+                    tag.end,
+                    tag.user_data.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            tags,
+            [
+                ("idle", 0, 0, None),
+                ("attack", 1, 1, Some("damage=2")),
+                ("enemy-idle", 2, 2, None),
+                ("enemy-attack", 3, 3, Some("damage=2")),
+            ]
+        );
+    }
+}
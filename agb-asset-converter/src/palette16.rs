@@ -16,6 +16,17 @@ impl Palette16 {
         }
     }
 
+    /// A palette with exactly these colours in this order, even if some of
+    /// them are repeated.
+    pub fn from_colours(colours: Vec<Colour>) -> Self {
+        assert!(
+            colours.len() <= MAX_COLOURS_PER_PALETTE,
+            "Can have at most 16 colours in a single palette"
+        );
+
+        Palette16 { colours }
+    }
+
     pub fn add_colour(&mut self, colour: Colour) -> bool {
         if self.colours.contains(&colour) {
             return false;
@@ -70,6 +81,14 @@ impl Palette16 {
             .len()
     }
 
+    /// Whether every opaque colour of this palette is also in `other`.
+    pub fn opaque_colours_in(&self, other: &Palette16) -> bool {
+        self.colours
+            .iter()
+            .filter(|colour| !colour.is_transparent())
+            .all(|colour| other.colours.contains(colour))
+    }
+
     fn is_satisfied_by(&self, other: &Palette16) -> bool {
         self.colours
             .iter()
@@ -161,6 +180,34 @@ impl Palette16Optimiser {
         }
     }
 
+    /// Assigns every palette to the first of the given fixed palettes which
+    /// has all of its opaque colours, rather than choosing new palettes. If a
+    /// palette doesn't fit any of them, returns its index.
+    pub fn assign_to(&self, pinned: &[Palette16]) -> Result<Palette16OptimisationResults, usize> {
+        let assignments = self
+            .palettes
+            .iter()
+            .enumerate()
+            .map(|(i, palette)| {
+                pinned
+                    .iter()
+                    .position(|pinned| palette.opaque_colours_in(pinned))
+                    .ok_or(i)
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Palette16OptimisationResults {
+            optimised_palettes: pinned.to_vec(),
+            assignments,
+            transparent_colour: self.transparent_colour,
+        })
+    }
+
+    /// The palette which was added at the given index.
+    pub fn palette(&self, index: usize) -> &Palette16 {
+        &self.palettes[index]
+    }
+
     fn find_maximal_palette_for(&self, unsatisfied_palettes: &HashSet<Palette16>) -> Palette16 {
         let mut palette = Palette16::new();
 
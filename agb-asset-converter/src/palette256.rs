@@ -42,10 +42,7 @@ impl Palette256 {
             .collect();
 
         let current_colours_set = HashSet::from_iter(optimised_palette_colours.iter().cloned());
-        let new_colours: HashSet<_> = self
-            .colours
-            .symmetric_difference(&current_colours_set)
-            .collect();
+        let new_colours: HashSet<_> = self.colours.difference(&current_colours_set).collect();
 
         assert!(
             new_colours.len() + optimised_palette_colours.len() <= 256,
@@ -70,3 +67,36 @@ impl Palette256 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colours_already_in_the_16_colour_palettes_are_shared() {
+        let palettes: Vec<_> = (0..16)
+            .map(|p| {
+                let mut palette = Palette16::new();
+                for i in 0..16 {
+                    palette.add_colour(Colour::from_rgb(p, i, 0, 255));
+                }
+                palette
+            })
+            .collect();
+
+        let palette16 = Palette16OptimisationResults {
+            optimised_palettes: palettes.clone(),
+            assignments: vec![],
+            transparent_colour: None,
+        };
+
+        // every palette is full, so this only fits if the colours are reused
+        let mut palette256 = Palette256::new();
+        palette256.add_image(&Image::from_colour_data(
+            palettes[3].colours().copied().collect(),
+        ));
+
+        let results = palette256.extend_results(&palette16);
+        assert_eq!(results.optimised_palettes, palettes);
+    }
+}
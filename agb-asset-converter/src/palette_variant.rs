@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use syn::parse::{Parse, ParseStream};
+use syn::{LitStr, Token};
+
+use crate::colour::Colour;
+use crate::image_loader::Image;
+
+/// A way of recolouring a palette to make an alternative version of it, such
+/// as `hue_shift(120)` or `remap("gfx/red-remap.png")`.
+#[derive(Debug, Clone)]
+pub(crate) enum PaletteVariant {
+    /// Replaces colours using a remap image. Each column of the image maps the
+    /// colour in the top row to the colour in the bottom row.
+    Remap {
+        file_name: PathBuf,
+        colours: HashMap<Colour, Colour>,
+    },
+    /// Rotates the hue of every colour by the given number of degrees.
+    HueShift(f64),
+    /// Multiplies every colour by the tint, as if seen through coloured glass.
+    Tint(Colour),
+    /// Turns every colour white, for damage flashes.
+    Flash,
+}
+
+impl PaletteVariant {
+    /// The recoloured version of the colour. Transparent colours are never
+    /// changed.
+    pub(crate) fn apply(&self, colour: Colour) -> Colour {
+        if colour.is_transparent() {
+            return colour;
+        }
+
+        match self {
+            PaletteVariant::Remap { colours, .. } => {
+                colours.get(&colour).copied().unwrap_or(colour)
+            }
+            PaletteVariant::HueShift(degrees) => hue_shift(colour, *degrees),
+            PaletteVariant::Tint(tint) => {
+                let multiply = |a: u8, b: u8| ((u16::from(a) * u16::from(b) + 127) / 255) as u8;
+
+                Colour::from_rgb(
+                    multiply(colour.r, tint.r),
+                    multiply(colour.g, tint.g),
+                    multiply(colour.b, tint.b),
+                    colour.a,
+                )
+            }
+            PaletteVariant::Flash => Colour::from_rgb(255, 255, 255, colour.a),
+        }
+    }
+
+    /// The remap image this variant depends on, if there is one.
+    pub(crate) fn file_name(&self) -> Option<&Path> {
+        match self {
+            PaletteVariant::Remap { file_name, .. } => Some(file_name),
+            _ => None,
+        }
+    }
+}
+
+impl Parse for PaletteVariant {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let kind: syn::Ident = input.parse()?;
+
+        if kind == "flash" {
+            return Ok(PaletteVariant::Flash);
+        }
+
+        let content;
+        syn::parenthesized!(content in input);
+
+        match kind.to_string().as_str() {
+            "remap" => {
+                let file_name: LitStr = content.parse()?;
+
+                let root = crate::manifest_dir()?;
+                let path = Path::new(&root).join(file_name.value());
+
+                let colours =
+                    load_remap(&path).map_err(|e| syn::Error::new_spanned(&file_name, e))?;
+                let file_name = path;
+                Ok(PaletteVariant::Remap { file_name, colours })
+            }
+            "hue_shift" => {
+                let negative = content.parse::<Option<Token![-]>>()?.is_some();
+                let degrees: f64 = match content.parse::<syn::Lit>()? {
+                    syn::Lit::Int(degrees) => degrees.base10_parse()?,
+                    syn::Lit::Float(degrees) => degrees.base10_parse()?,
+                    other => {
+                        return Err(syn::Error::new_spanned(
+                            other,
+                            "Expected a number of degrees",
+                        ))
+                    }
+                };
+
+                Ok(PaletteVariant::HueShift(if negative {
+                    -degrees
+                } else {
+                    degrees
+                }))
+            }
+            "tint" => {
+                let colour: LitStr = content.parse()?;
+                let tint = colour
+                    .value()
+                    .parse()
+                    .map_err(|msg| syn::Error::new_spanned(colour, msg))?;
+
+                Ok(PaletteVariant::Tint(tint))
+            }
+            _ => Err(syn::Error::new_spanned(
+                kind,
+                "Unknown palette variant, expected remap, hue_shift, tint or flash",
+            )),
+        }
+    }
+}
+
+/// Parses a block of named variants such as `{ "hurt": flash }`, where the
+/// names are either string literals or identifiers.
+pub(crate) fn parse_variants<Name: Parse>(
+    input: ParseStream,
+) -> syn::Result<Vec<(Name, PaletteVariant)>> {
+    let content;
+    syn::braced!(content in input);
+
+    let mut variants = Vec::new();
+
+    while !content.is_empty() {
+        let name: Name = content.parse()?;
+        let _: Token![:] = content.parse()?;
+        variants.push((name, content.parse()?));
+
+        if content.is_empty() {
+            break;
+        }
+
+        let _: Token![,] = content.parse()?;
+    }
+
+    Ok(variants)
+}
+
+fn load_remap(file_name: &Path) -> Result<HashMap<Colour, Colour>, String> {
+    let image = Image::load_from_file(file_name)?;
+    if image.height != 2 {
+        return Err(format!(
+            "Remap image {} must be 2 pixels tall, with the original colours on the top row and their replacements below",
+            file_name.display()
+        ));
+    }
+
+    Ok((0..image.width)
+        .map(|x| (image.colour(x, 0), image.colour(x, 1)))
+        .collect())
+}
+
+fn hue_shift(colour: Colour, degrees: f64) -> Colour {
+    let (r, g, b) = (
+        f64::from(colour.r) / 255.0,
+        f64::from(colour.g) / 255.0,
+        f64::from(colour.b) / 255.0,
+    );
+
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let chroma = max - min;
+
+    if chroma == 0.0 {
+        return colour;
+    }
+
+    let hue = if max == r {
+        ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / chroma + 2.0
+    } else {
+        (r - g) / chroma + 4.0
+    };
+
+    let hue = (hue + degrees / 60.0).rem_euclid(6.0);
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+
+    let (r, g, b) = match hue as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    let channel = |value: f64| ((value + min) * 255.0).round() as u8;
+    Colour::from_rgb(channel(r), channel(g), channel(b), colour.a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transforms_keep_transparency() {
+        let transparent = Colour::from_rgb(255, 0, 255, 0);
+
+        for variant in [
+            PaletteVariant::Flash,
+            PaletteVariant::HueShift(90.0),
+            PaletteVariant::Tint(Colour::from_rgb(0, 0, 0, 255)),
+        ] {
+            assert_eq!(variant.apply(transparent), transparent);
+        }
+    }
+
+    #[test]
+    fn colour_transforms() {
+        let red = Colour::from_rgb(255, 0, 0, 255);
+
+        assert_eq!(
+            PaletteVariant::HueShift(120.0).apply(red),
+            Colour::from_rgb(0, 255, 0, 255)
+        );
+        assert_eq!(
+            PaletteVariant::HueShift(-120.0).apply(red),
+            Colour::from_rgb(0, 0, 255, 255)
+        );
+        assert_eq!(
+            PaletteVariant::Tint(Colour::from_rgb(128, 255, 255, 255))
+                .apply(Colour::from_rgb(255, 100, 0, 255)),
+            Colour::from_rgb(128, 100, 0, 255)
+        );
+        assert_eq!(
+            PaletteVariant::Flash.apply(red),
+            Colour::from_rgb(255, 255, 255, 255)
+        );
+    }
+}
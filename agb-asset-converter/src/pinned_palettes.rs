@@ -0,0 +1,184 @@
+use std::path::{Path, PathBuf};
+
+use proc_macro2::Span;
+use syn::parse::{Parse, ParseStream};
+use syn::LitStr;
+
+use crate::colour::Colour;
+use crate::image_loader::Image;
+use crate::palette16::Palette16;
+use crate::quantise::hex;
+
+const MAX_PALETTES: usize = 16;
+const MAX_COLOURS_PER_PALETTE: usize = 16;
+
+/// Palettes fixed by a palette image such as `palettes: "gfx/palettes.png"`,
+/// so that several macro calls use the same colours in the same palette slots.
+/// Each row of the image is one palette, with palette 0 at the top. The first
+/// colour of each row is at index 0, which is transparent on the Game Boy
+/// Advance.
+pub(crate) struct PinnedPalettes {
+    pub file_name: PathBuf,
+    pub palettes: Vec<Palette16>,
+    span: Span,
+}
+
+impl Parse for PinnedPalettes {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let file_name: LitStr = input.parse()?;
+        Self::load(&file_name.value(), file_name.span())
+    }
+}
+
+impl PinnedPalettes {
+    /// Loads the palette image at the path relative to the crate root, with
+    /// any errors reported at the given span.
+    pub(crate) fn load(file_name: &str, span: Span) -> syn::Result<Self> {
+        let root = crate::manifest_dir()?;
+        let path = Path::new(&root).join(file_name);
+
+        let palettes = Image::load_from_file(&path)
+            .and_then(|image| palettes_from_image(&image))
+            .map_err(|message| syn::Error::new(span, message))?;
+
+        Ok(Self {
+            file_name: path,
+            palettes,
+            span,
+        })
+    }
+
+    /// A compile error pointing at the palette file name.
+    pub(crate) fn error(&self, message: impl std::fmt::Display) -> syn::Error {
+        syn::Error::new(self.span, message)
+    }
+
+    /// The error for when some colours don't all appear in any one palette.
+    pub(crate) fn missing_colours_error(&self, what: &str, palette: &Palette16) -> syn::Error {
+        let colours: Vec<_> = palette
+            .colours()
+            .filter(|colour| !colour.is_transparent())
+            .map(|&colour| hex(colour))
+            .collect();
+
+        self.error(format!(
+            "{what} uses the colours {} which are not all in any one palette of {}",
+            colours.join(", "),
+            self.file_name.display()
+        ))
+    }
+
+    /// The error for when a 256 colour image uses a colour which isn't in any
+    /// of the palettes.
+    pub(crate) fn missing_colour_error(&self, what: &str, colour: Colour) -> syn::Error {
+        self.error(format!(
+            "{what} uses the colour {} which is not in any palette of {}",
+            hex(colour),
+            self.file_name.display()
+        ))
+    }
+
+    /// Every opaque colour in any of the palettes.
+    pub(crate) fn opaque_colours(&self) -> Vec<Colour> {
+        let mut colours: Vec<Colour> = Vec::new();
+
+        for &colour in self.palettes.iter().flat_map(Palette16::colours) {
+            if !colour.is_transparent() && !colours.contains(&colour) {
+                colours.push(colour);
+            }
+        }
+
+        colours
+    }
+}
+
+fn palettes_from_image(image: &Image) -> Result<Vec<Palette16>, String> {
+    if image.width > MAX_COLOURS_PER_PALETTE || image.height > MAX_PALETTES {
+        return Err(format!(
+            "Palette image is {}x{}, but can be at most {MAX_COLOURS_PER_PALETTE} colours wide and {MAX_PALETTES} palettes tall",
+            image.width, image.height
+        ));
+    }
+
+    Ok((0..image.height)
+        .map(|y| Palette16::from_colours((0..image.width).map(|x| image.colour(x, y)).collect()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_row_is_a_palette() {
+        let transparent = Colour::from_rgb(0, 0, 0, 0);
+        let red = Colour::from_rgb(255, 0, 0, 255);
+        let blue = Colour::from_rgb(0, 0, 255, 255);
+
+        let image = Image::from_colour_data(vec![
+            transparent,
+            red,
+            blue,
+            red,
+            red,
+            red,
+            red,
+            red, //
+            transparent,
+            blue,
+            red,
+            blue,
+            blue,
+            blue,
+            blue,
+            blue,
+        ]);
+
+        let palettes = palettes_from_image(&image).unwrap();
+
+        assert_eq!(palettes.len(), 2);
+        assert_eq!(palettes[0].colour_index(blue), 2);
+        assert_eq!(palettes[1].colour_index(blue), 1);
+        assert_eq!(palettes[1].colour_index(transparent), 0);
+    }
+
+    #[test]
+    fn tiles_use_the_first_palette_with_their_colours() {
+        use crate::palette16::Palette16Optimiser;
+
+        let transparent = Colour::from_rgb(255, 0, 255, 0);
+        let red = Colour::from_rgb(255, 0, 0, 255);
+        let green = Colour::from_rgb(0, 255, 0, 255);
+        let blue = Colour::from_rgb(0, 0, 255, 255);
+
+        let pinned = [
+            Palette16::from_colours(vec![transparent, red]),
+            Palette16::from_colours(vec![transparent, red, green]),
+        ];
+
+        let mut optimiser = Palette16Optimiser::new(Some(transparent));
+        for colours in [vec![red], vec![transparent, green, red], vec![blue]] {
+            let mut palette = Palette16::new();
+            for colour in colours {
+                palette.add_colour(colour);
+            }
+            optimiser.add_palette(palette);
+        }
+
+        assert_eq!(optimiser.assign_to(&pinned).unwrap_err(), 2);
+
+        let mut optimiser = Palette16Optimiser::new(Some(transparent));
+        let mut palette = Palette16::new();
+        palette.add_colour(green);
+        optimiser.add_palette(palette);
+
+        assert_eq!(optimiser.assign_to(&pinned).unwrap().assignments, [1]);
+    }
+
+    #[test]
+    fn palettes_must_fit_in_palette_ram() {
+        let image = Image::from_colour_data(vec![Colour::from_rgb(0, 0, 0, 255); 8 * 17]);
+
+        assert!(palettes_from_image(&image).is_err());
+    }
+}
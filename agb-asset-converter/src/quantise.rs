@@ -0,0 +1,828 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+
+use crate::colour::Colour;
+use crate::palette16::{Palette16, Palette16OptimisationResults};
+
+/// The number of 16 colour palettes available for backgrounds and for sprites.
+pub(crate) const MAX_PALETTES: usize = 16;
+
+/// The most colours which can be in a 16 colour palette alongside the
+/// transparent colour.
+const OPAQUE_COLOURS_PER_PALETTE: usize = 15;
+
+/// How many times palettes are rebuilt after moving tiles between them.
+const GROUPING_ITERATIONS: usize = 4;
+const KMEANS_ITERATIONS: usize = 8;
+
+/// How to reduce an image with too many colours, such as
+/// `quantise: median_cut floyd_steinberg`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Quantise {
+    pub method: Method,
+    pub dither: Dither,
+}
+
+/// How the reduced set of colours is chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Method {
+    /// Repeatedly splits the colours in half along their widest channel.
+    MedianCut,
+    /// Refines the median cut colours by moving each to the centre of the
+    /// colours nearest to it.
+    KMeans,
+}
+
+/// How the error from replacing a colour is hidden.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Dither {
+    None,
+    /// A regular pattern, which works well for backgrounds made of tiles.
+    Ordered,
+    /// Spreads the error to the neighbouring pixels, which works well for
+    /// photos and gradients.
+    FloydSteinberg,
+}
+
+impl Parse for Quantise {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let method: syn::Ident = input.parse()?;
+        let method = match method.to_string().as_str() {
+            "median_cut" => Method::MedianCut,
+            "kmeans" => Method::KMeans,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    method,
+                    "Unknown quantise method, expected median_cut or kmeans",
+                ))
+            }
+        };
+
+        let dither = if input.peek(syn::Ident) {
+            let dither: syn::Ident = input.parse()?;
+            match dither.to_string().as_str() {
+                "ordered" => Dither::Ordered,
+                "floyd_steinberg" => Dither::FloydSteinberg,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        dither,
+                        "Unknown dither, expected ordered or floyd_steinberg",
+                    ))
+                }
+            }
+        } else {
+            Dither::None
+        };
+
+        Ok(Self { method, dither })
+    }
+}
+
+/// A rectangle of pixels which must use a single palette, such as a
+/// background tile or a whole sprite.
+#[derive(Debug, Clone)]
+pub(crate) struct Tile {
+    pub width: usize,
+    pub pixels: Vec<Colour>,
+    pub dither: Dither,
+}
+
+/// Tiles reduced to fit in a limited number of palettes.
+pub(crate) struct QuantisedTiles {
+    pub results: Palette16OptimisationResults,
+    pub tiles: Vec<Tile>,
+    pub loss: ColourLoss,
+}
+
+/// How much the colours of an image changed when they were quantised, so the
+/// loss can be reported to the artist. It is written as a summary such as
+/// `reduced 40 colours to 16, changing 120 of 4096 pixels` followed by the
+/// colours which changed the most.
+#[derive(Debug, Default, Clone)]
+pub struct ColourLoss {
+    original_colours: usize,
+    quantised_colours: usize,
+    changed_pixels: usize,
+    total_pixels: usize,
+    /// The colours which moved the furthest and what they were replaced with
+    worst: Vec<(Colour, Colour)>,
+}
+
+impl fmt::Display for ColourLoss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "reduced {} colours to {}, changing {} of {} pixels",
+            self.original_colours, self.quantised_colours, self.changed_pixels, self.total_pixels
+        )?;
+
+        if !self.worst.is_empty() {
+            let worst: Vec<_> = self
+                .worst
+                .iter()
+                .map(|(from, to)| format!("{} -> {}", hex(*from), hex(*to)))
+                .collect();
+            write!(f, ". Largest changes: {}", worst.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ColourLoss {
+    fn new(original: &[&[Colour]], quantised: &[&[Colour]], quantised_colours: usize) -> Self {
+        let mut changes = HashMap::new();
+        let mut loss = ColourLoss {
+            quantised_colours,
+            ..Default::default()
+        };
+
+        for (original, quantised) in original.iter().zip(quantised) {
+            for (&from, &to) in original.iter().zip(quantised.iter()) {
+                if from.is_transparent() {
+                    continue;
+                }
+
+                loss.total_pixels += 1;
+                if changed(from, to) {
+                    loss.changed_pixels += 1;
+                }
+
+                changes.entry(from).or_insert(to);
+            }
+        }
+
+        loss.original_colours = changes.len();
+
+        let mut changes: Vec<_> = changes
+            .into_iter()
+            .filter(|&(from, to)| changed(from, to))
+            .collect();
+        changes.sort_by_key(|&(from, to)| (std::cmp::Reverse(distance(from, to)), key(from)));
+        changes.truncate(5);
+        loss.worst = changes;
+
+        loss
+    }
+
+    /// Adds the loss from quantising other images, keeping the colours which
+    /// changed the most from either.
+    pub(crate) fn merge(&mut self, other: ColourLoss) {
+        self.original_colours += other.original_colours;
+        self.quantised_colours += other.quantised_colours;
+        self.changed_pixels += other.changed_pixels;
+        self.total_pixels += other.total_pixels;
+
+        self.worst.extend(other.worst);
+        self.worst
+            .sort_by_key(|&(from, to)| (std::cmp::Reverse(distance(from, to)), key(from)));
+        self.worst.truncate(5);
+    }
+
+    /// Whether any colours had to change, so that nothing needs reporting for
+    /// art which already fit.
+    #[must_use]
+    pub fn is_lossy(&self) -> bool {
+        self.changed_pixels > 0
+    }
+
+    /// A warning about the loss in the code generated by a macro, naming what
+    /// was quantised. Proc macros can't emit warnings of their own, so this
+    /// uses a deprecated constant, which warns when it is used.
+    pub(crate) fn warning(&self, name: &str) -> TokenStream {
+        if !self.is_lossy() {
+            return TokenStream::new();
+        }
+
+        let note = format!("quantising {name} {self}");
+        quote! {
+            const _: () = {
+                #[deprecated(note = #note)]
+                const COLOUR_LOSS: () = ();
+                COLOUR_LOSS
+            };
+        }
+    }
+
+    /// How many opaque pixels changed colour.
+    #[must_use]
+    pub fn changed_pixels(&self) -> usize {
+        self.changed_pixels
+    }
+
+    /// How many opaque pixels there were.
+    #[must_use]
+    pub fn total_pixels(&self) -> usize {
+        self.total_pixels
+    }
+}
+
+impl Quantise {
+    /// Reduces the tiles so that they fit in at most `max_palettes` 16 colour
+    /// palettes, with the transparent colour first in each one. Every tile is
+    /// recoloured using the palette it is assigned.
+    ///
+    /// Tiles are first grouped by their average colour and then repeatedly
+    /// moved to whichever group's palette represents them best.
+    pub(crate) fn quantise_tiles(
+        self,
+        tiles: &[Tile],
+        transparent_colour: Colour,
+        max_palettes: usize,
+    ) -> QuantisedTiles {
+        assert!(max_palettes > 0, "No palettes left to quantise into");
+
+        let group_count = max_palettes.min(tiles.len()).max(1);
+
+        // Start with tiles of similar brightness together
+        let mut order: Vec<usize> = (0..tiles.len()).collect();
+        order.sort_by_key(|&i| brightness(average(&tiles[i].pixels)));
+
+        let mut groups = vec![0; tiles.len()];
+        for (position, &tile) in order.iter().enumerate() {
+            groups[tile] = position * group_count / tiles.len();
+        }
+
+        let mut palettes = self.group_palettes(tiles, &groups, group_count);
+
+        for _ in 0..GROUPING_ITERATIONS {
+            for (tile, group) in tiles.iter().zip(groups.iter_mut()) {
+                *group = (0..group_count)
+                    .filter(|&group| !palettes[group].is_empty())
+                    .min_by_key(|&group| tile_error(tile, &palettes[group]))
+                    .unwrap_or(*group);
+            }
+
+            palettes = self.group_palettes(tiles, &groups, group_count);
+        }
+
+        // Drop any groups which ended up empty
+        let mut used_groups: Vec<usize> = groups.clone();
+        used_groups.sort_unstable();
+        used_groups.dedup();
+
+        let assignments: Vec<usize> = groups
+            .iter()
+            .map(|group| used_groups.binary_search(group).unwrap())
+            .collect();
+
+        let optimised_palettes: Vec<Palette16> = used_groups
+            .iter()
+            .map(|&group| {
+                let mut palette = Palette16::new();
+                palette.add_colour(transparent_colour);
+                for &colour in &palettes[group] {
+                    palette.add_colour(colour);
+                }
+                palette
+            })
+            .collect();
+
+        let quantised_tiles: Vec<Tile> = tiles
+            .iter()
+            .zip(groups.iter())
+            .map(|(tile, &group)| Tile {
+                pixels: remap(tile, &palettes[group], transparent_colour),
+                ..tile.clone()
+            })
+            .collect();
+
+        let quantised_colours = optimised_palettes
+            .iter()
+            .flat_map(|palette| palette.colours().skip(1))
+            .collect::<std::collections::HashSet<_>>()
+            .len();
+
+        let loss = ColourLoss::new(
+            &tiles
+                .iter()
+                .map(|t| t.pixels.as_slice())
+                .collect::<Vec<_>>(),
+            &quantised_tiles
+                .iter()
+                .map(|t| t.pixels.as_slice())
+                .collect::<Vec<_>>(),
+            quantised_colours,
+        );
+
+        QuantisedTiles {
+            results: Palette16OptimisationResults {
+                optimised_palettes,
+                assignments,
+                transparent_colour: Some(transparent_colour),
+            },
+            tiles: quantised_tiles,
+            loss,
+        }
+    }
+
+    /// Reduces the images to share a single palette of at most `max_colours`
+    /// opaque colours, for 256 colour backgrounds and bitmaps.
+    pub(crate) fn quantise_shared(
+        self,
+        images: &[Tile],
+        transparent_colour: Colour,
+        max_colours: usize,
+    ) -> (Vec<Tile>, ColourLoss) {
+        let palette = self.reduce(
+            &histogram(images.iter().flat_map(|image| &image.pixels)),
+            max_colours,
+        );
+
+        Self::remap_to_colours(images, &palette, transparent_colour)
+    }
+
+    /// Recolours each tile using whichever of the fixed palettes represents it
+    /// best, for palettes pinned by a palette file. The colours are already
+    /// chosen, so only the dithering of each tile matters.
+    pub(crate) fn quantise_to_palettes(
+        tiles: &[Tile],
+        palettes: &[Palette16],
+        transparent_colour: Colour,
+    ) -> QuantisedTiles {
+        let palette_colours: Vec<Vec<Colour>> = palettes
+            .iter()
+            .map(|palette| {
+                palette
+                    .colours()
+                    .copied()
+                    .filter(|colour| !colour.is_transparent())
+                    .collect()
+            })
+            .collect();
+
+        let assignments: Vec<usize> = tiles
+            .iter()
+            .map(|tile| {
+                (0..palettes.len())
+                    .filter(|&palette| !palette_colours[palette].is_empty())
+                    .min_by_key(|&palette| tile_error(tile, &palette_colours[palette]))
+                    .expect("Palette file has no opaque colours")
+            })
+            .collect();
+
+        let quantised_tiles: Vec<Tile> = tiles
+            .iter()
+            .zip(&assignments)
+            .map(|(tile, &palette)| Tile {
+                pixels: remap(tile, &palette_colours[palette], transparent_colour),
+                ..tile.clone()
+            })
+            .collect();
+
+        let loss = ColourLoss::new(
+            &tiles
+                .iter()
+                .map(|t| t.pixels.as_slice())
+                .collect::<Vec<_>>(),
+            &quantised_tiles
+                .iter()
+                .map(|t| t.pixels.as_slice())
+                .collect::<Vec<_>>(),
+            palette_colours.iter().flatten().count(),
+        );
+
+        QuantisedTiles {
+            results: Palette16OptimisationResults {
+                optimised_palettes: palettes.to_vec(),
+                assignments,
+                transparent_colour: Some(transparent_colour),
+            },
+            tiles: quantised_tiles,
+            loss,
+        }
+    }
+
+    /// Recolours the images using only the given opaque colours.
+    pub(crate) fn remap_to_colours(
+        images: &[Tile],
+        colours: &[Colour],
+        transparent_colour: Colour,
+    ) -> (Vec<Tile>, ColourLoss) {
+        let quantised: Vec<Tile> = images
+            .iter()
+            .map(|image| Tile {
+                pixels: remap(image, colours, transparent_colour),
+                ..image.clone()
+            })
+            .collect();
+
+        let loss = ColourLoss::new(
+            &images
+                .iter()
+                .map(|t| t.pixels.as_slice())
+                .collect::<Vec<_>>(),
+            &quantised
+                .iter()
+                .map(|t| t.pixels.as_slice())
+                .collect::<Vec<_>>(),
+            colours.len(),
+        );
+
+        (quantised, loss)
+    }
+
+    fn group_palettes(
+        self,
+        tiles: &[Tile],
+        groups: &[usize],
+        group_count: usize,
+    ) -> Vec<Vec<Colour>> {
+        (0..group_count)
+            .map(|group| {
+                let pixels = tiles
+                    .iter()
+                    .zip(groups)
+                    .filter(|&(_, &tile_group)| tile_group == group)
+                    .flat_map(|(tile, _)| &tile.pixels);
+
+                self.reduce(&histogram(pixels), OPAQUE_COLOURS_PER_PALETTE)
+            })
+            .collect()
+    }
+
+    /// Picks at most `max_colours` colours to represent the histogram. If
+    /// there are already few enough colours, they are kept exactly.
+    fn reduce(self, histogram: &[(Colour, usize)], max_colours: usize) -> Vec<Colour> {
+        if histogram.len() <= max_colours {
+            return histogram.iter().map(|&(colour, _)| colour).collect();
+        }
+
+        let colours = median_cut(histogram, max_colours);
+
+        let colours = match self.method {
+            Method::MedianCut => colours,
+            Method::KMeans => kmeans(histogram, colours),
+        };
+
+        let mut colours: Vec<_> = colours.into_iter().map(snap).collect();
+        colours.sort_by_key(|&colour| key(colour));
+        colours.dedup();
+        colours
+    }
+}
+
+/// Counts how often each opaque colour is used, after rounding each colour to
+/// what the Game Boy Advance can display. The result is sorted so that
+/// quantisation gives the same result on every build.
+fn histogram<'a>(pixels: impl Iterator<Item = &'a Colour>) -> Vec<(Colour, usize)> {
+    let mut counts: HashMap<Colour, usize> = HashMap::new();
+    for &colour in pixels {
+        if !colour.is_transparent() {
+            *counts.entry(snap(colour)).or_default() += 1;
+        }
+    }
+
+    let mut histogram: Vec<_> = counts.into_iter().collect();
+    histogram.sort_by_key(|&(colour, _)| key(colour));
+    histogram
+}
+
+fn median_cut(histogram: &[(Colour, usize)], max_colours: usize) -> Vec<Colour> {
+    let mut boxes = vec![histogram.to_vec()];
+
+    while boxes.len() < max_colours {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, colours)| colours.len() > 1)
+            .map(|(index, colours)| (index, widest_channel(colours)))
+            .max_by_key(|&(index, (_, range))| (range, std::cmp::Reverse(index)));
+
+        let Some((index, (channel, _))) = widest else {
+            break;
+        };
+
+        let mut colours = boxes.remove(index);
+        colours.sort_by_key(|&(colour, _)| (channel_value(colour, channel), key(colour)));
+
+        let total: usize = colours.iter().map(|&(_, count)| count).sum();
+        let mut seen = 0;
+        let split = colours
+            .iter()
+            .position(|&(_, count)| {
+                seen += count;
+                seen * 2 >= total
+            })
+            .map_or(1, |position| position + 1)
+            .clamp(1, colours.len() - 1);
+
+        let upper = colours.split_off(split);
+        boxes.push(colours);
+        boxes.push(upper);
+    }
+
+    boxes
+        .iter()
+        .map(|colours| weighted_average(colours))
+        .collect()
+}
+
+fn kmeans(histogram: &[(Colour, usize)], mut centres: Vec<Colour>) -> Vec<Colour> {
+    for _ in 0..KMEANS_ITERATIONS {
+        let mut clusters = vec![Vec::new(); centres.len()];
+
+        for &(colour, count) in histogram {
+            clusters[nearest(&centres, colour)].push((colour, count));
+        }
+
+        for (centre, cluster) in centres.iter_mut().zip(clusters) {
+            if !cluster.is_empty() {
+                *centre = weighted_average(&cluster);
+            }
+        }
+    }
+
+    centres
+}
+
+/// Recolours a tile using only colours from the palette, dithering if asked.
+fn remap(tile: &Tile, palette: &[Colour], transparent_colour: Colour) -> Vec<Colour> {
+    const BAYER: [[i32; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+    // Ordered dithering moves each channel by up to half of this either way
+    const ORDERED_SPREAD: i32 = 32;
+
+    let width = tile.width;
+    let height = tile.pixels.len() / width;
+
+    let mut errors = vec![[0i32; 3]; tile.pixels.len()];
+    let mut output = Vec::with_capacity(tile.pixels.len());
+
+    for (i, &colour) in tile.pixels.iter().enumerate() {
+        if colour.is_transparent() {
+            output.push(transparent_colour);
+            continue;
+        }
+
+        let (x, y) = (i % width, i / width);
+
+        let offset = match tile.dither {
+            Dither::None => [0; 3],
+            Dither::Ordered => {
+                let offset = (BAYER[y % 4][x % 4] * 2 - 15) * ORDERED_SPREAD / 32;
+                [offset; 3]
+            }
+            Dither::FloydSteinberg => errors[i].map(|error| error / 16),
+        };
+
+        let wanted = [
+            i32::from(colour.r) + offset[0],
+            i32::from(colour.g) + offset[1],
+            i32::from(colour.b) + offset[2],
+        ];
+
+        let chosen = palette[nearest_to(palette, wanted)];
+        output.push(chosen);
+
+        if tile.dither == Dither::FloydSteinberg {
+            let error = [
+                wanted[0] - i32::from(chosen.r),
+                wanted[1] - i32::from(chosen.g),
+                wanted[2] - i32::from(chosen.b),
+            ];
+
+            let mut spread = |dx: isize, dy: usize, weight: i32| {
+                let nx = x as isize + dx;
+                let ny = y + dy;
+                if nx < 0 || nx as usize >= width || ny >= height {
+                    return;
+                }
+
+                let target = &mut errors[ny * width + nx as usize];
+                for (target, error) in target.iter_mut().zip(error) {
+                    *target += error * weight;
+                }
+            };
+
+            spread(1, 0, 7);
+            spread(-1, 1, 3);
+            spread(0, 1, 5);
+            spread(1, 1, 1);
+        }
+    }
+
+    output
+}
+
+/// Whether a colour was changed by more than the rounding to what the Game
+/// Boy Advance can display.
+fn changed(from: Colour, to: Colour) -> bool {
+    from != to && snap(from) != to
+}
+
+fn tile_error(tile: &Tile, palette: &[Colour]) -> u64 {
+    tile.pixels
+        .iter()
+        .filter(|colour| !colour.is_transparent())
+        .map(|&colour| {
+            let nearest = palette[nearest(palette, colour)];
+            u64::from(distance(colour, nearest))
+        })
+        .sum()
+}
+
+fn nearest(palette: &[Colour], colour: Colour) -> usize {
+    nearest_to(
+        palette,
+        [
+            i32::from(colour.r),
+            i32::from(colour.g),
+            i32::from(colour.b),
+        ],
+    )
+}
+
+fn nearest_to(palette: &[Colour], wanted: [i32; 3]) -> usize {
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, colour)| {
+            let dr = i32::from(colour.r) - wanted[0];
+            let dg = i32::from(colour.g) - wanted[1];
+            let db = i32::from(colour.b) - wanted[2];
+            dr * dr + dg * dg + db * db
+        })
+        .map(|(index, _)| index)
+        .expect("palette should have at least one colour")
+}
+
+fn distance(a: Colour, b: Colour) -> u32 {
+    let dr = i32::from(a.r) - i32::from(b.r);
+    let dg = i32::from(a.g) - i32::from(b.g);
+    let db = i32::from(a.b) - i32::from(b.b);
+    (dr * dr + dg * dg + db * db) as u32
+}
+
+fn widest_channel(colours: &[(Colour, usize)]) -> (usize, u8) {
+    (0..3)
+        .map(|channel| {
+            let values = colours
+                .iter()
+                .map(|&(colour, _)| channel_value(colour, channel));
+            let range = values.clone().max().unwrap() - values.min().unwrap();
+            (channel, range)
+        })
+        .max_by_key(|&(channel, range)| (range, std::cmp::Reverse(channel)))
+        .unwrap()
+}
+
+fn channel_value(colour: Colour, channel: usize) -> u8 {
+    match channel {
+        0 => colour.r,
+        1 => colour.g,
+        _ => colour.b,
+    }
+}
+
+fn weighted_average(colours: &[(Colour, usize)]) -> Colour {
+    let total: usize = colours.iter().map(|&(_, count)| count).sum();
+    let channel = |channel| {
+        let sum: usize = colours
+            .iter()
+            .map(|&(colour, count)| usize::from(channel_value(colour, channel)) * count)
+            .sum();
+        ((sum + total / 2) / total) as u8
+    };
+
+    Colour::from_rgb(channel(0), channel(1), channel(2), 255)
+}
+
+fn average(pixels: &[Colour]) -> Colour {
+    let histogram = histogram(pixels.iter());
+    if histogram.is_empty() {
+        return Colour::from_rgb(0, 0, 0, 255);
+    }
+
+    weighted_average(&histogram)
+}
+
+fn brightness(colour: Colour) -> u32 {
+    u32::from(colour.r) * 77 + u32::from(colour.g) * 150 + u32::from(colour.b) * 29
+}
+
+/// Rounds the colour to the nearest one the Game Boy Advance can display, so
+/// that no palette slots are wasted on colours which look the same.
+fn snap(colour: Colour) -> Colour {
+    let channel = |value: u8| {
+        let five_bit = value >> 3;
+        five_bit << 3 | five_bit >> 2
+    };
+
+    Colour::from_rgb(channel(colour.r), channel(colour.g), channel(colour.b), 255)
+}
+
+fn key(colour: Colour) -> (u8, u8, u8) {
+    (colour.r, colour.g, colour.b)
+}
+
+/// The colour as it would be written in an art program, such as `#ff00ff`.
+pub(crate) fn hex(colour: Colour) -> String {
+    format!("#{:02x}{:02x}{:02x}", colour.r, colour.g, colour.b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSPARENT: Colour = Colour {
+        r: 255,
+        g: 0,
+        b: 255,
+        a: 0,
+    };
+
+    fn gradient_tile(offset: u8, dither: Dither) -> Tile {
+        Tile {
+            width: 8,
+            pixels: (0..64)
+                .map(|i| Colour::from_rgb(i as u8 * 4, offset, 255 - i as u8 * 4, 255))
+                .collect(),
+            dither,
+        }
+    }
+
+    fn quantise(method: Method, dither: Dither) -> Quantise {
+        Quantise { method, dither }
+    }
+
+    #[test]
+    fn tiles_fit_in_the_palette_budget() {
+        for (method, dither) in [
+            (Method::MedianCut, Dither::None),
+            (Method::KMeans, Dither::Ordered),
+            (Method::MedianCut, Dither::FloydSteinberg),
+        ] {
+            let tiles: Vec<_> = (0..20).map(|i| gradient_tile(i * 12, dither)).collect();
+
+            let quantised = quantise(method, dither).quantise_tiles(&tiles, TRANSPARENT, 4);
+
+            assert!(quantised.results.optimised_palettes.len() <= 4);
+            assert!(quantised.loss.is_lossy());
+
+            for (tile, &assignment) in quantised.tiles.iter().zip(&quantised.results.assignments) {
+                let palette = &quantised.results.optimised_palettes[assignment];
+                assert_eq!(palette.colours().next(), Some(&TRANSPARENT));
+                assert!(palette.colours().count() <= 16);
+
+                for colour in &tile.pixels {
+                    assert!(palette.colours().any(|c| c == colour));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn art_which_fits_is_unchanged() {
+        let red = Colour::from_rgb(255, 0, 0, 255);
+        let blue = Colour::from_rgb(0, 0, 255, 255);
+
+        let tile = Tile {
+            width: 2,
+            pixels: vec![red, blue, TRANSPARENT, red],
+            dither: Dither::FloydSteinberg,
+        };
+
+        let quantised = quantise(Method::KMeans, Dither::FloydSteinberg).quantise_tiles(
+            std::slice::from_ref(&tile),
+            TRANSPARENT,
+            16,
+        );
+
+        assert_eq!(quantised.tiles[0].pixels, tile.pixels);
+        assert!(!quantised.loss.is_lossy());
+        assert!(quantised.loss.warning("tile").is_empty());
+    }
+
+    #[test]
+    fn shared_palette_is_limited() {
+        let image = gradient_tile(0, Dither::None);
+
+        let (quantised, loss) =
+            quantise(Method::MedianCut, Dither::None).quantise_shared(&[image], TRANSPARENT, 8);
+
+        let colours: std::collections::HashSet<_> = quantised[0].pixels.iter().collect();
+        assert!(colours.len() <= 8);
+        assert!(loss.to_string().starts_with("reduced 64 colours"));
+        assert!(loss.warning("gradient").to_string().contains("deprecated"));
+    }
+
+    #[test]
+    fn losses_are_merged() {
+        let image = gradient_tile(0, Dither::None);
+        let method = quantise(Method::MedianCut, Dither::None);
+
+        let (_, mut loss) = method.quantise_shared(std::slice::from_ref(&image), TRANSPARENT, 8);
+        let (_, other) = method.quantise_shared(&[image], TRANSPARENT, 4);
+        let changed = loss.changed_pixels + other.changed_pixels;
+
+        loss.merge(other);
+
+        assert_eq!(loss.total_pixels, 128);
+        assert_eq!(loss.changed_pixels, changed);
+        assert_eq!(loss.worst.len(), 5);
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+
+use crate::aseprite::FrameSlice;
+
+/// The number of samples taken along each axis for every output pixel.
+const SUBSAMPLES: u32 = 3;
+
+/// Rotates the image about its centre by the given number of turns, keeping
+/// the same size. Anything rotated outside of the image is clipped.
+///
+/// The rotation matches `AffineMatrix::from_rotation` in agb, so a pre-rotated
+/// frame looks the same as the original frame displayed with an affine matrix.
+///
+/// Each output pixel takes the most common colour of a grid of samples, rather
+/// than blending them, so that no new colours are introduced and the result
+/// stays crisp.
+pub(crate) fn rotate(image: &DynamicImage, turns: f64) -> DynamicImage {
+    let (width, height) = image.dimensions();
+    let (sin, cos) = (turns * std::f64::consts::TAU).sin_cos();
+
+    let centre_x = f64::from(width) / 2.0;
+    let centre_y = f64::from(height) / 2.0;
+
+    let mut rotated = RgbaImage::new(width, height);
+
+    for (x, y, pixel) in rotated.enumerate_pixels_mut() {
+        let mut counts: HashMap<[u8; 4], usize> = HashMap::new();
+        let mut centre_sample = None;
+
+        for j in 0..SUBSAMPLES {
+            for i in 0..SUBSAMPLES {
+                let screen_x =
+                    f64::from(x) + (f64::from(i) + 0.5) / f64::from(SUBSAMPLES) - centre_x;
+                let screen_y =
+                    f64::from(y) + (f64::from(j) + 0.5) / f64::from(SUBSAMPLES) - centre_y;
+
+                let texture_x = (cos * screen_x - sin * screen_y + centre_x).floor();
+                let texture_y = (sin * screen_x + cos * screen_y + centre_y).floor();
+
+                let sample = if texture_x >= 0.0
+                    && texture_y >= 0.0
+                    && texture_x < f64::from(width)
+                    && texture_y < f64::from(height)
+                {
+                    let sample = image.get_pixel(texture_x as u32, texture_y as u32);
+                    if sample[3] == 0 {
+                        Rgba([0, 0, 0, 0])
+                    } else {
+                        sample
+                    }
+                } else {
+                    Rgba([0, 0, 0, 0])
+                };
+
+                if i == SUBSAMPLES / 2 && j == SUBSAMPLES / 2 {
+                    centre_sample = Some(sample);
+                }
+
+                *counts.entry(sample.0).or_default() += 1;
+            }
+        }
+
+        let centre_sample = centre_sample.expect("the centre is always sampled");
+        let centre_count = counts[&centre_sample.0];
+
+        // Ties go to the sample in the centre of the pixel
+        *pixel = counts
+            .into_iter()
+            .filter(|&(_, count)| count > centre_count)
+            .max_by_key(|&(_, count)| count)
+            .map_or(centre_sample, |(colour, _)| Rgba(colour));
+    }
+
+    DynamicImage::ImageRgba8(rotated)
+}
+
+/// Rotates the slices of a frame of the given size in the same way as
+/// [`rotate`]. Each slice becomes the smallest rectangle covering its rotated
+/// corners, and the pivot moves with the pixel it is on.
+pub(crate) fn rotate_slices(
+    slices: &[FrameSlice],
+    (width, height): (u32, u32),
+    turns: f64,
+) -> Vec<FrameSlice> {
+    let (sin, cos) = (turns * std::f64::consts::TAU).sin_cos();
+
+    let centre_x = f64::from(width) / 2.0;
+    let centre_y = f64::from(height) / 2.0;
+
+    // The inverse of the rotation in `rotate`, which maps the output back to
+    // the original image
+    let rotate_point = |x: f64, y: f64| {
+        let (x, y) = (x - centre_x, y - centre_y);
+        (cos * x + sin * y + centre_x, cos * y - sin * x + centre_y)
+    };
+
+    // Allows for rounding errors, so a corner on a pixel edge stays there
+    const EPSILON: f64 = 1e-6;
+
+    slices
+        .iter()
+        .map(|slice| {
+            let (left, top) = (f64::from(slice.x), f64::from(slice.y));
+            let right = left + f64::from(slice.width);
+            let bottom = top + f64::from(slice.height);
+
+            let corners = [(left, top), (right, top), (left, bottom), (right, bottom)]
+                .map(|(x, y)| rotate_point(x, y));
+
+            let min_x = corners.iter().map(|&(x, _)| x).fold(f64::MAX, f64::min);
+            let max_x = corners.iter().map(|&(x, _)| x).fold(f64::MIN, f64::max);
+            let min_y = corners.iter().map(|&(_, y)| y).fold(f64::MAX, f64::min);
+            let max_y = corners.iter().map(|&(_, y)| y).fold(f64::MIN, f64::max);
+
+            let x = (min_x + EPSILON).floor() as i32;
+            let y = (min_y + EPSILON).floor() as i32;
+
+            FrameSlice {
+                name: slice.name.clone(),
+                x,
+                y,
+                width: ((max_x - EPSILON).ceil() as i32 - x) as u32,
+                height: ((max_y - EPSILON).ceil() as i32 - y) as u32,
+                pivot: slice.pivot.map(|(pivot_x, pivot_y)| {
+                    let (x, y) = rotate_point(f64::from(pivot_x) + 0.5, f64::from(pivot_y) + 0.5);
+                    (x.floor() as i32, y.floor() as i32)
+                }),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_image() -> DynamicImage {
+        let mut image = RgbaImage::new(8, 8);
+        for (x, y, pixel) in image.enumerate_pixels_mut() {
+            *pixel = Rgba([x as u8 * 16, y as u8 * 16, 0, 255]);
+        }
+
+        DynamicImage::ImageRgba8(image)
+    }
+
+    #[test]
+    fn no_rotation_keeps_the_image() {
+        let image = test_image();
+
+        assert_eq!(rotate(&image, 0.0).to_rgba8(), image.to_rgba8());
+    }
+
+    #[test]
+    fn quarter_turn_matches_affine_rotation() {
+        let image = test_image();
+        let rotated = rotate(&image, 0.25);
+
+        // The top left of the rotated image comes from the top right of the
+        // original image, and the bottom left from the top left
+        assert_eq!(rotated.get_pixel(0, 0), image.get_pixel(7, 0));
+        assert_eq!(rotated.get_pixel(0, 7), image.get_pixel(0, 0));
+    }
+
+    #[test]
+    fn slices_rotate_with_the_image() {
+        let slice = FrameSlice {
+            name: "hand".to_owned(),
+            x: 0,
+            y: 0,
+            width: 4,
+            height: 2,
+            pivot: Some((1, 0)),
+        };
+
+        let rotated = rotate_slices(&[slice], (16, 16), 0.25);
+        let rotated = &rotated[0];
+
+        assert_eq!(rotated.name, "hand");
+        assert_eq!(
+            (rotated.x, rotated.y, rotated.width, rotated.height),
+            (0, 12, 2, 4)
+        );
+        assert_eq!(rotated.pivot, Some((0, 14)));
+
+        // The pivot is on the same pixel of the rotated image
+        let mut image = RgbaImage::new(16, 16);
+        image.put_pixel(1, 0, Rgba([255, 0, 0, 255]));
+        let image = rotate(&DynamicImage::ImageRgba8(image), 0.25);
+        assert_eq!(image.get_pixel(0, 14), Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn rotation_introduces_no_new_colours() {
+        let image = test_image();
+        let rotated = rotate(&image, 0.1);
+
+        let original: Vec<_> = image.pixels().map(|(_, _, pixel)| pixel).collect();
+        assert!(rotated
+            .pixels()
+            .all(|(_, _, pixel)| pixel[3] == 0 || original.contains(&pixel)));
+    }
+}
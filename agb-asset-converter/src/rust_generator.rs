@@ -0,0 +1,229 @@
+use crate::background::{ConvertedBackground, TileFormat, TileSetting};
+use crate::colour::Colour;
+use crate::deduplicator::{DeduplicatedData, Transformation};
+use crate::palette16::{Palette16, Palette16OptimisationResults};
+use crate::palette_variant::PaletteVariant;
+use crate::{add_image_256_to_tile_data, add_image_to_tile_data, collapse_to_4bpp};
+use crate::{image_loader::Image, ByteString};
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub(crate) fn generate_palette_code(
+    results: &Palette16OptimisationResults,
+    crate_prefix: &str,
+) -> TokenStream {
+    let crate_prefix = format_ident!("{}", crate_prefix);
+    let palettes = palettes(&palette_data(results, |colour| colour), &crate_prefix);
+
+    quote! {
+        pub const PALETTES: &[#crate_prefix::display::palette16::Palette16] = &[#(#palettes),*];
+    }
+}
+
+/// Generates a `<NAME>_PALETTES` constant with every palette recoloured by the
+/// variant, in the same order as `PALETTES`.
+pub(crate) fn generate_palette_variant_code(
+    results: &Palette16OptimisationResults,
+    crate_prefix: &str,
+    name: &syn::Ident,
+    variant: &PaletteVariant,
+) -> TokenStream {
+    let crate_prefix = format_ident!("{}", crate_prefix);
+    let palettes = palettes(
+        &palette_data(results, |colour| variant.apply(colour)),
+        &crate_prefix,
+    );
+
+    let constant_name = format_ident!("{}_PALETTES", name.to_string().to_uppercase());
+    let include_remap = variant.file_name().map(|file_name| {
+        let file_name = file_name.to_string_lossy();
+        quote! {
+            const _: &[u8] = include_bytes!(#file_name);
+        }
+    });
+
+    quote! {
+        #include_remap
+        pub const #constant_name: &[#crate_prefix::display::palette16::Palette16] = &[#(#palettes),*];
+    }
+}
+
+/// The optimised palettes as 15-bit colours, recoloured and padded to 16
+/// colours each.
+pub(crate) fn palette_data(
+    results: &Palette16OptimisationResults,
+    recolour: impl Fn(Colour) -> Colour,
+) -> Vec<[u16; 16]> {
+    results
+        .optimised_palettes
+        .iter()
+        .map(|palette| palette_colours(palette, &recolour))
+        .collect()
+}
+
+pub(crate) fn palette_colours(
+    palette: &Palette16,
+    recolour: impl Fn(Colour) -> Colour,
+) -> [u16; 16] {
+    let mut colours = [0; 16];
+    for (colour, palette_colour) in colours.iter_mut().zip(palette.clone()) {
+        *colour = recolour(palette_colour).to_rgb15();
+    }
+    colours
+}
+
+fn palettes(palettes: &[[u16; 16]], crate_prefix: &syn::Ident) -> Vec<TokenStream> {
+    palettes
+        .iter()
+        .map(|colours| {
+            quote! {
+                #crate_prefix::display::palette16::Palette16::new([
+                    #(#crate_prefix::display::Rgb15::new(#colours)),*
+                ])
+            }
+        })
+        .collect()
+}
+
+/// Splits the image into its tiles, deduplicating them if asked to. Images
+/// with an assignment offset use the 16 colour palettes from that offset
+/// onwards, and the others are 256 colour images.
+pub(crate) fn convert_image(
+    name: &str,
+    results: &Palette16OptimisationResults,
+    image: &Image,
+    image_filename: &Path,
+    assignment_offset: Option<usize>,
+    deduplicate: bool,
+) -> ConvertedBackground {
+    let (width, height) = (image.width, image.height);
+
+    let (image, dedup_data) = if deduplicate {
+        let (new_image, dedup_data) =
+            crate::deduplicator::deduplicate_image(image, assignment_offset.is_some());
+
+        (new_image, dedup_data)
+    } else {
+        (
+            image.clone(),
+            (0..(image.width * image.height / 8 / 8))
+                .map(|i| DeduplicatedData {
+                    new_index: i,
+                    transformation: Transformation::none(),
+                })
+                .collect(),
+        )
+    };
+
+    let remap_index = dedup_data
+        .iter()
+        .enumerate()
+        .map(|(i, data)| (data.new_index, i))
+        .collect::<BTreeMap<_, _>>(); // BTreeMap so that values below is in order
+
+    let remap_index = remap_index.values().cloned().collect::<Vec<_>>();
+
+    let (tile_data, assignments) = if let Some(assignment_offset) = assignment_offset {
+        let mut tile_data = Vec::new();
+
+        add_image_to_tile_data(
+            &mut tile_data,
+            &image,
+            results,
+            assignment_offset,
+            false,
+            &remap_index,
+        );
+
+        let tile_data = collapse_to_4bpp(&tile_data);
+
+        let num_tiles = image.width * image.height / 8usize.pow(2);
+
+        let all_assignments = &results.assignments[assignment_offset..];
+        let assignments = (0..num_tiles)
+            .map(|tile_id| all_assignments[remap_index[tile_id]] as u8)
+            .collect();
+
+        (tile_data, assignments)
+    } else {
+        let mut tile_data = Vec::new();
+
+        add_image_256_to_tile_data(&mut tile_data, &image, results);
+
+        (tile_data, vec![])
+    };
+
+    let tile_settings = dedup_data
+        .iter()
+        .map(|data| TileSetting {
+            tile_id: data.new_index as u16,
+            hflip: data.transformation.hflip,
+            vflip: data.transformation.vflip,
+            palette_id: *assignments.get(data.new_index).unwrap_or(&0),
+        })
+        .collect();
+
+    ConvertedBackground {
+        name: name.to_owned(),
+        file: image_filename.to_owned(),
+        width,
+        height,
+        tile_format: if assignment_offset.is_some() {
+            TileFormat::FourBpp
+        } else {
+            TileFormat::EightBpp
+        },
+        tiles: tile_data,
+        tile_settings,
+    }
+}
+
+pub(crate) fn generate_code(image: &ConvertedBackground, crate_prefix: &str) -> TokenStream {
+    let crate_prefix = format_ident!("{}", crate_prefix);
+    let output_variable_name = format_ident!("{}", image.name);
+    let image_filename = image.file.to_string_lossy();
+
+    let tile_settings = image
+        .tile_settings
+        .iter()
+        .map(|setting| setting.to_tokens(&crate_prefix));
+
+    let data = ByteString(&image.tiles);
+    let tile_format = match image.tile_format {
+        TileFormat::FourBpp => quote! { #crate_prefix::display::tiled::TileFormat::FourBpp },
+        TileFormat::EightBpp => quote! { #crate_prefix::display::tiled::TileFormat::EightBpp },
+    };
+
+    quote! {
+        #[allow(non_upper_case_globals)]
+        pub const #output_variable_name: #crate_prefix::display::tile_data::TileData = {
+            const _: &[u8] = include_bytes!(#image_filename);
+
+            const TILE_DATA: &[u8] = {
+                pub struct AlignedAs<Align, Bytes: ?Sized> {
+                    pub _align: [Align; 0],
+                    pub bytes: Bytes,
+                }
+
+                const ALIGNED: &AlignedAs<u32, [u8]> = &AlignedAs {
+                    _align: [],
+                    bytes: *#data,
+                };
+
+                &ALIGNED.bytes
+            };
+
+            const TILE_SET: #crate_prefix::display::tiled::TileSet = #crate_prefix::display::tiled::TileSet::new(TILE_DATA, #tile_format);
+
+            const TILE_SETTINGS: &[#crate_prefix::display::tiled::TileSetting] = &[
+                #(#tile_settings),*
+            ];
+
+            #crate_prefix::display::tile_data::TileData::new(TILE_SET, TILE_SETTINGS)
+        };
+    }
+}
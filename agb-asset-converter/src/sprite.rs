@@ -0,0 +1,170 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use proc_macro2::{Span, TokenStream};
+use quote::format_ident;
+
+use crate::pinned_palettes::PinnedPalettes;
+use crate::{AsepriteFileOption, Error};
+
+/// Converts aseprite files in the same way as `include_aseprite!`, for use
+/// from a build script. File names are relative to the crate root.
+///
+/// ```no_run
+/// use agb_asset_converter::SpriteConverter;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let sprites = SpriteConverter::new()
+///     .aseprite("gfx/player.aseprite")
+///     .convert()?;
+///
+/// let out_dir = std::env::var("OUT_DIR")?;
+/// sprites.write_binaries(&out_dir)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct SpriteConverter {
+    palettes: Option<PinnedPalettes>,
+    files: Vec<AsepriteFileOption>,
+}
+
+impl SpriteConverter {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses the palettes in the given palette image rather than finding new
+    /// ones, in the same way as the `palettes` setting of `include_aseprite!`.
+    pub fn palettes(mut self, file_name: &str) -> Result<Self, Error> {
+        self.palettes = Some(PinnedPalettes::load(file_name, Span::call_site())?);
+        Ok(self)
+    }
+
+    /// Adds an aseprite file. All the sprites share the same palettes.
+    #[must_use]
+    pub fn aseprite(mut self, file_name: &str) -> Self {
+        self.files.push(AsepriteFileOption {
+            file_name: file_name.to_owned(),
+            layers: Default::default(),
+            tag_prefix: String::new(),
+            rotations: 1,
+            palette_variants: Vec::new(),
+            quantise: None,
+        });
+        self
+    }
+
+    /// Converts the sprites, finding the palettes and the tiles of each frame.
+    pub fn convert(&self) -> Result<ConvertedSprites, Error> {
+        let (frames, tags, filenames) =
+            crate::load_aseprite_files(&self.files, self.palettes.as_ref())?;
+        let data = crate::sprite_data(&frames, self.palettes.as_ref())?;
+
+        let mut tiles = data.tile_data.chunks(32);
+        let sprites = frames
+            .iter()
+            .zip(&data.assignments)
+            .map(|(frame, &palette)| {
+                let (width, height) = (frame.image.width, frame.image.height);
+                ConvertedSprite {
+                    width,
+                    height,
+                    palette,
+                    duration: frame.duration,
+                    tiles: tiles
+                        .by_ref()
+                        .take(width / 8 * height / 8)
+                        .flatten()
+                        .copied()
+                        .collect(),
+                }
+            })
+            .collect();
+
+        let converted_tags = tags
+            .iter()
+            .map(|tag| ConvertedTag {
+                name: tag.name.clone(),
+                start: tag.start,
+                end: tag.end,
+                direction: tag.direction,
+            })
+            .collect();
+
+        Ok(ConvertedSprites {
+            palettes: data.palettes.clone(),
+            sprites,
+            tags: converted_tags,
+            graphics: crate::sprite_code(&frames, &tags, &filenames, &data),
+        })
+    }
+}
+
+/// The result of a [`SpriteConverter`].
+pub struct ConvertedSprites {
+    /// The palettes shared by all of the sprites, as 15-bit colours.
+    pub palettes: Vec<[u16; 16]>,
+    /// Every frame of every file, in order.
+    pub sprites: Vec<ConvertedSprite>,
+    pub tags: Vec<ConvertedTag>,
+    graphics: TokenStream,
+}
+
+impl ConvertedSprites {
+    /// Rust source for a static with the same `Graphics` that
+    /// `include_aseprite!` generates, ready to be written to `OUT_DIR` and
+    /// included with `include!`.
+    #[must_use]
+    pub fn to_rust(&self, static_name: &str) -> String {
+        crate::graphics_static(
+            &format_ident!("agb"),
+            &format_ident!("{}", static_name),
+            &self.graphics,
+        )
+        .to_string()
+    }
+
+    /// Writes the palettes to `palettes.bin` and the tiles of every sprite,
+    /// one after the other, to `sprites.bin` in the directory. All the values
+    /// are little endian, in the format the Game Boy Advance expects in
+    /// memory.
+    pub fn write_binaries(&self, directory: impl AsRef<Path>) -> io::Result<()> {
+        let directory = directory.as_ref();
+
+        fs::write(
+            directory.join("palettes.bin"),
+            crate::u16_bytes(self.palettes.iter().flatten().copied()),
+        )?;
+        fs::write(
+            directory.join("sprites.bin"),
+            self.sprites
+                .iter()
+                .flat_map(|sprite| sprite.tiles.iter().copied())
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+/// A single frame of a sprite.
+pub struct ConvertedSprite {
+    pub width: usize,
+    pub height: usize,
+    /// The index of the sprite's palette in [`ConvertedSprites::palettes`].
+    pub palette: usize,
+    /// How many frames the sprite should be shown for.
+    pub duration: u16,
+    /// The 4bpp tile data, 32 bytes per tile.
+    pub tiles: Vec<u8>,
+}
+
+/// A named range of sprites from an aseprite tag.
+pub struct ConvertedTag {
+    pub name: String,
+    pub start: usize,
+    pub end: usize,
+    /// 0 for forward, 1 for reverse and 2 for ping pong.
+    pub direction: usize,
+}
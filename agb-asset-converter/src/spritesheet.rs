@@ -0,0 +1,253 @@
+use std::path::Path;
+
+use image::{DynamicImage, GenericImageView};
+use proc_macro2::Span;
+use syn::{parse::Parse, LitInt, LitStr, Token};
+
+use crate::colour::Colour;
+
+/// The settings passed to `include_spritesheet!`, for example
+/// `"player.png", 16, 16, spacing: 1, tags: { "walk": 0..4 }`.
+pub(crate) struct SpritesheetOption {
+    pub file_name: String,
+    pub frame_width: u32,
+    pub frame_height: u32,
+    pub margin: u32,
+    pub spacing: u32,
+    pub transparent_colour: Option<Colour>,
+    pub tags: Vec<SpritesheetTag>,
+    size_span: Span,
+}
+
+pub(crate) struct SpritesheetTag {
+    pub name: String,
+    pub from_frame: usize,
+    pub to_frame: usize,
+    pub direction: usize,
+    span: Span,
+}
+
+impl Parse for SpritesheetOption {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let file_name: LitStr = input.parse()?;
+        let _: Token![,] = input.parse()?;
+        let frame_width: LitInt = input.parse()?;
+        let _: Token![,] = input.parse()?;
+        let frame_height: LitInt = input.parse()?;
+
+        let mut option = Self {
+            file_name: file_name.value(),
+            frame_width: frame_width.base10_parse()?,
+            frame_height: frame_height.base10_parse()?,
+            margin: 0,
+            spacing: 0,
+            transparent_colour: None,
+            tags: Vec::new(),
+            size_span: frame_width.span(),
+        };
+
+        if !crate::valid_sprite_size(option.frame_width, option.frame_height) {
+            return Err(syn::Error::new(
+                option.size_span,
+                format!(
+                    "Frames of size {}x{} cannot be represented on the GameBoy Advance",
+                    option.frame_width, option.frame_height
+                ),
+            ));
+        }
+
+        while !input.is_empty() {
+            let _: Token![,] = input.parse()?;
+            if input.is_empty() {
+                break;
+            }
+
+            let setting: syn::Ident = input.parse()?;
+            let _: Token![:] = input.parse()?;
+
+            match setting.to_string().as_str() {
+                "margin" => option.margin = input.parse::<LitInt>()?.base10_parse()?,
+                "spacing" => option.spacing = input.parse::<LitInt>()?.base10_parse()?,
+                "transparent_colour" => {
+                    let colour: LitStr = input.parse()?;
+                    option.transparent_colour = Some(
+                        colour
+                            .value()
+                            .parse()
+                            .map_err(|e| syn::Error::new_spanned(colour, e))?,
+                    );
+                }
+                "tags" => option.tags = parse_tags(input)?,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        setting,
+                        "Unknown setting, expected margin, spacing, transparent_colour or tags",
+                    ))
+                }
+            }
+        }
+
+        Ok(option)
+    }
+}
+
+fn parse_tags(input: syn::parse::ParseStream) -> syn::Result<Vec<SpritesheetTag>> {
+    let content;
+    syn::braced!(content in input);
+
+    let mut tags = Vec::new();
+
+    while !content.is_empty() {
+        let name: LitStr = content.parse()?;
+        let _: Token![:] = content.parse()?;
+
+        let from_frame: usize = content.parse::<LitInt>()?.base10_parse()?;
+        let to_frame = if content.peek(Token![..=]) {
+            let _: Token![..=] = content.parse()?;
+            content.parse::<LitInt>()?.base10_parse()?
+        } else {
+            let _: Token![..] = content.parse()?;
+            let end: LitInt = content.parse()?;
+            let end: usize = end.base10_parse()?;
+            if end <= from_frame {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!("Tag {} has no frames", name.value()),
+                ));
+            }
+            end - 1
+        };
+
+        // These match the values of the animation directions in agb
+        let direction = if content.peek(syn::Ident) {
+            let direction: syn::Ident = content.parse()?;
+            match direction.to_string().as_str() {
+                "forward" => 0,
+                "reverse" => 1,
+                "pingpong" => 2,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        direction,
+                        "Unknown direction, expected one of forward, reverse or pingpong",
+                    ))
+                }
+            }
+        } else {
+            0
+        };
+
+        if from_frame > to_frame {
+            return Err(syn::Error::new(
+                name.span(),
+                format!("Tag {} has start > end", name.value()),
+            ));
+        }
+
+        tags.push(SpritesheetTag {
+            name: name.value(),
+            from_frame,
+            to_frame,
+            direction,
+            span: name.span(),
+        });
+
+        if content.is_empty() {
+            break;
+        }
+
+        let _: Token![,] = content.parse()?;
+    }
+
+    Ok(tags)
+}
+
+/// Cuts the sprite sheet into frames, reading them left to right and then top
+/// to bottom.
+pub(crate) fn generate_from_file(
+    filename: &Path,
+    option: &SpritesheetOption,
+) -> syn::Result<Vec<DynamicImage>> {
+    let mut image = image::open(filename)
+        .unwrap_or_else(|e| panic!("Failed to open {}: {e}", filename.display()));
+
+    if let Some(transparent_colour) = option.transparent_colour {
+        image = DynamicImage::ImageRgba8(make_transparent(&image, transparent_colour));
+    }
+
+    let (width, height) = image.dimensions();
+
+    let columns = frame_count(width, option.frame_width, option.margin, option.spacing);
+    let rows = frame_count(height, option.frame_height, option.margin, option.spacing);
+
+    if columns == 0 || rows == 0 {
+        return Err(syn::Error::new(
+            option.size_span,
+            format!(
+                "File {} of size {}x{} is too small to contain any {}x{} frames",
+                filename.display(),
+                width,
+                height,
+                option.frame_width,
+                option.frame_height
+            ),
+        ));
+    }
+
+    let frames: Vec<_> = (0..rows)
+        .flat_map(|row| (0..columns).map(move |column| (column, row)))
+        .map(|(column, row)| {
+            image.crop_imm(
+                option.margin + column * (option.frame_width + option.spacing),
+                option.margin + row * (option.frame_height + option.spacing),
+                option.frame_width,
+                option.frame_height,
+            )
+        })
+        .collect();
+
+    if let Some(tag) = option.tags.iter().find(|tag| tag.to_frame >= frames.len()) {
+        return Err(syn::Error::new(
+            tag.span,
+            format!(
+                "Tag {} ends at frame {} but {} only contains {} frames",
+                tag.name,
+                tag.to_frame,
+                filename.display(),
+                frames.len()
+            ),
+        ));
+    }
+
+    Ok(frames)
+}
+
+fn frame_count(length: u32, frame_length: u32, margin: u32, spacing: u32) -> u32 {
+    let usable = length.saturating_sub(2 * margin) + spacing;
+    usable / (frame_length + spacing)
+}
+
+fn make_transparent(image: &DynamicImage, transparent_colour: Colour) -> image::RgbaImage {
+    let mut image = image.to_rgba8();
+
+    for pixel in image.pixels_mut() {
+        if Colour::from_rgb(pixel[0], pixel[1], pixel[2], 255) == transparent_colour {
+            pixel[3] = 0;
+        }
+    }
+
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_frames_with_margin_and_spacing() {
+        assert_eq!(frame_count(64, 8, 0, 0), 8);
+        assert_eq!(frame_count(64, 16, 1, 2), 3);
+        assert_eq!(frame_count(54, 16, 1, 2), 3);
+        assert_eq!(frame_count(53, 16, 1, 2), 2);
+        assert_eq!(frame_count(4, 8, 0, 0), 0);
+    }
+}
@@ -0,0 +1,333 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{LitStr, Token};
+
+/// The input to `include_strings!`, which is an optional `crate` prefix, the
+/// name of the module to generate and then each language with its file, such
+/// as `en => "strings/en.toml"`.
+struct IncludeStringsInput {
+    crate_prefix: syn::Ident,
+    module_name: syn::Ident,
+    languages: Punctuated<LanguageFile, Token![,]>,
+}
+
+impl Parse for IncludeStringsInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let crate_prefix = if input.peek(Token![crate]) {
+            let _: Token![crate] = input.parse()?;
+            let _: Token![,] = input.parse()?;
+            format_ident!("crate")
+        } else {
+            format_ident!("agb")
+        };
+
+        let module_name = input.parse()?;
+        let _: Token![,] = input.parse()?;
+
+        let languages = Punctuated::parse_terminated(input)?;
+        if languages.is_empty() {
+            return Err(input.error("Expected at least one language"));
+        }
+
+        Ok(Self {
+            crate_prefix,
+            module_name,
+            languages,
+        })
+    }
+}
+
+struct LanguageFile {
+    name: syn::Ident,
+    file_name: LitStr,
+}
+
+impl Parse for LanguageFile {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        let _: Token![=>] = input.parse()?;
+        let file_name = input.parse()?;
+
+        Ok(Self { name, file_name })
+    }
+}
+
+/// Loads the strings of a TOML string table, sorted by key. Nested tables are
+/// flattened, so `start` in the `[menu]` table has the key `menu.start`.
+pub(crate) fn load_strings(path: &Path) -> Result<Vec<(String, String)>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let table: toml::value::Table =
+        toml::from_str(&content).map_err(|e| format!("Invalid strings file: {e}"))?;
+
+    let mut strings = BTreeMap::new();
+    flatten("", table, &mut strings)?;
+
+    Ok(strings.into_iter().collect())
+}
+
+fn flatten(
+    prefix: &str,
+    table: toml::value::Table,
+    strings: &mut BTreeMap<String, String>,
+) -> Result<(), String> {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key
+        } else {
+            format!("{prefix}.{key}")
+        };
+
+        match value {
+            toml::Value::String(value) => {
+                strings.insert(key, value);
+            }
+            toml::Value::Table(table) => flatten(&key, table, strings)?,
+            _ => return Err(format!("Expected {key} to be a string")),
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts a key such as `menu.start_game` to `MenuStartGame`.
+fn variant_name(key: &str) -> String {
+    key.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// The string of each key in each language, which must all have the same
+/// keys as the first.
+fn string_table(
+    languages: &[(String, Vec<(String, String)>)],
+) -> Result<(Vec<String>, Vec<Vec<String>>), String> {
+    let (first_language, first_strings) = &languages[0];
+    let keys: Vec<_> = first_strings.iter().map(|(key, _)| key.clone()).collect();
+
+    if keys.is_empty() {
+        return Err(format!("{first_language} has no strings"));
+    }
+
+    let mut variants = BTreeMap::new();
+    for key in &keys {
+        let variant = variant_name(key);
+        if variant.is_empty() || variant.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(format!("The key {key} can't be used as a name"));
+        }
+
+        if let Some(other) = variants.insert(variant, key) {
+            return Err(format!("The keys {other} and {key} have the same name"));
+        }
+    }
+
+    let table = languages
+        .iter()
+        .map(|(language, strings)| {
+            if let Some((key, _)) = strings
+                .iter()
+                .find(|(key, _)| keys.binary_search(key).is_err())
+            {
+                return Err(format!(
+                    "{language} has the key {key}, which {first_language} doesn't"
+                ));
+            }
+
+            keys.iter()
+                .map(|key| {
+                    strings
+                        .binary_search_by(|(other, _)| other.cmp(key))
+                        .map(|index| strings[index].1.clone())
+                        .map_err(|_| format!("{language} is missing the key {key}"))
+                })
+                .collect()
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok((keys, table))
+}
+
+#[doc(hidden)]
+pub fn include_strings(input: TokenStream) -> TokenStream {
+    let input = match syn::parse2::<IncludeStringsInput>(input) {
+        Ok(input) => input,
+        Err(e) => return e.into_compile_error(),
+    };
+
+    strings(&input).unwrap_or_else(syn::Error::into_compile_error)
+}
+
+fn strings(input: &IncludeStringsInput) -> syn::Result<TokenStream> {
+    let root = std::env::var("CARGO_MANIFEST_DIR").expect("Failed to get cargo manifest dir");
+
+    let mut paths = Vec::new();
+    let mut languages = Vec::new();
+
+    for language in &input.languages {
+        let path = Path::new(&root).join(language.file_name.value());
+        let strings =
+            load_strings(&path).map_err(|e| syn::Error::new_spanned(&language.file_name, e))?;
+
+        paths.push(path.to_string_lossy().into_owned());
+        languages.push((language.name.to_string(), strings));
+    }
+
+    let (keys, table) =
+        string_table(&languages).map_err(|e| syn::Error::new_spanned(&input.module_name, e))?;
+
+    let crate_prefix = &input.crate_prefix;
+    let module_name = &input.module_name;
+
+    let language_names = input.languages.iter().map(|language| &language.name);
+    let language_codes = language_names.clone().map(ToString::to_string);
+    let language_variants: Vec<_> = language_names
+        .map(|name| format_ident!("{}", variant_name(&name.to_string())))
+        .collect();
+    let default_language = &language_variants[0];
+
+    let key_variants: Vec<_> = keys
+        .iter()
+        .map(|key| format_ident!("{}", variant_name(key)))
+        .collect();
+    let number_of_keys = keys.len();
+    let number_of_languages = language_variants.len();
+    let table = table.iter().map(|strings| quote!([#(#strings),*]));
+
+    Ok(quote! {
+        // Games rarely use every string and language in every build
+        #[allow(dead_code)]
+        mod #module_name {
+            #(const _: &[u8] = include_bytes!(#paths);)*
+
+            /// The languages the strings are in.
+            #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+            pub enum Language {
+                #(#language_variants),*
+            }
+
+            impl Language {
+                /// Every language, in the order they were included.
+                pub const ALL: &'static [Language] = &[#(Language::#language_variants),*];
+
+                /// The name the language was included with, such as `en`.
+                #[must_use]
+                pub const fn code(self) -> &'static str {
+                    match self {
+                        #(Language::#language_variants => #language_codes),*
+                    }
+                }
+            }
+
+            /// The key of each string.
+            #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+            pub enum Text {
+                #(#key_variants),*
+            }
+
+            static STRINGS: [[&str; #number_of_keys]; #number_of_languages] = [#(#table),*];
+            static LANGUAGE: #crate_prefix::sync::Static<Language> =
+                #crate_prefix::sync::Static::new(Language::#default_language);
+
+            /// Changes the language used to display the strings.
+            pub fn set_language(language: Language) {
+                LANGUAGE.write(language);
+            }
+
+            /// The language used to display the strings.
+            #[must_use]
+            pub fn language() -> Language {
+                LANGUAGE.read()
+            }
+
+            impl Text {
+                /// Every key, sorted by its name in the strings files.
+                pub const ALL: &'static [Text] = &[#(Text::#key_variants),*];
+
+                /// The string in the given language.
+                #[must_use]
+                pub fn get(self, language: Language) -> &'static str {
+                    STRINGS[language as usize][self as usize]
+                }
+
+                /// The string in the current language.
+                #[must_use]
+                pub fn text(self) -> &'static str {
+                    self.get(language())
+                }
+            }
+
+            impl core::fmt::Display for Text {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    f.write_str(self.text())
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(content: &str) -> Vec<(String, String)> {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("strings.toml");
+        std::fs::write(&file, content).unwrap();
+
+        load_strings(&file).unwrap()
+    }
+
+    #[test]
+    fn nested_tables_are_flattened() {
+        let strings = strings("title = \"Hello\"\n[menu]\nstart_game = \"Start\"\n");
+
+        assert_eq!(
+            strings,
+            [
+                ("menu.start_game".to_owned(), "Start".to_owned()),
+                ("title".to_owned(), "Hello".to_owned())
+            ]
+        );
+        assert_eq!(variant_name("menu.start_game"), "MenuStartGame");
+    }
+
+    #[test]
+    fn languages_need_the_same_keys() {
+        let en = vec![
+            ("a".to_owned(), "A".to_owned()),
+            ("b".to_owned(), "B".to_owned()),
+        ];
+        let fr = vec![
+            ("a".to_owned(), "À".to_owned()),
+            ("b".to_owned(), "Ç".to_owned()),
+        ];
+
+        let (keys, table) =
+            string_table(&[("en".to_owned(), en.clone()), ("fr".to_owned(), fr)]).unwrap();
+        assert_eq!(keys, ["a", "b"]);
+        assert_eq!(table[1], ["À", "Ç"]);
+
+        let missing = vec![("a".to_owned(), "A".to_owned())];
+        assert!(
+            string_table(&[("en".to_owned(), en.clone()), ("de".to_owned(), missing)]).is_err()
+        );
+
+        let clashing = vec![
+            ("a_b".to_owned(), "1".to_owned()),
+            ("a.b".to_owned(), "2".to_owned()),
+        ];
+        assert!(string_table(&[("en".to_owned(), clashing)]).is_err());
+    }
+}
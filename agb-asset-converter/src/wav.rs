@@ -0,0 +1,66 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::path::Path;
+
+use crate::{ByteString, Error};
+
+/// Converts the wav file at `path` to the 8-bit signed samples which agb's
+/// mixer plays.
+pub fn convert_wav(path: impl AsRef<Path>) -> Result<Vec<u8>, Error> {
+    let path = path.as_ref();
+
+    let wav_reader = hound::WavReader::open(path)
+        .map_err(|e| Error::new(format!("Failed to load file {}: {e}", path.display())))?;
+
+    Ok(samples_from_reader(wav_reader).collect())
+}
+
+#[doc(hidden)]
+pub fn include_wav(input: TokenStream) -> TokenStream {
+    let input = match syn::parse2::<syn::LitStr>(input) {
+        Ok(input) => input,
+        Err(e) => return e.into_compile_error(),
+    };
+
+    let filename = input.value();
+
+    let root = std::env::var("CARGO_MANIFEST_DIR").expect("Failed to get cargo manifest dir");
+    let path = Path::new(&root).join(&*filename);
+
+    let include_path = path.to_string_lossy();
+
+    let samples = convert_wav(&path).unwrap_or_else(|e| panic!("{e}"));
+    let samples = ByteString(&samples);
+
+    quote! {
+        {
+            #[repr(align(4))]
+            struct AlignmentWrapper<const N: usize>([u8; N]);
+
+            const _: &[u8] = include_bytes!(#include_path);
+
+            &AlignmentWrapper(*#samples).0
+        }
+    }
+}
+
+fn samples_from_reader<'a, R>(reader: hound::WavReader<R>) -> Box<dyn Iterator<Item = u8> + 'a>
+where
+    R: std::io::Read + 'a,
+{
+    let bitrate = reader.spec().bits_per_sample;
+    let reduction = bitrate - 8;
+
+    match reader.spec().sample_format {
+        hound::SampleFormat::Float => Box::new(
+            reader
+                .into_samples::<f32>()
+                .map(|sample| (sample.unwrap() * (i8::MAX as f32)) as u8),
+        ),
+        hound::SampleFormat::Int => Box::new(
+            reader
+                .into_samples::<i32>()
+                .map(move |sample| (sample.unwrap() >> reduction) as u8),
+        ),
+    }
+}
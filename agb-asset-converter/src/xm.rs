@@ -0,0 +1,32 @@
+use std::path::Path;
+
+pub use agb_tracker_interop::Track;
+
+use crate::Error;
+
+/// Converts the XM file at `path` to Rust source for an `agb_tracker::Track`
+/// expression, the same as `include_xm!` generates.
+///
+/// Use [`convert_xm_with`] to look at the converted track instead.
+pub fn convert_xm(path: impl AsRef<Path>) -> Result<String, Error> {
+    convert_xm_with(path, |track| quote::quote!(#track).to_string())
+}
+
+/// Converts the XM file at `path` and calls `f` with the converted track, for
+/// build scripts which want the samples and patterns themselves.
+///
+/// The track borrows its data from buffers which only live for the duration
+/// of the call, so `f` must copy out anything it wants to keep. There is no
+/// binary format for tracks, since agb plays them from the `static` data which
+/// [`convert_xm`] generates.
+pub fn convert_xm_with<R>(
+    path: impl AsRef<Path>,
+    f: impl FnOnce(&Track<'_>) -> R,
+) -> Result<R, Error> {
+    let path = path.as_ref();
+
+    let module = agb_xm_core::load_module_from_file(path)
+        .map_err(|e| Error::new(format!("Failed to load file {}: {e}", path.display())))?;
+
+    Ok(agb_xm_core::with_track(&module, f))
+}
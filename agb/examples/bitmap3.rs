@@ -28,6 +28,6 @@ fn main(mut gba: agb::Gba) -> ! {
 
         pos.x = pos.x.clamp(0, display::WIDTH - 1);
         pos.y = pos.y.clamp(0, display::HEIGHT - 1);
-        bitmap.draw_point(pos.x, pos.y, 0x001F);
+        bitmap.draw_point(pos.x, pos.y, display::Rgb15::new(0x001F));
     }
 }
@@ -8,8 +8,8 @@ fn main(mut gba: agb::Gba) -> ! {
     let mut bitmap = gba.display.video.bitmap4();
     let vblank = agb::interrupt::VBlank::get();
 
-    bitmap.set_palette_entry(1, 0x001F);
-    bitmap.set_palette_entry(2, 0x03E0);
+    bitmap.set_palette_entry(1, display::Rgb15::new(0x001F));
+    bitmap.set_palette_entry(2, display::Rgb15::new(0x03E0));
 
     for y in 0..HEIGHT {
         for x in 0..WIDTH {
@@ -7,7 +7,7 @@ use agb::{
         object::{OamManaged, Object, Size, Sprite},
         palette16::Palette16,
         tiled::RegularBackgroundSize,
-        HEIGHT, WIDTH,
+        Rgb15, HEIGHT, WIDTH,
     },
     input::Button,
 };
@@ -253,8 +253,11 @@ fn handle_collision(
 
 // Below is the data for the sprites
 
-static CHICKEN_PALETTE: Palette16 =
-    Palette16::new([0x7C1E, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+static CHICKEN_PALETTE: Palette16 = Palette16::new({
+    let mut palette = [Rgb15::BLACK; 16];
+    palette[0] = Rgb15::new(0x7C1E);
+    palette
+});
 
 static CHICKEN_SPRITES: &[Sprite] = unsafe {
     &[
@@ -441,4 +444,4 @@ static MAP_MAP: [u16; 1024] = [
     0x0000, 0x0000, 0x0000, 0x0000,
 ];
 
-static MAP_PALETTE: [u16; 2] = [0x0000, 0x6A2F];
+static MAP_PALETTE: [Rgb15; 2] = [Rgb15::BLACK, Rgb15::new(0x6A2F)];
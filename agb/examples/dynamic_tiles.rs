@@ -4,7 +4,7 @@
 use agb::display::{
     palette16::Palette16,
     tiled::{RegularBackgroundSize, TileFormat, TiledMap},
-    Priority,
+    Priority, Rgb15,
 };
 
 #[agb::entry]
@@ -12,10 +12,13 @@ fn main(mut gba: agb::Gba) -> ! {
     let (gfx, mut vram) = gba.display.video.tiled0();
     let vblank = agb::interrupt::VBlank::get();
 
-    vram.set_background_palettes(&[Palette16::new([
-        0xff00, 0x0ff0, 0x00ff, 0xf00f, 0xf0f0, 0x0f0f, 0xaaaa, 0x5555, 0x0000, 0x0000, 0x0000,
-        0x0000, 0x0000, 0x0000, 0x0000, 0x0000,
-    ])]);
+    vram.set_background_palettes(&[Palette16::new(
+        [
+            0xff00, 0x0ff0, 0x00ff, 0xf00f, 0xf0f0, 0x0f0f, 0xaaaa, 0x5555, 0x0000, 0x0000, 0x0000,
+            0x0000, 0x0000, 0x0000, 0x0000, 0x0000,
+        ]
+        .map(Rgb15::new),
+    )]);
 
     let mut bg = gfx.background(
         Priority::P0,
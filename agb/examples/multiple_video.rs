@@ -42,7 +42,7 @@ fn bitmap3_mode(
 
         pos.x = pos.x.clamp(0, display::WIDTH - 1);
         pos.y = pos.y.clamp(0, display::HEIGHT - 1);
-        bitmap.draw_point(pos.x, pos.y, 0x001F);
+        bitmap.draw_point(pos.x, pos.y, display::Rgb15::new(0x001F));
     }
 }
 
@@ -51,8 +51,8 @@ fn bitmap4_mode(
     vblank: &agb::interrupt::VBlank,
     input: &mut agb::input::ButtonController,
 ) {
-    bitmap.set_palette_entry(1, 0x001F);
-    bitmap.set_palette_entry(2, 0x03E0);
+    bitmap.set_palette_entry(1, display::Rgb15::new(0x001F));
+    bitmap.set_palette_entry(2, display::Rgb15::new(0x03E0));
 
     bitmap.draw_point_page(
         display::WIDTH / 2,
@@ -12,7 +12,7 @@ fn main(mut gba: agb::Gba) -> ! {
         input.update();
         // if A is pressed, draw out of range
         if input.is_just_pressed(agb::input::Button::A) {
-            bitmap.draw_point(display::WIDTH, 0, 0x05);
+            bitmap.draw_point(display::WIDTH, 0, display::Rgb15::new(0x05));
         }
         if input.is_just_pressed(agb::input::Button::B) {
             #[allow(arithmetic_overflow)]
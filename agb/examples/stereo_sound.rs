@@ -4,7 +4,7 @@
 use agb::{
     display::{
         tiled::{RegularBackgroundSize, RegularMap, TileFormat, TiledMap, VRamManager},
-        Font, Priority,
+        Font, Priority, Rgb15,
     },
     include_font, include_wav,
     sound::mixer::{Frequency, SoundChannel},
@@ -85,10 +85,13 @@ fn main(mut gba: Gba) -> ! {
 fn init_background(bg: &mut RegularMap, vram: &mut VRamManager) {
     let background_tile = vram.new_dynamic_tile().fill_with(0);
 
-    vram.set_background_palette_raw(&[
-        0x0000, 0x0ff0, 0x00ff, 0xf00f, 0xf0f0, 0x0f0f, 0xaaaa, 0x5555, 0x0000, 0x0000, 0x0000,
-        0x0000, 0x0000, 0x0000, 0x0000, 0x0000,
-    ]);
+    vram.set_background_palette_raw(
+        &[
+            0x0000, 0x0ff0, 0x00ff, 0xf00f, 0xf0f0, 0x0f0f, 0xaaaa, 0x5555, 0x0000, 0x0000, 0x0000,
+            0x0000, 0x0000, 0x0000, 0x0000, 0x0000,
+        ]
+        .map(Rgb15::new),
+    );
 
     for y in 0..20u16 {
         for x in 0..30u16 {
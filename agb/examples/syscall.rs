@@ -10,7 +10,7 @@ fn main(mut gba: agb::Gba) -> ! {
     for x in 0..display::WIDTH {
         let y = syscall::sqrt(x << 6);
         let y = (display::HEIGHT - y).clamp(0, display::HEIGHT - 1);
-        bitmap.draw_point(x, y, 0x001F);
+        bitmap.draw_point(x, y, display::Rgb15::new(0x001F));
     }
 
     loop {
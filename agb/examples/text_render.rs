@@ -4,7 +4,7 @@
 use agb::{
     display::{
         tiled::{RegularBackgroundSize, TileFormat, TiledMap},
-        Font, Priority,
+        Font, Priority, Rgb15,
     },
     include_font,
 };
@@ -18,10 +18,13 @@ fn main(mut gba: agb::Gba) -> ! {
     let (gfx, mut vram) = gba.display.video.tiled0();
     let vblank = agb::interrupt::VBlank::get();
 
-    vram.set_background_palette_raw(&[
-        0x0000, 0x0ff0, 0x00ff, 0xf00f, 0xf0f0, 0x0f0f, 0xaaaa, 0x5555, 0x0000, 0x0000, 0x0000,
-        0x0000, 0x0000, 0x0000, 0x0000, 0x0000,
-    ]);
+    vram.set_background_palette_raw(
+        &[
+            0x0000, 0x0ff0, 0x00ff, 0xf00f, 0xf0f0, 0x0f0f, 0xaaaa, 0x5555, 0x0000, 0x0000, 0x0000,
+            0x0000, 0x0000, 0x0000, 0x0000, 0x0000,
+        ]
+        .map(Rgb15::new),
+    );
 
     let background_tile = vram.new_dynamic_tile().fill_with(0);
 
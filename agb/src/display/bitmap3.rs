@@ -1,7 +1,8 @@
 use crate::memory_mapped::MemoryMapped2DArray;
 
 use super::{
-    set_graphics_mode, set_graphics_settings, DisplayMode, GraphicsSettings, HEIGHT, WIDTH,
+    bitmap_data::Bitmap3Data, set_graphics_mode, set_graphics_settings, DisplayMode,
+    GraphicsSettings, Rgb15, HEIGHT, WIDTH,
 };
 
 use core::{convert::TryInto, marker::PhantomData};
@@ -25,24 +26,38 @@ impl Bitmap3<'_> {
 
     /// Draws point to screen at (x, y) coordinates with colour and panics if
     /// (x, y) is out of the bounds of the screen.
-    pub fn draw_point(&mut self, x: i32, y: i32, colour: u16) {
+    pub fn draw_point(&mut self, x: i32, y: i32, colour: Rgb15) {
         let x = x.try_into().unwrap();
         let y = y.try_into().unwrap();
-        BITMAP_MODE_3.set(x, y, colour);
+        BITMAP_MODE_3.set(x, y, colour.to_raw());
     }
 
     #[must_use]
-    pub fn read_point(&self, x: i32, y: i32) -> u16 {
+    pub fn read_point(&self, x: i32, y: i32) -> Rgb15 {
         let x = x.try_into().unwrap();
         let y = y.try_into().unwrap();
-        BITMAP_MODE_3.get(x, y)
+        Rgb15::new(BITMAP_MODE_3.get(x, y))
     }
 
-    pub fn clear(&mut self, colour: u16) {
+    pub fn clear(&mut self, colour: Rgb15) {
         for y in 0..(HEIGHT as usize) {
             for x in 0..(WIDTH as usize) {
-                BITMAP_MODE_3.set(x, y, colour);
+                BITMAP_MODE_3.set(x, y, colour.to_raw());
             }
         }
     }
+
+    /// Copies the whole image to the screen with its top left corner at (x, y)
+    /// using DMA. Panics if the image doesn't fit on the screen.
+    pub fn draw_bitmap(&mut self, bitmap: &Bitmap3Data, x: i32, y: i32) {
+        unsafe {
+            bitmap.copy_to(
+                BITMAP_MODE_3.as_ptr(),
+                WIDTH as usize,
+                HEIGHT as usize,
+                x,
+                y,
+            );
+        }
+    }
 }
@@ -1,10 +1,13 @@
 use core::marker::PhantomData;
 
-use crate::memory_mapped::{MemoryMapped1DArray, MemoryMapped2DArray};
+use crate::{
+    dma::dma_copy16,
+    memory_mapped::{MemoryMapped1DArray, MemoryMapped2DArray},
+};
 
 use super::{
-    set_graphics_mode, set_graphics_settings, DisplayMode, GraphicsSettings, DISPLAY_CONTROL,
-    HEIGHT, WIDTH,
+    bitmap_data::Bitmap4Data, set_graphics_mode, set_graphics_settings, DisplayMode,
+    GraphicsSettings, Rgb15, DISPLAY_CONTROL, HEIGHT, WIDTH,
 };
 
 const BITMAP_PAGE_FRONT_MODE_4: MemoryMapped2DArray<
@@ -77,8 +80,53 @@ impl Bitmap4<'_> {
     }
 
     /// Sets the colour of colour index in the background palette.
-    pub fn set_palette_entry(&mut self, entry: u32, colour: u16) {
-        PALETTE_BACKGROUND.set(entry as usize, colour);
+    pub fn set_palette_entry(&mut self, entry: u32, colour: Rgb15) {
+        PALETTE_BACKGROUND.set(entry as usize, colour.to_raw());
+    }
+
+    /// Replaces the start of the background palette with the given colours
+    /// using DMA, such as the palette of a [Bitmap4Data]. Panics if there are
+    /// more than 256 colours.
+    pub fn set_palette(&mut self, colours: &[Rgb15]) {
+        assert!(colours.len() <= 256, "the palette has at most 256 colours");
+
+        unsafe {
+            dma_copy16(
+                colours.as_ptr().cast(),
+                PALETTE_BACKGROUND.as_ptr(),
+                colours.len(),
+            );
+        }
+    }
+
+    /// Copies the whole image to the specified page with its top left corner at
+    /// (x, y) using DMA. The x coordinate must be even. Panics if the image
+    /// doesn't fit on the screen.
+    pub fn draw_bitmap_page(&mut self, bitmap: &Bitmap4Data, x: i32, y: i32, page: Page) {
+        let addr = match page {
+            Page::Front => BITMAP_PAGE_FRONT_MODE_4,
+            Page::Back => BITMAP_PAGE_BACK_MODE_4,
+        };
+
+        unsafe {
+            bitmap.copy_to(addr.as_ptr(), (WIDTH / 2) as usize, HEIGHT as usize, x, y);
+        }
+    }
+
+    /// Copies the whole image to the non-current page with its top left corner
+    /// at (x, y) using DMA. The x coordinate must be even. Panics if the image
+    /// doesn't fit on the screen.
+    pub fn draw_bitmap(&mut self, bitmap: &Bitmap4Data, x: i32, y: i32) {
+        let display = DISPLAY_CONTROL.get();
+
+        // get other page
+        let page = if display & GraphicsSettings::PAGE_SELECT.bits() != 0 {
+            Page::Front
+        } else {
+            Page::Back
+        };
+
+        self.draw_bitmap_page(bitmap, x, y, page);
     }
 
     /// Flips page, changing the Gameboy advance to draw the contents of the
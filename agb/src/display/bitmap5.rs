@@ -0,0 +1,92 @@
+use core::marker::PhantomData;
+
+use crate::memory_mapped::MemoryMapped2DArray;
+
+use super::{
+    bitmap4::Page, bitmap_data::Bitmap5Data, set_graphics_mode, set_graphics_settings, DisplayMode,
+    GraphicsSettings, Rgb15, DISPLAY_CONTROL,
+};
+
+/// The width of the mode 5 framebuffer in pixels.
+pub const WIDTH: i32 = 160;
+/// The height of the mode 5 framebuffer in pixels.
+pub const HEIGHT: i32 = 128;
+
+const BITMAP_PAGE_FRONT_MODE_5: MemoryMapped2DArray<u16, { WIDTH as usize }, { HEIGHT as usize }> =
+    unsafe { MemoryMapped2DArray::new(0x600_0000) };
+const BITMAP_PAGE_BACK_MODE_5: MemoryMapped2DArray<u16, { WIDTH as usize }, { HEIGHT as usize }> =
+    unsafe { MemoryMapped2DArray::new(0x600_A000) };
+
+#[non_exhaustive]
+pub struct Bitmap5<'gba> {
+    phantom: PhantomData<&'gba ()>,
+}
+
+impl Bitmap5<'_> {
+    pub(crate) unsafe fn new() -> Self {
+        set_graphics_mode(DisplayMode::Bitmap5);
+        set_graphics_settings(GraphicsSettings::LAYER_BG2);
+        Bitmap5 {
+            phantom: PhantomData,
+        }
+    }
+
+    fn page_memory(
+        page: Page,
+    ) -> MemoryMapped2DArray<u16, { WIDTH as usize }, { HEIGHT as usize }> {
+        match page {
+            Page::Front => BITMAP_PAGE_FRONT_MODE_5,
+            Page::Back => BITMAP_PAGE_BACK_MODE_5,
+        }
+    }
+
+    fn other_page() -> Page {
+        if DISPLAY_CONTROL.get() & GraphicsSettings::PAGE_SELECT.bits() != 0 {
+            Page::Front
+        } else {
+            Page::Back
+        }
+    }
+
+    /// Draws point on specified page at (x, y) coordinates with colour. Panics
+    /// if (x, y) is out of the bounds of the 160x128 framebuffer.
+    pub fn draw_point_page(&mut self, x: i32, y: i32, colour: Rgb15, page: Page) {
+        let x = x.try_into().unwrap();
+        let y = y.try_into().unwrap();
+        Self::page_memory(page).set(x, y, colour.to_raw());
+    }
+
+    /// Draws point on the non-current page at (x, y) coordinates with colour.
+    /// Panics if (x, y) is out of the bounds of the 160x128 framebuffer.
+    pub fn draw_point(&mut self, x: i32, y: i32, colour: Rgb15) {
+        self.draw_point_page(x, y, colour, Self::other_page());
+    }
+
+    /// Copies the whole image to the specified page with its top left corner at
+    /// (x, y) using DMA. Panics if the image doesn't fit on the page.
+    pub fn draw_bitmap_page(&mut self, bitmap: &Bitmap5Data, x: i32, y: i32, page: Page) {
+        unsafe {
+            bitmap.copy_to(
+                Self::page_memory(page).as_ptr(),
+                WIDTH as usize,
+                HEIGHT as usize,
+                x,
+                y,
+            );
+        }
+    }
+
+    /// Copies the whole image to the non-current page with its top left corner
+    /// at (x, y) using DMA. Panics if the image doesn't fit on the page.
+    pub fn draw_bitmap(&mut self, bitmap: &Bitmap5Data, x: i32, y: i32) {
+        self.draw_bitmap_page(bitmap, x, y, Self::other_page());
+    }
+
+    /// Flips page, changing the Gameboy advance to draw the contents of the
+    /// other page
+    pub fn flip_page(&mut self) {
+        let display = DISPLAY_CONTROL.get();
+        let swapped = display ^ GraphicsSettings::PAGE_SELECT.bits();
+        DISPLAY_CONTROL.set(swapped);
+    }
+}
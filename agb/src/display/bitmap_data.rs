@@ -0,0 +1,267 @@
+use crate::dma::dma_copy16;
+
+use super::Rgb15;
+
+/// The pixels of an image, stored as rows of halfwords so they can be copied
+/// straight into a framebuffer.
+struct Pixels {
+    width: usize,
+    height: usize,
+    row_length: usize,
+    data: &'static [u8],
+}
+
+impl Pixels {
+    /// Copies every row of the image into a framebuffer which is
+    /// `screen_width` halfwords wide. `x` is measured in halfwords.
+    unsafe fn copy_to(
+        &self,
+        framebuffer: *mut u16,
+        screen_width: usize,
+        screen_height: usize,
+        x: i32,
+        y: i32,
+    ) {
+        let x: usize = x.try_into().expect("bitmap must not start off the screen");
+        let y: usize = y.try_into().expect("bitmap must not start off the screen");
+        assert!(
+            x + self.row_length <= screen_width && y + self.height <= screen_height,
+            "bitmap must fit on the screen"
+        );
+
+        let source = self.data.as_ptr().cast::<u16>();
+
+        for row in 0..self.height {
+            dma_copy16(
+                source.add(row * self.row_length),
+                framebuffer.add((y + row) * screen_width + x),
+                self.row_length,
+            );
+        }
+    }
+}
+
+/// A 16-bit colour image for [Bitmap3](super::bitmap3::Bitmap3), made with
+/// [include_bitmap](crate::include_bitmap).
+pub struct Bitmap3Data {
+    pixels: Pixels,
+}
+
+impl Bitmap3Data {
+    #[doc(hidden)]
+    /// # Safety
+    /// `data` must be aligned to 2 bytes and contain `width * height` raw
+    /// colours.
+    #[must_use]
+    pub const unsafe fn new(width: usize, height: usize, data: &'static [u8]) -> Self {
+        Self {
+            pixels: Pixels {
+                width,
+                height,
+                row_length: width,
+                data,
+            },
+        }
+    }
+
+    /// The width of the image in pixels.
+    #[must_use]
+    pub const fn width(&self) -> usize {
+        self.pixels.width
+    }
+
+    /// The height of the image in pixels.
+    #[must_use]
+    pub const fn height(&self) -> usize {
+        self.pixels.height
+    }
+
+    pub(crate) unsafe fn copy_to(
+        &self,
+        framebuffer: *mut u16,
+        screen_width: usize,
+        screen_height: usize,
+        x: i32,
+        y: i32,
+    ) {
+        self.pixels
+            .copy_to(framebuffer, screen_width, screen_height, x, y);
+    }
+}
+
+/// An 8-bit paletted image and its palette for
+/// [Bitmap4](super::bitmap4::Bitmap4), made with
+/// [include_bitmap](crate::include_bitmap). The width is always even.
+pub struct Bitmap4Data {
+    pixels: Pixels,
+    palette: &'static [Rgb15],
+}
+
+impl Bitmap4Data {
+    #[doc(hidden)]
+    /// # Safety
+    /// `width` must be even and `data` must be aligned to 2 bytes and contain
+    /// `width * height` palette indices.
+    #[must_use]
+    pub const unsafe fn new(
+        width: usize,
+        height: usize,
+        data: &'static [u8],
+        palette: &'static [Rgb15],
+    ) -> Self {
+        Self {
+            pixels: Pixels {
+                width,
+                height,
+                row_length: width / 2,
+                data,
+            },
+            palette,
+        }
+    }
+
+    /// The width of the image in pixels.
+    #[must_use]
+    pub const fn width(&self) -> usize {
+        self.pixels.width
+    }
+
+    /// The height of the image in pixels.
+    #[must_use]
+    pub const fn height(&self) -> usize {
+        self.pixels.height
+    }
+
+    /// The colours used by the image, which should be loaded with
+    /// [`Bitmap4::set_palette`](super::bitmap4::Bitmap4::set_palette) before
+    /// it is shown.
+    #[must_use]
+    pub const fn palette(&self) -> &'static [Rgb15] {
+        self.palette
+    }
+
+    pub(crate) unsafe fn copy_to(
+        &self,
+        framebuffer: *mut u16,
+        screen_width: usize,
+        screen_height: usize,
+        x: i32,
+        y: i32,
+    ) {
+        assert!(x % 2 == 0, "mode 4 bitmaps must be drawn at an even x");
+
+        self.pixels
+            .copy_to(framebuffer, screen_width, screen_height, x / 2, y);
+    }
+}
+
+/// A 16-bit colour image of at most 160x128 pixels for
+/// [Bitmap5](super::bitmap5::Bitmap5), made with
+/// [include_bitmap](crate::include_bitmap).
+pub struct Bitmap5Data {
+    pixels: Pixels,
+}
+
+impl Bitmap5Data {
+    #[doc(hidden)]
+    /// # Safety
+    /// `data` must be aligned to 2 bytes and contain `width * height` raw
+    /// colours.
+    #[must_use]
+    pub const unsafe fn new(width: usize, height: usize, data: &'static [u8]) -> Self {
+        Self {
+            pixels: Pixels {
+                width,
+                height,
+                row_length: width,
+                data,
+            },
+        }
+    }
+
+    /// The width of the image in pixels.
+    #[must_use]
+    pub const fn width(&self) -> usize {
+        self.pixels.width
+    }
+
+    /// The height of the image in pixels.
+    #[must_use]
+    pub const fn height(&self) -> usize {
+        self.pixels.height
+    }
+
+    pub(crate) unsafe fn copy_to(
+        &self,
+        framebuffer: *mut u16,
+        screen_width: usize,
+        screen_height: usize,
+        x: i32,
+        y: i32,
+    ) {
+        self.pixels
+            .copy_to(framebuffer, screen_width, screen_height, x, y);
+    }
+}
+
+/// Includes an image for one of the bitmap modes. The mode is given after the
+/// file name, and decides which type is produced:
+///
+/// * `mode3` gives a [Bitmap3Data] of 16-bit colours, up to 240x160 pixels.
+/// * `mode4` gives a [Bitmap4Data] of palette indices along with the palette,
+///   up to 240x160 pixels with an even width. The image can have at most 256
+///   colours unless `quantise` is given, which reduces the colours in the same
+///   way as for [include_background_gfx](crate::include_background_gfx).
+/// * `mode5` gives a [Bitmap5Data] of 16-bit colours, up to 160x128 pixels.
+///
+/// Transparent pixels become palette index 0 in mode 4, and black otherwise.
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// use agb::{display::bitmap_data::Bitmap4Data, include_bitmap};
+///
+/// static TITLE: Bitmap4Data = include_bitmap!(
+///     "gfx/test_logo.png",
+///     mode4,
+///     quantise: median_cut floyd_steinberg
+/// );
+///
+/// # fn test(mut gba: agb::Gba) {
+/// let mut bitmap = gba.display.video.bitmap4();
+/// bitmap.set_palette(TITLE.palette());
+/// bitmap.draw_bitmap(&TITLE, 0, 0);
+/// bitmap.flip_page();
+/// # }
+/// ```
+#[macro_export]
+macro_rules! include_bitmap {
+    ($($bitmap: tt)*) => {{
+        #[allow(unused_imports)]
+        use $crate::display::{
+            bitmap_data::{Bitmap3Data, Bitmap4Data, Bitmap5Data},
+            Rgb15,
+        };
+        use $crate::align_bytes;
+
+        $crate::include_bitmap_inner!($($bitmap)*)
+    }};
+}
+
+pub use include_bitmap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static LOGO: Bitmap3Data = include_bitmap!("gfx/test_logo.png", mode3);
+
+    #[test_case]
+    fn bitmap_fills_the_screen(gba: &mut crate::Gba) {
+        let mut bitmap = gba.display.video.bitmap3();
+
+        bitmap.draw_bitmap(&LOGO, 0, 0);
+
+        crate::test_runner::assert_image_output("gfx/test_logo.png");
+    }
+}
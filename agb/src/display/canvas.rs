@@ -0,0 +1,314 @@
+use crate::fixnum::{Rect, Vector2D};
+
+use super::{
+    object::Sprite,
+    tiled::{TileFormat, TileSet},
+    Font,
+};
+
+/// A surface of 4 bit paletted pixels which can be drawn on. This is
+/// implemented for [`DynamicSprite`](super::object::DynamicSprite),
+/// [`DynamicTile`](super::tiled::DynamicTile) and
+/// [`TileCanvas`](super::tiled::TileCanvas).
+///
+/// Drawing outside of the canvas is clipped, so shapes can be partially off
+/// the edge. Colours are palette indices and must be less than 16.
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// use agb::{
+///     display::{canvas::Canvas, object::{DynamicSprite, Size}},
+///     fixnum::{Rect, Vector2D},
+/// };
+///
+/// # fn foo() {
+/// let mut health_bar = DynamicSprite::new(Size::S32x8);
+/// health_bar.draw_rect(Rect::new((0, 0).into(), (32, 8).into()), 1);
+/// health_bar.fill_rect(Rect::new((1, 1).into(), (20, 6).into()), 2);
+/// # }
+/// ```
+pub trait Canvas {
+    /// The width and height of the canvas in pixels.
+    fn size(&self) -> Vector2D<i32>;
+
+    /// Gets the palette index of the pixel at the given position. Panics if
+    /// the position is outside of the canvas.
+    fn pixel(&self, position: Vector2D<i32>) -> u8;
+
+    /// Sets the pixel at the given position to the palette index. Does nothing
+    /// if the position is outside of the canvas.
+    fn draw_pixel(&mut self, position: Vector2D<i32>, colour: u8);
+
+    /// Draws a straight line between the two points, including both ends.
+    fn draw_line(&mut self, start: Vector2D<i32>, end: Vector2D<i32>, colour: u8) {
+        let dx = (end.x - start.x).abs();
+        let dy = -(end.y - start.y).abs();
+        let step_x = if start.x < end.x { 1 } else { -1 };
+        let step_y = if start.y < end.y { 1 } else { -1 };
+
+        let mut error = dx + dy;
+        let mut position = start;
+
+        loop {
+            self.draw_pixel(position, colour);
+
+            if position == end {
+                return;
+            }
+
+            let doubled_error = error * 2;
+            if doubled_error >= dy {
+                error += dy;
+                position.x += step_x;
+            }
+            if doubled_error <= dx {
+                error += dx;
+                position.y += step_y;
+            }
+        }
+    }
+
+    /// Draws the outline of the rectangle, one pixel wide.
+    fn draw_rect(&mut self, rect: Rect<i32>, colour: u8) {
+        if rect.size.x <= 0 || rect.size.y <= 0 {
+            return;
+        }
+
+        let top_left = rect.position;
+        let bottom_right = rect.position + rect.size - (1, 1).into();
+
+        self.draw_line(top_left, (bottom_right.x, top_left.y).into(), colour);
+        self.draw_line(top_left, (top_left.x, bottom_right.y).into(), colour);
+        self.draw_line((bottom_right.x, top_left.y).into(), bottom_right, colour);
+        self.draw_line((top_left.x, bottom_right.y).into(), bottom_right, colour);
+    }
+
+    /// Fills the rectangle with the colour.
+    fn fill_rect(&mut self, rect: Rect<i32>, colour: u8) {
+        let size = self.size();
+
+        for y in rect.position.y.max(0)..(rect.position.y + rect.size.y).min(size.y) {
+            for x in rect.position.x.max(0)..(rect.position.x + rect.size.x).min(size.x) {
+                self.draw_pixel((x, y).into(), colour);
+            }
+        }
+    }
+
+    /// Copies the `source` region of the sprite so its top left is at
+    /// `position`. Pixels using colour 0 are transparent and are skipped.
+    fn blit_sprite(&mut self, sprite: &Sprite, source: Rect<i32>, position: Vector2D<i32>) {
+        self.blit_sprite_remapped(sprite, source, position, &IDENTITY_REMAP);
+    }
+
+    /// Copies the `source` region of the sprite like
+    /// [`blit_sprite`](Canvas::blit_sprite), replacing each colour `i` with
+    /// `remap[i]`. Colour 0 in the sprite stays transparent.
+    fn blit_sprite_remapped(
+        &mut self,
+        sprite: &Sprite,
+        source: Rect<i32>,
+        position: Vector2D<i32>,
+        remap: &[u8; 16],
+    ) {
+        let (width, height) = sprite.size().to_width_height();
+        let tiles_width = width / 8;
+
+        blit(
+            self,
+            (width as i32, height as i32).into(),
+            |pixel| pixel_4bpp(sprite.data, tiles_width, pixel),
+            source,
+            position,
+            remap,
+        );
+    }
+
+    /// Copies the `source` region of a 4 bit tile in the tile set so its top
+    /// left is at `position`. Pixels using colour 0 are transparent and are
+    /// skipped.
+    fn blit_tile(
+        &mut self,
+        tile_set: &TileSet<'_>,
+        tile: usize,
+        source: Rect<i32>,
+        position: Vector2D<i32>,
+    ) {
+        self.blit_tile_remapped(tile_set, tile, source, position, &IDENTITY_REMAP);
+    }
+
+    /// Copies the `source` region of a tile like
+    /// [`blit_tile`](Canvas::blit_tile), replacing each colour `i` with
+    /// `remap[i]`. Colour 0 in the tile stays transparent.
+    fn blit_tile_remapped(
+        &mut self,
+        tile_set: &TileSet<'_>,
+        tile: usize,
+        source: Rect<i32>,
+        position: Vector2D<i32>,
+        remap: &[u8; 16],
+    ) {
+        assert_eq!(
+            tile_set.format(),
+            TileFormat::FourBpp,
+            "can only blit from 4 bit tiles"
+        );
+
+        let tile_size = TileFormat::FourBpp.tile_size();
+        let tile_data = &tile_set.tiles()[tile * tile_size..(tile + 1) * tile_size];
+
+        blit(
+            self,
+            (8, 8).into(),
+            |pixel| pixel_4bpp(tile_data, 1, pixel),
+            source,
+            position,
+            remap,
+        );
+    }
+
+    /// Draws a single character with the top of the line at `position`, and
+    /// returns how far across the next character should be drawn.
+    fn draw_char(&mut self, font: &Font, c: char, position: Vector2D<i32>, colour: u8) -> i32 {
+        let letter = font.letter(c);
+
+        let x_start = position.x + i32::from(letter.xmin);
+        let y_start =
+            position.y + font.ascent() - i32::from(letter.height) - i32::from(letter.ymin);
+
+        for y in 0..usize::from(letter.height) {
+            for x in 0..usize::from(letter.width) {
+                if letter.bit_absolute(x, y) {
+                    self.draw_pixel((x_start + x as i32, y_start + y as i32).into(), colour);
+                }
+            }
+        }
+
+        i32::from(letter.advance_width)
+    }
+
+    /// Draws the text with the top left of the first line at `position`,
+    /// starting a new line on each `\n`. Returns the width of the widest line.
+    fn draw_text(&mut self, font: &Font, text: &str, position: Vector2D<i32>, colour: u8) -> i32 {
+        let mut cursor = position;
+        let mut widest = 0;
+        let mut previous = None;
+
+        for c in text.chars() {
+            if c == '\n' {
+                cursor = (position.x, cursor.y + font.line_height()).into();
+                previous = None;
+            } else {
+                if let Some(previous) = previous.replace(c) {
+                    cursor.x += font.kerning(previous, c);
+                }
+
+                cursor.x += self.draw_char(font, c, cursor, colour);
+                widest = widest.max(cursor.x - position.x);
+            }
+        }
+
+        widest
+    }
+}
+
+const IDENTITY_REMAP: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+
+fn blit<C: Canvas + ?Sized>(
+    canvas: &mut C,
+    source_size: Vector2D<i32>,
+    source_pixel: impl Fn(Vector2D<i32>) -> u8,
+    source: Rect<i32>,
+    position: Vector2D<i32>,
+    remap: &[u8; 16],
+) {
+    let end = source.position + source.size;
+
+    for y in source.position.y.max(0)..end.y.min(source_size.y) {
+        for x in source.position.x.max(0)..end.x.min(source_size.x) {
+            let colour = source_pixel((x, y).into());
+            if colour != 0 {
+                canvas.draw_pixel(
+                    position + Vector2D::new(x, y) - source.position,
+                    remap[usize::from(colour)],
+                );
+            }
+        }
+    }
+}
+
+/// Reads a pixel from 4 bit tile data laid out for objects, where the tiles
+/// are stored left to right and then top to bottom.
+fn pixel_4bpp(data: &[u8], tiles_width: usize, position: Vector2D<i32>) -> u8 {
+    let (x, y) = (position.x as usize, position.y as usize);
+
+    let tile = x / 8 + (y / 8) * tiles_width;
+    let byte = data[tile * 32 + (y % 8) * 4 + (x % 8) / 2];
+
+    (byte >> ((x % 2) * 4)) & 0xf
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::display::object::{DynamicSprite, Size};
+
+    use super::*;
+
+    fn pixels(sprite: &DynamicSprite) -> [[u8; 8]; 8] {
+        let mut pixels = [[0; 8]; 8];
+        for (y, row) in pixels.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                *pixel = sprite.pixel((x as i32, y as i32).into());
+            }
+        }
+
+        pixels
+    }
+
+    #[test_case]
+    fn draws_clipped_shapes(_gba: &mut crate::Gba) {
+        let mut sprite = DynamicSprite::new(Size::S8x8);
+
+        sprite.fill_rect(Rect::new((-2, 6).into(), (4, 4).into()), 3);
+        sprite.draw_line((0, 0).into(), (10, 10).into(), 1);
+
+        let pixels = pixels(&sprite);
+
+        for (i, row) in pixels.iter().enumerate() {
+            assert_eq!(row[i], 1);
+        }
+        assert_eq!(pixels[6][1], 3);
+        assert_eq!(pixels[7][0], 3);
+        assert_eq!(pixels[6][2], 0);
+        assert_eq!(pixels[0][1], 0);
+    }
+
+    #[test_case]
+    fn blit_remaps_colours_and_skips_transparency(_gba: &mut crate::Gba) {
+        let mut canvas = DynamicSprite::new(Size::S8x8);
+        canvas.clear(7);
+
+        let mut remap = IDENTITY_REMAP;
+        remap[2] = 9;
+
+        let mut tiles = [0; 32];
+        tiles[0] = 0x20;
+        tiles[1] = 0x05;
+        let tile_set = TileSet::new(&tiles, TileFormat::FourBpp);
+
+        canvas.blit_tile_remapped(
+            &tile_set,
+            0,
+            Rect::new((1, 0).into(), (2, 1).into()),
+            (4, 4).into(),
+            &remap,
+        );
+
+        let pixels = pixels(&canvas);
+
+        assert_eq!(pixels[4][4], 9);
+        assert_eq!(pixels[4][5], 5);
+        assert_eq!(pixels[4][3], 7);
+        assert_eq!(pixels[4][6], 7);
+    }
+}
@@ -0,0 +1,207 @@
+use agb_fixnum::Num;
+
+/// A colour in the format used by the Game Boy Advance, with 5 bits for each
+/// of the red, green and blue channels. This is what is stored in the palettes
+/// and in the mode 3 and 5 bitmaps.
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// use agb::{
+///     display::Rgb15,
+///     fixnum::{num, Num},
+/// };
+///
+/// const SKY: Rgb15 = Rgb15::from_hex(0x6495ed);
+/// const SUNSET: Rgb15 = Rgb15::from_rgb(255, 94, 77);
+///
+/// # fn foo() {
+/// let half: Num<i32, 8> = num!(0.5);
+/// let dusk = SKY.lerp(SUNSET, half).blend_multiply(Rgb15::from_rgb(200, 200, 255));
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Rgb15(u16);
+
+impl Rgb15 {
+    /// Black, with every channel at 0.
+    pub const BLACK: Rgb15 = Rgb15(0);
+    /// White, with every channel at its maximum.
+    pub const WHITE: Rgb15 = Rgb15(0x7fff);
+
+    #[must_use]
+    /// Creates a colour from its raw representation, where red is in the
+    /// lowest 5 bits followed by green and then blue. The top bit is ignored.
+    pub const fn new(raw: u16) -> Self {
+        Self(raw & 0x7fff)
+    }
+
+    #[must_use]
+    /// Creates a colour from 5 bit red, green and blue channels, each from 0 to 31.
+    pub const fn from_rgb5(r: u8, g: u8, b: u8) -> Self {
+        assert!(r < 32 && g < 32 && b < 32, "channels must be less than 32");
+        Self(r as u16 | (g as u16) << 5 | (b as u16) << 10)
+    }
+
+    #[must_use]
+    /// Creates a colour from 8 bit red, green and blue channels. The lowest 3
+    /// bits of each channel are lost.
+    pub const fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::from_rgb5(r >> 3, g >> 3, b >> 3)
+    }
+
+    #[must_use]
+    /// Creates a colour from a hex code like `0xff8800`, as used in image
+    /// editors and on the web.
+    pub const fn from_hex(hex: u32) -> Self {
+        Self::from_rgb((hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
+    }
+
+    #[must_use]
+    /// The raw representation of the colour.
+    pub const fn to_raw(self) -> u16 {
+        self.0
+    }
+
+    #[must_use]
+    /// The red channel, from 0 to 31.
+    pub const fn r(self) -> u8 {
+        (self.0 & 31) as u8
+    }
+
+    #[must_use]
+    /// The green channel, from 0 to 31.
+    pub const fn g(self) -> u8 {
+        ((self.0 >> 5) & 31) as u8
+    }
+
+    #[must_use]
+    /// The blue channel, from 0 to 31.
+    pub const fn b(self) -> u8 {
+        ((self.0 >> 10) & 31) as u8
+    }
+
+    #[must_use]
+    /// The red, green and blue channels scaled up to 8 bits, so that 31
+    /// becomes 255.
+    pub const fn to_rgb(self) -> (u8, u8, u8) {
+        const fn expand(channel: u8) -> u8 {
+            channel << 3 | channel >> 2
+        }
+
+        (expand(self.r()), expand(self.g()), expand(self.b()))
+    }
+
+    #[must_use]
+    /// Linearly interpolates between this colour and the other one. An amount
+    /// of 0 gives this colour and 1 gives the other, and the amount is clamped
+    /// to that range.
+    pub fn lerp<const N: usize>(self, other: Rgb15, amount: Num<i32, N>) -> Self {
+        let amount = amount.to_raw().clamp(0, 1 << N);
+
+        let mix = |from: u8, to: u8| {
+            let (from, to) = (i32::from(from), i32::from(to));
+            let mixed = from * (1 << N) + (to - from) * amount;
+            ((mixed + (1 << N) / 2) >> N) as u8
+        };
+
+        Self::from_rgb5(
+            mix(self.r(), other.r()),
+            mix(self.g(), other.g()),
+            mix(self.b(), other.b()),
+        )
+    }
+
+    #[must_use]
+    /// Adds each of the channels together, saturating at the maximum. This
+    /// brightens the colour, like a light shining on it.
+    pub const fn blend_add(self, other: Rgb15) -> Self {
+        const fn add(a: u8, b: u8) -> u8 {
+            let sum = a + b;
+            if sum > 31 {
+                31
+            } else {
+                sum
+            }
+        }
+
+        Self::from_rgb5(
+            add(self.r(), other.r()),
+            add(self.g(), other.g()),
+            add(self.b(), other.b()),
+        )
+    }
+
+    #[must_use]
+    /// Multiplies each of the channels together, treating 31 as 1. This
+    /// darkens the colour, like tinting it through coloured glass.
+    pub const fn blend_multiply(self, other: Rgb15) -> Self {
+        const fn multiply(a: u8, b: u8) -> u8 {
+            ((a as u16 * b as u16 + 15) / 31) as u8
+        }
+
+        Self::from_rgb5(
+            multiply(self.r(), other.r()),
+            multiply(self.g(), other.g()),
+            multiply(self.b(), other.b()),
+        )
+    }
+
+    #[must_use]
+    /// The grey with the same perceived brightness as this colour.
+    pub const fn greyscale(self) -> Self {
+        let luma = (self.r() as u16 * 77 + self.g() as u16 * 150 + self.b() as u16 * 29 + 128) >> 8;
+        let luma = luma as u8;
+
+        Self::from_rgb5(luma, luma, luma)
+    }
+}
+
+impl From<u16> for Rgb15 {
+    fn from(raw: u16) -> Self {
+        Self::new(raw)
+    }
+}
+
+impl From<Rgb15> for u16 {
+    fn from(colour: Rgb15) -> Self {
+        colour.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use agb_fixnum::num;
+
+    use super::*;
+
+    #[test_case]
+    fn constructors_agree(_gba: &mut crate::Gba) {
+        let colour = Rgb15::from_hex(0xff8000);
+
+        assert_eq!(colour, Rgb15::from_rgb(255, 128, 0));
+        assert_eq!(colour, Rgb15::from_rgb5(31, 16, 0));
+        assert_eq!(colour.to_raw(), 31 | 16 << 5);
+        assert_eq!(colour.to_rgb(), (255, 132, 0));
+    }
+
+    #[test_case]
+    fn colour_maths(_gba: &mut crate::Gba) {
+        let red = Rgb15::from_rgb5(31, 0, 0);
+        let blue = Rgb15::from_rgb5(0, 0, 31);
+        let half: Num<i32, 8> = num!(0.5);
+
+        assert_eq!(red.lerp(blue, half), Rgb15::from_rgb5(16, 0, 16));
+        assert_eq!(red.lerp(blue, half * 4), blue);
+        assert_eq!(red.blend_add(blue), Rgb15::from_rgb5(31, 0, 31));
+        assert_eq!(
+            Rgb15::from_rgb5(20, 20, 20).blend_add(Rgb15::from_rgb5(20, 5, 0)),
+            Rgb15::from_rgb5(31, 25, 20)
+        );
+        assert_eq!(red.blend_multiply(Rgb15::WHITE), red);
+        assert_eq!(red.blend_multiply(blue), Rgb15::BLACK);
+        assert_eq!(Rgb15::WHITE.greyscale(), Rgb15::WHITE);
+        assert_eq!(Rgb15::BLACK.greyscale(), Rgb15::BLACK);
+    }
+}
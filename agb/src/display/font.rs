@@ -5,9 +5,15 @@ use crate::hash_map::HashMap;
 
 use super::tiled::{DynamicTile, RegularMap, VRamManager};
 
+mod text_box;
+
+pub use text_box::TextBox;
+
 /// The text renderer renders a variable width fixed size
 /// bitmap font using dynamic tiles as a rendering surface.
-/// Does not support any unicode features.
+/// Each character is drawn with its own glyph, so any unicode character the
+/// font includes can be used, but there is no shaping or combining of
+/// characters.
 /// For usage see the `text_render.rs` example
 pub struct FontLetter {
     pub(crate) width: u8,
@@ -46,24 +52,72 @@ impl FontLetter {
     }
 }
 
+/// A font generated by [`include_font!`](crate::include_font). The glyphs are
+/// stored sorted by character, and any character the font doesn't have a
+/// glyph for is drawn with the fallback glyph.
 pub struct Font {
-    letters: &'static [FontLetter; 128],
+    characters: &'static [char],
+    letters: &'static [FontLetter],
+    fallback: &'static FontLetter,
+    kerning: &'static [(char, char, i8)],
     line_height: i32,
     ascent: i32,
 }
 
 impl Font {
+    /// Creates a font with a glyph for each character. The characters must be
+    /// sorted, and `letters` must be in the same order.
     #[must_use]
-    pub const fn new(letters: &'static [FontLetter; 128], line_height: i32, ascent: i32) -> Self {
+    pub const fn new(
+        characters: &'static [char],
+        letters: &'static [FontLetter],
+        fallback: &'static FontLetter,
+        line_height: i32,
+        ascent: i32,
+    ) -> Self {
+        assert!(characters.len() == letters.len());
+
         Self {
+            characters,
             letters,
+            fallback,
+            kerning: &[],
             line_height,
             ascent,
         }
     }
 
+    /// Adds kerning to the font, which moves a character closer to or further
+    /// from the character before it. Each entry is the pair of characters and
+    /// how many pixels to move the second one by, and the pairs must be sorted.
+    #[must_use]
+    pub const fn with_kerning(self, kerning: &'static [(char, char, i8)]) -> Self {
+        Self { kerning, ..self }
+    }
+
     pub(crate) fn letter(&self, letter: char) -> &'static FontLetter {
-        &self.letters[letter as usize & (128 - 1)]
+        match self.characters.binary_search(&letter) {
+            Ok(index) => &self.letters[index],
+            Err(_) => self.fallback,
+        }
+    }
+
+    /// Whether the font has a glyph for the character, rather than drawing it
+    /// with the fallback glyph.
+    #[must_use]
+    pub fn has_glyph(&self, letter: char) -> bool {
+        self.characters.binary_search(&letter).is_ok()
+    }
+
+    /// How far to move `c` horizontally when it is drawn after `previous`.
+    pub(crate) fn kerning(&self, previous: char, c: char) -> i32 {
+        match self
+            .kerning
+            .binary_search_by_key(&(previous, c), |&(first, second, _)| (first, second))
+        {
+            Ok(index) => i32::from(self.kerning[index].2),
+            Err(_) => 0,
+        }
     }
 
     pub(crate) fn ascent(&self) -> i32 {
@@ -82,6 +136,7 @@ impl Font {
         TextRenderer {
             current_x_pos: 0,
             current_y_pos: 0,
+            previous_char: None,
             font: self,
             tile_pos,
             tiles: Default::default(),
@@ -93,6 +148,7 @@ impl Font {
 pub struct TextRenderer<'a> {
     current_x_pos: i32,
     current_y_pos: i32,
+    previous_char: Option<char>,
     font: &'a Font,
     tile_pos: Vector2D<u16>,
     tiles: HashMap<(i32, i32), DynamicTile<'a>>,
@@ -246,10 +302,16 @@ impl<'a, 'b> TextRenderer<'b> {
         if c == '\n' {
             self.current_y_pos += self.font.line_height;
             self.current_x_pos = 0;
+            self.previous_char = None;
         } else {
+            if let Some(previous) = self.previous_char {
+                self.current_x_pos += self.font.kerning(previous, c);
+            }
+
             let letter = self.font.letter(c);
             self.render_letter(letter, vram_manager, foreground_colour, background_colour);
             self.current_x_pos += i32::from(letter.advance_width);
+            self.previous_char = Some(c);
         }
     }
 
@@ -257,6 +319,7 @@ impl<'a, 'b> TextRenderer<'b> {
     pub fn clear(&mut self, vram_manager: &mut VRamManager) {
         self.current_x_pos = 0;
         self.current_y_pos = 0;
+        self.previous_char = None;
         let tiles = core::mem::take(&mut self.tiles);
 
         for (_, tile) in tiles.into_iter() {
@@ -268,7 +331,10 @@ impl<'a, 'b> TextRenderer<'b> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::display::tiled::{TileFormat, TiledMap};
+    use crate::display::{
+        tiled::{TileFormat, TiledMap},
+        Rgb15,
+    };
     const FONT: Font = crate::include_font!("examples/font/yoster.ttf", 12);
 
     #[test_case]
@@ -281,10 +347,13 @@ mod tests {
             TileFormat::FourBpp,
         );
 
-        vram.set_background_palette_raw(&[
-            0x0000, 0x0ff0, 0x00ff, 0xf00f, 0xf0f0, 0x0f0f, 0xaaaa, 0x5555, 0x0000, 0x0000, 0x0000,
-            0x0000, 0x0000, 0x0000, 0x0000, 0x0000,
-        ]);
+        vram.set_background_palette_raw(
+            &[
+                0x0000, 0x0ff0, 0x00ff, 0xf00f, 0xf0f0, 0x0f0f, 0xaaaa, 0x5555, 0x0000, 0x0000,
+                0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000,
+            ]
+            .map(Rgb15::new),
+        );
 
         let background_tile = vram.new_dynamic_tile().fill_with(0);
 
@@ -327,4 +396,101 @@ mod tests {
             renderer.clear(&mut vram);
         }
     }
+
+    #[test_case]
+    fn unknown_characters_use_the_fallback(_gba: &mut crate::Gba) {
+        assert!(FONT.has_glyph('A'));
+        assert!(!FONT.has_glyph('\u{3042}'));
+
+        assert!(core::ptr::eq(
+            FONT.letter('\u{3042}'),
+            FONT.letter('\u{4e00}')
+        ));
+        assert!(!core::ptr::eq(FONT.letter('A'), FONT.letter('\u{3042}')));
+    }
+
+    crate::include_strings!(
+        crate,
+        test_strings,
+        en => "examples/strings/en.toml",
+        fr => "examples/strings/fr.toml",
+    );
+
+    #[test_case]
+    fn strings_follow_the_language(_gba: &mut crate::Gba) {
+        use alloc::string::ToString;
+        use test_strings::{Language, Text};
+
+        const STRINGS_FONT: Font = crate::include_font!(
+            "examples/font/yoster.ttf", 12,
+            strings_from: ["examples/strings/en.toml", "examples/strings/fr.toml"],
+        );
+
+        assert_eq!(test_strings::language(), Language::En);
+        assert_eq!(Text::MenuStart.text(), "Start game");
+
+        test_strings::set_language(Language::Fr);
+        assert_eq!(Text::MenuSettings.to_string(), "Réglages");
+        assert_eq!(Text::Greeting.get(Language::En), "Hello, World!");
+        test_strings::set_language(Language::En);
+
+        for &language in Language::ALL {
+            for &text in Text::ALL {
+                assert!(text
+                    .get(language)
+                    .chars()
+                    .all(|c| STRINGS_FONT.has_glyph(c)));
+            }
+        }
+
+        assert!(!STRINGS_FONT.has_glyph('z'));
+    }
+
+    #[test_case]
+    fn glyph_grids_match_bmfont(_gba: &mut crate::Gba) {
+        const GRID_FONT: Font = crate::include_font!(
+            "examples/font/pixel-font.png",
+            cell_size: (8, 8),
+            characters: "AB? ",
+            baseline: 7,
+        );
+        const BMFONT: Font = crate::include_font!("examples/font/pixel-font.fnt");
+
+        for c in ['A', 'B', '?', ' '] {
+            let (grid, bmfont) = (GRID_FONT.letter(c), BMFONT.letter(c));
+
+            assert_eq!(grid.width, bmfont.width);
+            assert_eq!(grid.height, bmfont.height);
+            assert_eq!(grid.xmin, bmfont.xmin);
+            assert_eq!(grid.ymin, bmfont.ymin);
+            assert_eq!(grid.advance_width, bmfont.advance_width);
+            assert_eq!(grid.data, bmfont.data);
+        }
+
+        assert_eq!(GRID_FONT.ascent(), BMFONT.ascent());
+        assert_eq!(GRID_FONT.letter('C').data, GRID_FONT.letter('?').data);
+    }
+
+    #[test_case]
+    fn bmfont_kerning_is_included(_gba: &mut crate::Gba) {
+        const BMFONT: Font = crate::include_font!("examples/font/pixel-font.fnt");
+
+        assert_eq!(BMFONT.kerning('A', 'B'), -1);
+        assert_eq!(BMFONT.kerning('B', 'A'), 0);
+        // 'C' has no glyph, so its pair is left out
+        assert_eq!(BMFONT.kerning('B', 'C'), 0);
+    }
+
+    #[test_case]
+    fn glyph_ranges_include_extra_characters(_gba: &mut crate::Gba) {
+        const ACCENTED_FONT: Font =
+            crate::include_font!("examples/font/yoster.ttf", 12, ranges: ['\u{e0}'..='\u{ff}']);
+
+        assert!(ACCENTED_FONT.has_glyph('\u{e9}'));
+        assert!(!ACCENTED_FONT.has_glyph('A'));
+        assert!(!core::ptr::eq(
+            ACCENTED_FONT.letter('\u{e9}'),
+            ACCENTED_FONT.letter('\u{e8}')
+        ));
+    }
 }
@@ -0,0 +1,446 @@
+use core::fmt::Write;
+
+use alloc::collections::VecDeque;
+
+use crate::display::{
+    canvas::Canvas,
+    object::{
+        font::{
+            is_private_use,
+            preprocess::{Preprocessed, PreprocessedElement},
+            WhiteSpace,
+        },
+        ChangeColour, TextAlignment,
+    },
+    tiled::{RegularMap, TileCanvas, VRamManager},
+};
+use crate::fixnum::{Num, Vector2D};
+
+use super::{Font, FontLetter};
+
+/// The widest a letter group can be. Words are laid out in groups of letters
+/// at most this wide, and can only be split between lines at the edges of a
+/// group.
+const MAX_GROUP_WIDTH: i32 = 64;
+
+/// A rectangle of [`DynamicTile`](crate::display::tiled::DynamicTile)s on a
+/// background which text is wrapped and aligned in. This uses the same layout
+/// as [`ObjectTextRender`](crate::display::object::ObjectTextRender),
+/// including [`ChangeColour`], but draws onto a background, which is better
+/// suited to long passages of text.
+///
+/// Write text to it with the [`Write`] trait, then [`draw`](TextBox::draw) it
+/// and [`commit`](TextBox::commit) it to a background. Words are only laid
+/// out once the space or new line after them is written. When there is more
+/// text than fits in the box, [`pop_line`](TextBox::pop_line) and
+/// [`scroll_to_end`](TextBox::scroll_to_end) scroll it up.
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// use agb::{
+///     display::{
+///         font::TextBox,
+///         object::{ChangeColour, TextAlignment},
+///         tiled::{RegularMap, VRamManager},
+///         Font,
+///     },
+///     fixnum::Vector2D,
+/// };
+///
+/// use core::fmt::Write;
+///
+/// const FONT: Font = agb::include_font!("examples/font/yoster.ttf", 12);
+///
+/// # fn foo(bg: &mut RegularMap, vram: &mut VRamManager) {
+/// let mut text_box = TextBox::new(&FONT, vram, Vector2D::new(28, 6), TextAlignment::Left, 2);
+///
+/// let _ = writeln!(text_box, "Hello, {}World{}!", ChangeColour::new(2), ChangeColour::new(1));
+/// text_box.scroll_to_end();
+/// text_box.draw();
+/// text_box.commit(bg, vram, Vector2D::new(1, 13));
+/// # }
+/// ```
+pub struct TextBox<'a> {
+    font: &'a Font,
+    canvas: TileCanvas<'a>,
+    characters: VecDeque<char>,
+    preprocessed: Preprocessed,
+    alignment: TextAlignment,
+    paragraph_spacing: i32,
+    foreground_colour: u8,
+    background_colour: u8,
+}
+
+impl<'a> TextBox<'a> {
+    /// Allocates the tiles for a text box of the given size in tiles. Text is
+    /// drawn in colour 1 on colour 0 until changed.
+    #[must_use]
+    pub fn new(
+        font: &'a Font,
+        vram_manager: &mut VRamManager,
+        size_in_tiles: Vector2D<u16>,
+        alignment: TextAlignment,
+        paragraph_spacing: i32,
+    ) -> Self {
+        Self {
+            font,
+            canvas: TileCanvas::new(vram_manager, size_in_tiles, 0),
+            characters: VecDeque::new(),
+            preprocessed: Preprocessed::new(),
+            alignment,
+            paragraph_spacing,
+            foreground_colour: 1,
+            background_colour: 0,
+        }
+    }
+
+    /// Sets the palette indices of the text and of the rest of the box. The
+    /// text colour can be changed part way through with [`ChangeColour`].
+    pub fn set_colours(&mut self, foreground_colour: u8, background_colour: u8) {
+        assert!(foreground_colour < 16);
+        assert!(background_colour < 16);
+
+        self.foreground_colour = foreground_colour;
+        self.background_colour = background_colour;
+    }
+
+    /// Whether all of the text fits in the box.
+    #[must_use]
+    pub fn fits(&self) -> bool {
+        let (width, height) = (self.canvas.size().x, self.canvas.size().y);
+        let line_height = self.font.line_height();
+        let mut y = 0;
+
+        for (_, mut elements) in self
+            .preprocessed
+            .lines_element(width, self.minimum_space_width())
+        {
+            if y + line_height > height {
+                return false;
+            }
+
+            y += line_height;
+            if elements.any(|element| {
+                element.decode() == PreprocessedElement::WhiteSpace(WhiteSpace::NewLine)
+            }) {
+                y += self.paragraph_spacing;
+            }
+        }
+
+        true
+    }
+
+    /// Removes the top line, moving the rest of the text up. Returns whether
+    /// there was a line to remove. You must [`draw`](TextBox::draw) the text
+    /// again to see the change.
+    pub fn pop_line(&mut self) -> bool {
+        let width = self.canvas.size().x;
+        let Some((line, elements)) = self
+            .preprocessed
+            .lines_element(width, self.minimum_space_width())
+            .next()
+        else {
+            return false;
+        };
+
+        let mut cursor = Cursor {
+            characters: &self.characters,
+            index: 0,
+            colour: self.foreground_colour,
+        };
+
+        for element in elements {
+            match element.decode() {
+                PreprocessedElement::LetterGroup { .. } => {
+                    cursor.letter_group(self.font, group_width(width), |_, _, _| {});
+                }
+                PreprocessedElement::WhiteSpace(_) => cursor.skip_white_space(),
+            }
+        }
+
+        let (used, colour) = (cursor.index, cursor.colour);
+
+        self.characters.drain(..used);
+        self.foreground_colour = colour;
+        self.preprocessed.pop(&line);
+
+        true
+    }
+
+    /// Removes lines from the top until the rest of the text fits in the box.
+    pub fn scroll_to_end(&mut self) {
+        while !self.fits() && self.pop_line() {}
+    }
+
+    /// Removes all of the text. You must [`draw`](TextBox::draw) the text
+    /// again to see the change.
+    pub fn clear(&mut self) {
+        self.characters.clear();
+        self.preprocessed = Preprocessed::new();
+    }
+
+    /// Draws the lines of text which fit in the box onto its tiles.
+    pub fn draw(&mut self) {
+        self.canvas.clear(self.background_colour);
+
+        let (width, height) = (self.canvas.size().x, self.canvas.size().y);
+        let font = self.font;
+        let minimum_space_width = self.minimum_space_width();
+        let line_height = font.line_height();
+        let ascent = font.ascent();
+
+        let mut cursor = Cursor {
+            characters: &self.characters,
+            index: 0,
+            colour: self.foreground_colour,
+        };
+        let mut y = 0;
+
+        for (line, elements) in self.preprocessed.lines_element(width, minimum_space_width) {
+            if y + line_height > height {
+                break;
+            }
+
+            let settings = self.alignment.settings(&line, minimum_space_width, width);
+            let mut x: Num<i32, 10> = settings.start_x.into();
+
+            for element in elements {
+                match element.decode() {
+                    PreprocessedElement::LetterGroup { width: group } => {
+                        let group_x = x.floor();
+                        let canvas = &mut self.canvas;
+
+                        cursor.letter_group(font, group_width(width), |letter, offset, colour| {
+                            let x_start = group_x + offset;
+                            let y_start =
+                                y + ascent - i32::from(letter.height) - i32::from(letter.ymin);
+
+                            for letter_y in 0..usize::from(letter.height) {
+                                for letter_x in 0..usize::from(letter.width) {
+                                    if letter.bit_absolute(letter_x, letter_y) {
+                                        canvas.draw_pixel(
+                                            (x_start + letter_x as i32, y_start + letter_y as i32)
+                                                .into(),
+                                            colour,
+                                        );
+                                    }
+                                }
+                            }
+                        });
+
+                        x += i32::from(group);
+                    }
+                    PreprocessedElement::WhiteSpace(space) => {
+                        cursor.skip_white_space();
+
+                        match space {
+                            WhiteSpace::NewLine => y += self.paragraph_spacing,
+                            WhiteSpace::Space => x += settings.space_width,
+                        }
+                    }
+                }
+            }
+
+            y += line_height;
+        }
+    }
+
+    /// Puts the tiles of the box on the background with the top left at the
+    /// given tile position.
+    pub fn commit(
+        &self,
+        bg: &mut RegularMap,
+        vram_manager: &mut VRamManager,
+        tile_pos: Vector2D<u16>,
+    ) {
+        self.canvas.commit(bg, vram_manager, tile_pos);
+    }
+
+    /// Frees the tiles used by the text box.
+    pub fn remove(self, vram_manager: &mut VRamManager) {
+        self.canvas.remove(vram_manager);
+    }
+
+    fn minimum_space_width(&self) -> i32 {
+        i32::from(self.font.letter(' ').advance_width)
+    }
+}
+
+impl Write for TextBox<'_> {
+    fn write_str(&mut self, text: &str) -> core::fmt::Result {
+        let group_width = group_width(self.canvas.size().x);
+
+        for c in text.chars() {
+            self.preprocessed.add_character(self.font, c, group_width);
+            self.characters.push_back(c);
+        }
+
+        Ok(())
+    }
+}
+
+fn group_width(box_width: i32) -> i32 {
+    box_width.min(MAX_GROUP_WIDTH)
+}
+
+/// Walks through the text in the same way as the preprocessor, so that each
+/// letter group and space in the layout can be matched up to its characters.
+struct Cursor<'c> {
+    characters: &'c VecDeque<char>,
+    index: usize,
+    colour: u8,
+}
+
+impl Cursor<'_> {
+    fn peek(&self) -> Option<char> {
+        self.characters.get(self.index).copied()
+    }
+
+    /// Calls `letter` with each letter of the next letter group, its offset
+    /// from the start of the group and its colour.
+    fn letter_group(
+        &mut self,
+        font: &Font,
+        group_width: i32,
+        mut letter: impl FnMut(&FontLetter, i32, u8),
+    ) {
+        let mut width = 0;
+        let mut previous = None;
+
+        while let Some(c) = self.peek() {
+            if c == ' ' || c == '\n' {
+                return;
+            }
+
+            if is_private_use(c) {
+                if let Some(change) = ChangeColour::try_from_char(c) {
+                    self.colour = change.0;
+                }
+                self.index += 1;
+                continue;
+            }
+
+            let mut x = width;
+            if let Some(previous) = previous {
+                x += font.kerning(previous, c);
+            }
+
+            let font_letter = font.letter(c);
+            if x + i32::from(font_letter.width) > group_width {
+                return;
+            }
+
+            if x != 0 {
+                x += i32::from(font_letter.xmin);
+            }
+
+            letter(font_letter, x, self.colour);
+
+            width = x + i32::from(font_letter.advance_width);
+            previous = Some(c);
+            self.index += 1;
+        }
+    }
+
+    /// Moves past the next space or new line, and any colour changes before
+    /// it.
+    fn skip_white_space(&mut self) {
+        while let Some(c) = self.peek() {
+            self.index += 1;
+
+            if let Some(change) = ChangeColour::try_from_char(c) {
+                self.colour = change.0;
+            } else if c == ' ' || c == '\n' {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FONT: Font = crate::include_font!("examples/font/pixel-font.fnt");
+
+    fn columns_drawn(text_box: &TextBox, colour: u8) -> (i32, i32) {
+        let size = text_box.canvas.size();
+        let drawn = (0..size.x)
+            .filter(|&x| (0..size.y).any(|y| text_box.canvas.pixel((x, y).into()) == colour));
+
+        drawn.fold((i32::MAX, i32::MIN), |(min, max), x| {
+            (min.min(x), max.max(x))
+        })
+    }
+
+    #[test_case]
+    fn text_wraps_and_scrolls(gba: &mut crate::Gba) {
+        let (_, mut vram) = gba.display.video.tiled0();
+
+        // one 9 pixel line fits in 32x16
+        let mut text_box = TextBox::new(
+            &FONT,
+            &mut vram,
+            Vector2D::new(4, 2),
+            TextAlignment::Left,
+            0,
+        );
+        let _ = writeln!(text_box, "AB AB AB");
+
+        assert!(!text_box.fits());
+        assert!(text_box.pop_line());
+        assert!(text_box.fits());
+
+        text_box.draw();
+        // both letters are 5 pixels wide with an advance of 6, and 'B' is
+        // kerned 1 pixel closer to 'A', so it ends at column 9 rather than 10
+        assert_eq!(columns_drawn(&text_box, 1), (0, 9));
+
+        text_box.remove(&mut vram);
+    }
+
+    #[test_case]
+    fn text_is_aligned_and_coloured(gba: &mut crate::Gba) {
+        let (_, mut vram) = gba.display.video.tiled0();
+
+        let mut text_box = TextBox::new(
+            &FONT,
+            &mut vram,
+            Vector2D::new(4, 2),
+            TextAlignment::Right,
+            0,
+        );
+        let _ = writeln!(text_box, "A{}B", ChangeColour::new(2));
+
+        text_box.draw();
+        assert_eq!(columns_drawn(&text_box, 1), (21, 25));
+        assert_eq!(columns_drawn(&text_box, 2), (26, 30));
+
+        text_box.remove(&mut vram);
+    }
+
+    #[test_case]
+    fn long_words_are_split_between_lines(gba: &mut crate::Gba) {
+        let (_, mut vram) = gba.display.video.tiled0();
+
+        let mut text_box = TextBox::new(
+            &FONT,
+            &mut vram,
+            Vector2D::new(2, 4),
+            TextAlignment::Left,
+            0,
+        );
+        let _ = writeln!(text_box, "ABABABAB");
+
+        let mut lines = 0;
+        while text_box.pop_line() {
+            lines += 1;
+        }
+
+        assert!(lines > 1);
+        assert!(text_box.characters.is_empty());
+
+        text_box.remove(&mut vram);
+    }
+}
@@ -15,6 +15,10 @@ use self::{
 pub mod bitmap3;
 /// Graphics mode 4. Bitmap 4 provides two 8-bit paletted framebuffers with page switching.
 pub mod bitmap4;
+/// Graphics mode 5. Bitmap 5 provides two smaller 16-bit colour framebuffers with page switching.
+pub mod bitmap5;
+/// Images for the bitmap modes, made with [include_bitmap](crate::include_bitmap).
+pub mod bitmap_data;
 /// Test logo of agb.
 pub mod example_logo;
 pub mod object;
@@ -29,11 +33,17 @@ pub mod video;
 
 pub mod affine;
 pub mod blend;
+/// Drawing lines, shapes, sprites and text onto dynamic sprites and tiles.
+pub mod canvas;
 pub mod window;
 
 pub mod font;
 pub use font::{Font, FontLetter};
 
+/// The colour type used by palettes and bitmaps.
+pub mod colour;
+pub use colour::Rgb15;
+
 const DISPLAY_CONTROL: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0000) };
 pub(crate) const DISPLAY_STATUS: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0004) };
 const VCOUNT: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0006) };
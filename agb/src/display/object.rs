@@ -9,21 +9,25 @@
 //! harder to integrate into your games depending on how they are architectured.
 
 mod affine;
-mod font;
+pub(crate) mod font;
 mod managed;
 mod sprites;
 mod unmanaged;
 
 pub use sprites::{
-    include_aseprite, DynamicSprite, Graphics, PaletteVram, Size, Sprite, SpriteLoader, SpriteVram,
-    Tag, TagMap,
+    include_aseprite, include_spritesheet, AnimationPlayer, DynamicSprite, Graphics, PaletteVram,
+    Repeat, RotatedTag, Size, Slice, Sprite, SpriteLoader, SpriteVram, Tag, TagMap,
 };
 
-pub use affine::AffineMatrixInstance;
+pub use affine::{AffineMatrixInstance, AffineMatrixOverflow, AffineMatrixUsage};
 pub use managed::{OamManaged, Object};
-pub use unmanaged::{AffineMode, OamIterator, OamSlot, OamUnmanaged, ObjectUnmanaged};
+pub use unmanaged::{
+    AffineMode, HBlankMultiplexer, OamIterator, OamSlot, OamUnmanaged, ObjectUnmanaged,
+};
 
-pub use font::{ChangeColour, ObjectTextRender, TextAlignment};
+pub use font::{
+    ChangeColour, Dialogue, DialogueStatus, ObjectTextRender, TextAlignment, TextEffect,
+};
 
 use super::DISPLAY_CONTROL;
 
@@ -44,6 +44,34 @@ impl AffineMatrixInstance {
     }
 }
 
+/// What to do when more than 32 different affine matrices are used by objects
+/// in a single frame. Identical matrices are always shared, even between
+/// different [`AffineMatrixInstance`]s, so only distinct matrices count towards
+/// the limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AffineMatrixOverflow {
+    /// Panic, which is the default.
+    #[default]
+    Panic,
+    /// Use the already written matrix which is closest to the one requested.
+    ReuseNearest,
+    /// Hide the objects that use the matrix.
+    Hide,
+}
+
+/// How the affine matrices were used in the most recently written frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AffineMatrixUsage {
+    /// The number of matrices written to OAM.
+    pub matrices: usize,
+    /// The number of instances which shared a matrix identical to one already
+    /// written rather than using a new one.
+    pub deduplicated: usize,
+    /// The number of instances which didn't fit and were handled according to
+    /// the [`AffineMatrixOverflow`] setting.
+    pub overflowed: usize,
+}
+
 impl AffineMatrixVram {
     pub fn frame_count(&self) -> u32 {
         self.0.frame_count.get()
@@ -53,6 +81,10 @@ impl AffineMatrixVram {
         self.0.frame_count.set(frame);
     }
 
+    pub fn matrix(&self) -> AffineMatrixObject {
+        self.0.matrix
+    }
+
     pub fn location(&self) -> u32 {
         self.0.location.get()
     }
@@ -12,9 +12,19 @@ use self::{
 
 use super::{OamIterator, ObjectUnmanaged, PaletteVram, Size, SpriteVram};
 
-mod preprocess;
+mod dialogue;
+pub(crate) mod preprocess;
 mod renderer;
 
+pub use dialogue::{Dialogue, DialogueStatus};
+
+/// How many frames each fade in palette is used for.
+const FADE_IN_FRAMES_PER_PALETTE: u32 = 4;
+/// How many frames each rainbow palette is used for.
+const RAINBOW_FRAMES_PER_PALETTE: u32 = 8;
+/// How many frames letters stay in each position when shaking.
+const SHAKE_FRAMES: u32 = 4;
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 #[non_exhaustive]
 pub(crate) enum WhiteSpace {
@@ -42,10 +52,29 @@ struct BufferedRender<'font> {
 
 #[derive(Debug, Default)]
 struct Letters {
-    letters: VecDeque<SpriteVram>,
+    letters: VecDeque<LetterGroup>,
     number_of_groups: usize,
 }
 
+/// A rendered group of letters, which is shown as one object.
+#[derive(Debug)]
+pub(crate) struct LetterGroup {
+    sprite: SpriteVram,
+    effect: TextEffect,
+    /// The frame the group was first shown on
+    shown_at: Option<u32>,
+}
+
+impl LetterGroup {
+    pub(crate) fn new(sprite: SpriteVram, effect: TextEffect) -> Self {
+        Self {
+            sprite,
+            effect,
+            shown_at: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[non_exhaustive]
 /// The text alignment of the layout
@@ -61,13 +90,18 @@ pub enum TextAlignment {
     Justify,
 }
 
-struct TextAlignmentSettings {
-    space_width: Num<i32, 10>,
-    start_x: i32,
+pub(crate) struct TextAlignmentSettings {
+    pub(crate) space_width: Num<i32, 10>,
+    pub(crate) start_x: i32,
 }
 
 impl TextAlignment {
-    fn settings(self, line: &Line, minimum_space_width: i32, width: i32) -> TextAlignmentSettings {
+    pub(crate) fn settings(
+        self,
+        line: &Line,
+        minimum_space_width: i32,
+        width: i32,
+    ) -> TextAlignmentSettings {
         match self {
             TextAlignment::Left => TextAlignmentSettings {
                 space_width: minimum_space_width.into(),
@@ -112,7 +146,7 @@ impl<'font> BufferedRender<'font> {
     }
 }
 
-fn is_private_use(c: char) -> bool {
+pub(crate) fn is_private_use(c: char) -> bool {
     ('\u{E000}'..'\u{F8FF}').contains(&c)
 }
 
@@ -123,16 +157,16 @@ fn is_private_use(c: char) -> bool {
 /// # #![no_main]
 /// use agb::display::object::{ObjectTextRender, PaletteVram, ChangeColour, Size};
 /// use agb::display::palette16::Palette16;
-/// use agb::display::Font;
+/// use agb::display::{Font, Rgb15};
 ///
 /// use core::fmt::Write;
 ///
 /// const EXAMPLE_FONT: Font = agb::include_font!("examples/font/yoster.ttf", 12);
 ///
 /// # fn foo() {
-/// let mut palette = [0x0; 16];
-/// palette[1] = 0xFF_FF;
-/// palette[2] = 0x00_FF;
+/// let mut palette = [Rgb15::BLACK; 16];
+/// palette[1] = Rgb15::WHITE;
+/// palette[2] = Rgb15::new(0x00_FF);
 /// let palette = Palette16::new(palette);
 /// let palette = PaletteVram::new(&palette).unwrap();
 /// let mut writer = ObjectTextRender::new(&EXAMPLE_FONT, Size::S16x16, palette);
@@ -140,7 +174,7 @@ fn is_private_use(c: char) -> bool {
 /// let _ = writeln!(writer, "Hello, {}World{}!", ChangeColour::new(2), ChangeColour::new(1));
 /// # }
 /// ```
-pub struct ChangeColour(u8);
+pub struct ChangeColour(pub(crate) u8);
 
 impl ChangeColour {
     #[must_use]
@@ -151,7 +185,7 @@ impl ChangeColour {
         Self(colour as u8)
     }
 
-    fn try_from_char(c: char) -> Option<Self> {
+    pub(crate) fn try_from_char(c: char) -> Option<Self> {
         let c = c as u32 as usize;
         if (0xE000..0xE000 + 16).contains(&c) {
             Some(ChangeColour::new(c - 0xE000))
@@ -171,12 +205,81 @@ impl Display for ChangeColour {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[non_exhaustive]
+/// Animates the letters after it until the next effect. Like [`ChangeColour`],
+/// this is written inline with the text. Effects apply to each letter group,
+/// which is as many letters as fit in one sprite, so a small sprite size makes
+/// them apply to each letter.
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// use agb::display::object::{ObjectTextRender, PaletteVram, Size, TextEffect};
+/// use agb::display::Font;
+///
+/// use core::fmt::Write;
+///
+/// const EXAMPLE_FONT: Font = agb::include_font!("examples/font/yoster.ttf", 12);
+///
+/// # fn foo(palette: PaletteVram) {
+/// let mut writer = ObjectTextRender::new(&EXAMPLE_FONT, Size::S8x8, palette);
+///
+/// let _ = writeln!(writer, "That's {}terrifying{}!", TextEffect::Shake, TextEffect::None);
+/// # }
+/// ```
+pub enum TextEffect {
+    #[default]
+    /// No effect, which ends the previous one.
+    None,
+    /// The letters bob up and down in a wave.
+    Wave,
+    /// The letters shake around their positions.
+    Shake,
+    /// The letters step through the palettes set with
+    /// [`ObjectTextRender::set_fade_in_palettes`] as they appear.
+    FadeIn,
+    /// The letters cycle through the palettes set with
+    /// [`ObjectTextRender::set_rainbow_palettes`].
+    Rainbow,
+}
+
+impl TextEffect {
+    const FIRST_CHAR: u32 = 0xE010;
+    const EFFECTS: [TextEffect; 5] = [
+        TextEffect::None,
+        TextEffect::Wave,
+        TextEffect::Shake,
+        TextEffect::FadeIn,
+        TextEffect::Rainbow,
+    ];
+
+    pub(crate) fn try_from_char(c: char) -> Option<Self> {
+        let index = (c as u32).checked_sub(Self::FIRST_CHAR)?;
+        Self::EFFECTS.get(index as usize).copied()
+    }
+
+    fn to_char(self) -> char {
+        char::from_u32(Self::FIRST_CHAR + self as u32).unwrap()
+    }
+}
+
+impl Display for TextEffect {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_char(self.to_char())
+    }
+}
+
+/// The palettes used by [`TextEffect::FadeIn`] and [`TextEffect::Rainbow`].
+#[derive(Clone, Default)]
+struct EffectPalettes {
+    fade_in: Vec<PaletteVram>,
+    rainbow: Vec<PaletteVram>,
+}
+
 impl BufferedRender<'_> {
     fn input_character(&mut self, character: char) {
-        if !is_private_use(character) {
-            self.preprocessor
-                .add_character(self.font, character, self.char_render.sprite_width());
-        }
+        self.preprocessor
+            .add_character(self.font, character, self.char_render.sprite_width());
         self.buffered_chars.push_back(character);
     }
 
@@ -210,7 +313,7 @@ impl BufferedRender<'_> {
 /// #![no_main]
 /// use agb::display::object::{ObjectTextRender, PaletteVram, TextAlignment, Size};
 /// use agb::display::palette16::Palette16;
-/// use agb::display::{Font, WIDTH};
+/// use agb::display::{Font, Rgb15, WIDTH};
 ///
 /// use core::fmt::Write;
 ///
@@ -221,8 +324,8 @@ impl BufferedRender<'_> {
 ///     let (mut unmanaged, _) = gba.display.object.get_unmanaged();
 ///     let vblank = agb::interrupt::VBlank::get();
 ///
-///     let mut palette = [0x0; 16];
-///     palette[1] = 0xFF_FF;
+///     let mut palette = [Rgb15::BLACK; 16];
+///     palette[1] = Rgb15::WHITE;
 ///     let palette = Palette16::new(palette);
 ///     let palette = PaletteVram::new(&palette).unwrap();
 ///
@@ -244,6 +347,8 @@ pub struct ObjectTextRender<'font> {
     buffer: BufferedRender<'font>,
     layout: LayoutCache,
     number_of_objects: usize,
+    effect_palettes: EffectPalettes,
+    frame: u32,
 }
 
 impl<'font> ObjectTextRender<'font> {
@@ -255,6 +360,8 @@ impl<'font> ObjectTextRender<'font> {
         Self {
             buffer: BufferedRender::new(font, sprite_size, palette),
             number_of_objects: 0,
+            effect_palettes: EffectPalettes::default(),
+            frame: 0,
             layout: LayoutCache {
                 positions: VecDeque::new(),
                 line_capacity: VecDeque::new(),
@@ -277,6 +384,18 @@ impl Write for ObjectTextRender<'_> {
 }
 
 impl ObjectTextRender<'_> {
+    /// Sets the palettes which letters with [`TextEffect::FadeIn`] step
+    /// through as they appear, before going back to their own palette.
+    pub fn set_fade_in_palettes(&mut self, palettes: Vec<PaletteVram>) {
+        self.effect_palettes.fade_in = palettes;
+    }
+
+    /// Sets the palettes which letters with [`TextEffect::Rainbow`] cycle
+    /// through.
+    pub fn set_rainbow_palettes(&mut self, palettes: Vec<PaletteVram>) {
+        self.effect_palettes.rainbow = palettes;
+    }
+
     /// Commits work already done to screen. You can commit to multiple places in the same frame.
     pub fn commit(&mut self, oam: &mut OamIterator) {
         for (object, slot) in self.layout.objects.iter().zip(oam) {
@@ -340,9 +459,21 @@ impl ObjectTextRender<'_> {
 
         self.layout.update_objects_to_display_at_position(
             position,
-            self.buffer.letters.letters.iter(),
+            self.buffer
+                .letters
+                .letters
+                .iter()
+                .map(|letter| &letter.sprite),
             self.number_of_objects,
         );
+
+        self.frame = self.frame.wrapping_add(1);
+        self.layout.apply_effects(
+            position,
+            self.buffer.letters.letters.iter_mut(),
+            self.frame,
+            &self.effect_palettes,
+        );
     }
 
     /// Causes the next letter group to be shown on the next update. Returns
@@ -443,6 +574,58 @@ impl LayoutCache {
         self.objects_are_at_origin = position;
     }
 
+    /// Moves and recolours the objects of letter groups which have effects.
+    fn apply_effects<'a>(
+        &mut self,
+        position: Vector2D<i32>,
+        letters: impl Iterator<Item = &'a mut LetterGroup>,
+        frame: u32,
+        palettes: &EffectPalettes,
+    ) {
+        for (index, ((object, offset), letter)) in self
+            .objects
+            .iter_mut()
+            .zip(&self.positions)
+            .zip(letters)
+            .enumerate()
+        {
+            let shown_at = *letter.shown_at.get_or_insert(frame);
+            let position = offset.change_base() + position;
+
+            match letter.effect {
+                TextEffect::None => {}
+                TextEffect::Wave => {
+                    let angle =
+                        Num::<i32, 8>::new((frame % 32) as i32) / 32 + Num::new(index as i32) / 8;
+                    let height = (angle.sin() * 2).floor();
+                    object.set_position(position + (0, height).into());
+                }
+                TextEffect::Shake => {
+                    if frame % SHAKE_FRAMES == 0 {
+                        let shake = (
+                            crate::rng::gen().rem_euclid(3) - 1,
+                            crate::rng::gen().rem_euclid(3) - 1,
+                        );
+                        object.set_position(position + shake.into());
+                    }
+                }
+                TextEffect::FadeIn => {
+                    let step = (frame.wrapping_sub(shown_at) / FADE_IN_FRAMES_PER_PALETTE) as usize;
+                    match palettes.fade_in.get(step) {
+                        Some(palette) => object.set_palette(palette.clone()),
+                        None => object.reset_palette(),
+                    };
+                }
+                TextEffect::Rainbow => {
+                    if !palettes.rainbow.is_empty() {
+                        let step = (frame / RAINBOW_FRAMES_PER_PALETTE) as usize + index;
+                        object.set_palette(palettes.rainbow[step % palettes.rainbow.len()].clone());
+                    }
+                }
+            }
+        }
+    }
+
     fn create_positions(
         &mut self,
         font: &Font,
@@ -509,3 +692,116 @@ struct LayoutSettings {
     alignment: TextAlignment,
     paragraph_spacing: i32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::{palette16::Palette16, Rgb15};
+
+    const FONT: Font = crate::include_font!("examples/font/pixel-font.fnt");
+
+    #[test_case]
+    fn text_effects_apply_to_letter_groups(_gba: &mut crate::Gba) {
+        let palette = PaletteVram::new(&Palette16::new([Rgb15::WHITE; 16])).unwrap();
+        let mut writer = ObjectTextRender::new(&FONT, Size::S8x8, palette);
+
+        let _ = writeln!(writer, "A{}B{} A", TextEffect::Wave, TextEffect::None);
+        writer.layout((64, 32).into(), TextAlignment::Left, 0);
+
+        while writer.next_letter_group() {}
+
+        let effects: Vec<_> = writer
+            .buffer
+            .letters
+            .letters
+            .iter()
+            .map(|letter| letter.effect)
+            .collect();
+        assert_eq!(
+            effects,
+            [TextEffect::None, TextEffect::Wave, TextEffect::None]
+        );
+
+        assert_eq!(
+            TextEffect::try_from_char(TextEffect::Rainbow.to_char()),
+            Some(TextEffect::Rainbow)
+        );
+        assert_eq!(
+            TextEffect::try_from_char(ChangeColour::new(1).to_char()),
+            None
+        );
+    }
+
+    #[test_case]
+    fn text_effects_move_and_recolour_letters(_gba: &mut crate::Gba) {
+        let new_palette = || PaletteVram::new(&Palette16::new([Rgb15::WHITE; 16])).unwrap();
+        let palette = new_palette();
+        let fade_in = [new_palette(), new_palette()];
+        let rainbow = [new_palette(), new_palette(), new_palette()];
+
+        let mut writer = ObjectTextRender::new(&FONT, Size::S8x8, palette.clone());
+        writer.set_fade_in_palettes(fade_in.to_vec());
+        writer.set_rainbow_palettes(rainbow.to_vec());
+
+        let _ = writeln!(
+            writer,
+            "A{}B{}A{}B{}A",
+            TextEffect::Wave,
+            TextEffect::FadeIn,
+            TextEffect::Rainbow,
+            TextEffect::None
+        );
+        writer.layout((64, 32).into(), TextAlignment::Left, 0);
+
+        while writer.next_letter_group() {}
+
+        let origin = Vector2D::new(10, 20);
+        let mut wave_heights = Vec::new();
+
+        // the first update is frame 1, which is when the letters are shown
+        for frame in 1..=40 {
+            writer.update(origin);
+            assert_eq!(writer.layout.objects.len(), 5);
+
+            let laid_out = |index: usize| {
+                let offset = writer.layout.positions[index];
+                (
+                    origin.x + i32::from(offset.x),
+                    origin.y + i32::from(offset.y),
+                )
+            };
+            let position = |index: usize| {
+                let bits = writer.layout.objects[index].attribute_bits();
+                (i32::from(bits[1] & 0x1ff), i32::from(bits[0] & 0xff))
+            };
+            let palette_bank =
+                |index: usize| writer.layout.objects[index].attribute_bits()[2] >> 12;
+
+            for index in [0, 4] {
+                assert_eq!(position(index), laid_out(index));
+                assert_eq!(palette_bank(index), palette.location());
+            }
+
+            let (x, y) = position(1);
+            assert_eq!(x, laid_out(1).0);
+            assert!((-2..=2).contains(&(y - laid_out(1).1)));
+            assert_eq!(palette_bank(1), palette.location());
+            wave_heights.push(y - laid_out(1).1);
+
+            let fade_in_palette = match (frame - 1) / FADE_IN_FRAMES_PER_PALETTE {
+                0 => &fade_in[0],
+                1 => &fade_in[1],
+                _ => &palette,
+            };
+            assert_eq!(position(2), laid_out(2));
+            assert_eq!(palette_bank(2), fade_in_palette.location());
+
+            let rainbow_palette = &rainbow[(frame / RAINBOW_FRAMES_PER_PALETTE + 3) as usize % 3];
+            assert_eq!(position(3), laid_out(3));
+            assert_eq!(palette_bank(3), rainbow_palette.location());
+        }
+
+        assert!(wave_heights.iter().any(|&height| height != wave_heights[0]));
+        assert_eq!(wave_heights[..8], wave_heights[32..]);
+    }
+}
@@ -0,0 +1,657 @@
+use core::fmt::Write;
+
+use agb_fixnum::{Num, Rect, Vector2D};
+use alloc::{collections::VecDeque, vec::Vec};
+
+use crate::{
+    display::{
+        object::{OamIterator, ObjectUnmanaged, PaletteVram, Size, SpriteVram},
+        Font,
+    },
+    input::{Button, ButtonController, Tri},
+};
+
+use super::{ChangeColour, EffectPalettes, ObjectTextRender, TextAlignment, TextEffect};
+
+/// How often the indicator blinks while waiting for the player, in frames.
+const INDICATOR_BLINK_FRAMES: u32 = 16;
+
+/// A dialogue box which reveals messages a little at a time, pages through
+/// messages too long for the box and asks the player to pick between choices.
+/// It uses an [`ObjectTextRender`] for the text.
+///
+/// Messages can contain markup in braces:
+///
+/// * `{pause:30}` waits for 30 frames before revealing any more.
+/// * `{speed:2}` reveals 2 letter groups per frame, and `{speed:1/4}` reveals
+///   one letter group every 4 frames. `{speed}` goes back to the speed set
+///   with [`set_speed`](Dialogue::set_speed).
+/// * `{colour:2}` changes the colour of the text to palette index 2, in the
+///   same way as [`ChangeColour`].
+/// * `{effect:wave}` animates the text after it with a [`TextEffect`], which
+///   is one of `none`, `wave`, `shake`, `fade_in` or `rainbow`.
+/// * `{{` is a literal `{`.
+///
+/// Anything else in braces, or a `{` which is never closed, is shown as it is
+/// written.
+///
+/// Text is revealed a letter group at a time, which is as many letters as fit
+/// in one sprite, so use a small sprite size such as [`Size::S8x8`] to reveal
+/// it letter by letter. Pauses and speed changes happen at the start of the
+/// letter group they are in.
+///
+/// Pressing A while the text is being revealed shows the rest of the page.
+/// Once the page is full, the indicator blinks in the bottom right corner and
+/// pressing A moves on to the next page. At the end of the message, pressing A
+/// finishes it, or for a question, up and down move the indicator between the
+/// choices and A picks one.
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// use agb::display::{
+///     object::{Dialogue, DialogueStatus, OamUnmanaged, PaletteVram, Size, SpriteVram},
+///     Font, WIDTH,
+/// };
+/// use agb::fixnum::Rect;
+/// use agb::input::ButtonController;
+///
+/// const FONT: Font = agb::include_font!("examples/font/yoster.ttf", 12);
+///
+/// # fn foo(mut oam: OamUnmanaged, palette: PaletteVram, arrow: SpriteVram) {
+/// let vblank = agb::interrupt::VBlank::get();
+/// let mut input = ButtonController::new();
+///
+/// let area = Rect::new((8, 112).into(), (WIDTH - 16, 40).into());
+/// let mut dialogue = Dialogue::new(&FONT, Size::S8x8, palette, arrow, area);
+///
+/// dialogue.ask("Do you want to {colour:2}save{colour:1}?{pause:20}", &["Yes", "No"]);
+///
+/// loop {
+///     input.update();
+///     if let DialogueStatus::Chosen(choice) = dialogue.update(&input) {
+///         // choice is 0 for "Yes" and 1 for "No"
+///     }
+///
+///     vblank.wait_for_vblank();
+///     dialogue.commit(&mut oam.iter());
+/// }
+/// # }
+/// ```
+pub struct Dialogue<'font> {
+    font: &'font Font,
+    sprite_size: Size,
+    palette: PaletteVram,
+    indicator: SpriteVram,
+    area: Rect<i32>,
+    alignment: TextAlignment,
+    speed: Num<i32, 8>,
+    effect_palettes: EffectPalettes,
+    message: Option<Message<'font>>,
+    frame: u32,
+}
+
+/// What the player has done, returned by [`Dialogue::update`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DialogueStatus {
+    /// There is no message being shown.
+    Idle,
+    /// The message is still being shown.
+    Showing,
+    /// The player has finished reading the message, which is removed.
+    Finished,
+    /// The player picked the choice with this index, and the question is
+    /// removed.
+    Chosen(usize),
+}
+
+impl<'font> Dialogue<'font> {
+    /// Creates a dialogue box which shows text within the area of the screen.
+    /// The indicator sprite shows when the player needs to press A, and marks
+    /// the selected choice.
+    #[must_use]
+    pub fn new(
+        font: &'font Font,
+        sprite_size: Size,
+        palette: PaletteVram,
+        indicator: SpriteVram,
+        area: Rect<i32>,
+    ) -> Self {
+        Self {
+            font,
+            sprite_size,
+            palette,
+            indicator,
+            area,
+            alignment: TextAlignment::Left,
+            speed: 1.into(),
+            effect_palettes: EffectPalettes::default(),
+            message: None,
+            frame: 0,
+        }
+    }
+
+    /// Sets how many letter groups are revealed each frame, which can be less
+    /// than 1. This is used for the next message.
+    pub fn set_speed(&mut self, speed: Num<i32, 8>) {
+        assert!(speed > 0.into(), "speed must be positive");
+        self.speed = speed;
+    }
+
+    /// Sets the palettes used by [`TextEffect::FadeIn`], in the same way as
+    /// [`ObjectTextRender::set_fade_in_palettes`], for the next message.
+    pub fn set_fade_in_palettes(&mut self, palettes: Vec<PaletteVram>) {
+        self.effect_palettes.fade_in = palettes;
+    }
+
+    /// Sets the palettes used by [`TextEffect::Rainbow`], in the same way as
+    /// [`ObjectTextRender::set_rainbow_palettes`], for the next message.
+    pub fn set_rainbow_palettes(&mut self, palettes: Vec<PaletteVram>) {
+        self.effect_palettes.rainbow = palettes;
+    }
+
+    /// Sets the alignment of the text, which is used for the next message.
+    pub fn set_alignment(&mut self, alignment: TextAlignment) {
+        self.alignment = alignment;
+    }
+
+    /// Starts showing a message, replacing any message already being shown.
+    pub fn say(&mut self, text: &str) {
+        let mut message = Message::new(self, text);
+        message.finish_writing(self);
+
+        self.message = Some(message);
+    }
+
+    /// Starts showing a question with the choices listed under it, replacing
+    /// any message already being shown. The question and choices should fit
+    /// in the box together so the player can see the question while choosing.
+    pub fn ask(&mut self, text: &str, choices: &[&str]) {
+        assert!(!choices.is_empty(), "a question needs at least one choice");
+
+        let mut message = Message::new(self, text);
+
+        let (indicator_width, _) = self.indicator.size().to_width_height();
+        let space_width = i32::from(self.font.letter(' ').advance_width).max(1);
+        let indent = (indicator_width as i32 + space_width) / space_width;
+
+        let mut first_letter_groups = Vec::with_capacity(choices.len());
+        for choice in choices {
+            let _ = writeln!(message.text);
+            for _ in 0..indent {
+                let _ = message.text.write_char(' ');
+            }
+
+            first_letter_groups.push(message.letter_groups_written());
+            message.write_markup(choice, self.speed);
+        }
+
+        message.choices = Some(Choices {
+            first_letter_groups,
+            selected: 0,
+        });
+        message.finish_writing(self);
+
+        self.message = Some(message);
+    }
+
+    /// Whether there is a message being shown.
+    #[must_use]
+    pub fn is_showing(&self) -> bool {
+        self.message.is_some()
+    }
+
+    /// Reveals more of the message and responds to the player's input. Call
+    /// this once per frame, in the same frame as and before
+    /// [`commit`](Dialogue::commit).
+    pub fn update(&mut self, input: &ButtonController) -> DialogueStatus {
+        self.frame = self.frame.wrapping_add(1);
+
+        let Some(message) = &mut self.message else {
+            return DialogueStatus::Idle;
+        };
+
+        let lines_per_page = (self.area.size.y / self.font.line_height()) as usize;
+        let pressed = input.is_just_pressed(Button::A);
+
+        let status = match message.state {
+            State::Revealing => {
+                if pressed {
+                    message.reveal_page();
+                } else {
+                    message.reveal();
+                }
+
+                DialogueStatus::Showing
+            }
+            State::PageFull => {
+                if pressed {
+                    message.state = State::Scrolling(lines_per_page);
+                }
+
+                DialogueStatus::Showing
+            }
+            State::Scrolling(lines) => {
+                message.state = if lines != 0 && message.text.pop_line() {
+                    State::Scrolling(lines - 1)
+                } else {
+                    State::Revealing
+                };
+
+                DialogueStatus::Showing
+            }
+            State::Finished => match &mut message.choices {
+                None if pressed => DialogueStatus::Finished,
+                Some(choices) if pressed => DialogueStatus::Chosen(choices.selected),
+                Some(choices) => {
+                    let count = choices.first_letter_groups.len();
+                    choices.selected = match input.repeated_y_tri() {
+                        Tri::Negative => (choices.selected + count - 1) % count,
+                        Tri::Positive => (choices.selected + 1) % count,
+                        Tri::Zero => choices.selected,
+                    };
+
+                    DialogueStatus::Showing
+                }
+                None => DialogueStatus::Showing,
+            },
+        };
+
+        if matches!(status, DialogueStatus::Finished | DialogueStatus::Chosen(_)) {
+            self.message = None;
+        } else {
+            message.text.update(self.area.position);
+        }
+
+        status
+    }
+
+    /// Shows the text and indicator using the slots of the iterator.
+    pub fn commit(&mut self, oam: &mut OamIterator) {
+        let Some(message) = &mut self.message else {
+            return;
+        };
+
+        message.text.commit(oam);
+
+        let Some(position) = self.indicator_position() else {
+            return;
+        };
+
+        if let Some(slot) = oam.next() {
+            let mut object = ObjectUnmanaged::new(self.indicator.clone());
+            object.show().set_position(position);
+            slot.set(&object);
+        }
+    }
+
+    /// Where to show the indicator on screen, if it should be shown this frame.
+    fn indicator_position(&self) -> Option<Vector2D<i32>> {
+        let message = self.message.as_ref()?;
+        let (width, height) = self.indicator.size().to_width_height();
+        let (width, height) = (width as i32, height as i32);
+
+        match (&message.state, &message.choices) {
+            (State::Finished, Some(choices)) => {
+                let letter_group = choices.first_letter_groups[choices.selected]
+                    .checked_sub(message.letter_groups_popped())?;
+                let line_top = message.text.layout.positions.get(letter_group)?.y;
+
+                Some(
+                    self.area.position
+                        + (
+                            0,
+                            i32::from(line_top) + (self.font.line_height() - height) / 2,
+                        )
+                            .into(),
+                )
+            }
+            (State::PageFull | State::Finished, _) => {
+                if (self.frame / INDICATOR_BLINK_FRAMES) % 2 != 0 {
+                    return None;
+                }
+
+                Some(self.area.position + self.area.size - (width, height).into())
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Revealing,
+    /// Waiting for the player to move on to the next page.
+    PageFull,
+    /// Removing this many more lines of the previous page, one per frame.
+    Scrolling(usize),
+    /// The whole message is shown.
+    Finished,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Markup {
+    Pause(u16),
+    Speed(Num<i32, 8>),
+}
+
+struct Choices {
+    /// The index of the first letter group of each choice.
+    first_letter_groups: Vec<usize>,
+    selected: usize,
+}
+
+struct Message<'font> {
+    text: ObjectTextRender<'font>,
+    /// Markup which takes effect once the given number of letter groups have
+    /// been revealed.
+    markup: VecDeque<(usize, Markup)>,
+    choices: Option<Choices>,
+    state: State,
+    speed: Num<i32, 8>,
+    /// How far through revealing the next letter group.
+    progress: Num<i32, 8>,
+    pause: u16,
+    revealed: usize,
+    total: usize,
+}
+
+impl<'font> Message<'font> {
+    fn new(dialogue: &Dialogue<'font>, text: &str) -> Self {
+        let mut render = ObjectTextRender::new(
+            dialogue.font,
+            dialogue.sprite_size,
+            dialogue.palette.clone(),
+        );
+        render.effect_palettes = dialogue.effect_palettes.clone();
+
+        let mut message = Self {
+            text: render,
+            markup: VecDeque::new(),
+            choices: None,
+            state: State::Revealing,
+            speed: dialogue.speed,
+            progress: 0.into(),
+            pause: 0,
+            revealed: 0,
+            total: 0,
+        };
+
+        message.write_markup(text, dialogue.speed);
+        message
+    }
+
+    fn finish_writing(&mut self, dialogue: &Dialogue<'font>) {
+        // the last word is only laid out once something follows it
+        let _ = writeln!(self.text);
+
+        self.total = self.letter_groups_written();
+        self.text.layout(dialogue.area.size, dialogue.alignment, 0);
+    }
+
+    fn letter_groups_written(&self) -> usize {
+        self.text.buffer.preprocessor.number_of_letter_groups()
+    }
+
+    fn letter_groups_popped(&self) -> usize {
+        self.revealed - self.text.number_of_objects
+    }
+
+    fn write_markup(&mut self, mut text: &str, default_speed: Num<i32, 8>) {
+        while let Some(brace) = text.find('{') {
+            let _ = self.text.write_str(&text[..brace]);
+            text = &text[brace + 1..];
+
+            if let Some(rest) = text.strip_prefix('{') {
+                let _ = self.text.write_char('{');
+                text = rest;
+                continue;
+            }
+
+            // anything which isn't valid markup, including a `{` which is
+            // never closed, is left in as text
+            let Some((tag, rest)) = text
+                .split_once('}')
+                .and_then(|(tag, rest)| Some((parse_tag(tag, default_speed)?, rest)))
+            else {
+                let _ = self.text.write_char('{');
+                continue;
+            };
+
+            text = rest;
+
+            match tag {
+                Tag::Colour(colour) => {
+                    let _ = write!(self.text, "{colour}");
+                }
+                Tag::Effect(effect) => {
+                    let _ = write!(self.text, "{effect}");
+                }
+                Tag::Markup(markup) => {
+                    self.markup
+                        .push_back((self.letter_groups_written(), markup));
+                }
+            }
+        }
+
+        let _ = self.text.write_str(text);
+    }
+
+    fn reveal(&mut self) {
+        if self.pause > 0 {
+            self.pause -= 1;
+            return;
+        }
+
+        self.progress += self.speed;
+
+        while self.progress >= 1.into() {
+            if !self.reveal_letter_group(false) {
+                self.progress = 0.into();
+                return;
+            }
+
+            self.progress -= 1;
+        }
+    }
+
+    /// Reveals the rest of the page straight away, skipping any pauses.
+    fn reveal_page(&mut self) {
+        while self.reveal_letter_group(true) {}
+
+        self.pause = 0;
+        self.progress = 0.into();
+    }
+
+    /// Reveals the next letter group, returning whether it could. It can't if
+    /// there is a pause, the page is full or the whole message is shown.
+    fn reveal_letter_group(&mut self, skip_pauses: bool) -> bool {
+        while let Some(&(letter_group, markup)) = self.markup.front() {
+            if letter_group > self.revealed {
+                break;
+            }
+
+            self.markup.pop_front();
+
+            match markup {
+                Markup::Pause(frames) if !skip_pauses => {
+                    self.pause = frames;
+                    return false;
+                }
+                Markup::Pause(_) => {}
+                Markup::Speed(speed) => self.speed = speed,
+            }
+        }
+
+        if self.revealed == self.total {
+            self.state = State::Finished;
+            return false;
+        }
+
+        if !self.text.next_letter_group() {
+            self.state = State::PageFull;
+            return false;
+        }
+
+        self.revealed += 1;
+        true
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Tag {
+    Colour(ChangeColour),
+    Effect(TextEffect),
+    Markup(Markup),
+}
+
+/// Parses the markup between a pair of braces, or `None` if it isn't valid
+/// markup, in which case it is shown as written.
+fn parse_tag(tag: &str, default_speed: Num<i32, 8>) -> Option<Tag> {
+    let (name, value) = tag.split_once(':').unwrap_or((tag, ""));
+    let number = |value: &str| value.trim().parse::<u16>().ok();
+
+    Some(match name.trim() {
+        "pause" => Tag::Markup(Markup::Pause(number(value)?)),
+        "speed" if value.is_empty() => Tag::Markup(Markup::Speed(default_speed)),
+        "speed" => {
+            let (numerator, denominator) = value.split_once('/').unwrap_or((value, "1"));
+            let (numerator, denominator) = (number(numerator)?, number(denominator)?);
+            if numerator == 0 || denominator == 0 {
+                return None;
+            }
+
+            Tag::Markup(Markup::Speed(
+                Num::new(i32::from(numerator)) / i32::from(denominator),
+            ))
+        }
+        "colour" => match number(value)? {
+            colour @ 0..=15 => Tag::Colour(ChangeColour::new(colour.into())),
+            _ => return None,
+        },
+        "effect" => Tag::Effect(match value.trim() {
+            "none" => TextEffect::None,
+            "wave" => TextEffect::Wave,
+            "shake" => TextEffect::Shake,
+            "fade_in" => TextEffect::FadeIn,
+            "rainbow" => TextEffect::Rainbow,
+            _ => return None,
+        }),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::{object::DynamicSprite, palette16::Palette16, Rgb15};
+
+    const FONT: Font = crate::include_font!("examples/font/pixel-font.fnt");
+
+    #[test_case]
+    fn markup_is_parsed(_gba: &mut crate::Gba) {
+        let default_speed = Num::new(3);
+
+        assert_eq!(
+            parse_tag("pause:30", default_speed),
+            Some(Tag::Markup(Markup::Pause(30)))
+        );
+        assert_eq!(
+            parse_tag("speed:1/4", default_speed),
+            Some(Tag::Markup(Markup::Speed(Num::new(1) / 4)))
+        );
+        assert_eq!(
+            parse_tag("speed", default_speed),
+            Some(Tag::Markup(Markup::Speed(default_speed)))
+        );
+        assert_eq!(
+            parse_tag("colour:2", default_speed),
+            Some(Tag::Colour(ChangeColour::new(2)))
+        );
+        assert_eq!(
+            parse_tag("effect:wave", default_speed),
+            Some(Tag::Effect(TextEffect::Wave))
+        );
+
+        for malformed in [
+            "foo",
+            "pause:x",
+            "speed:0",
+            "speed:1/0",
+            "colour:16",
+            "effect:foo",
+        ] {
+            assert_eq!(parse_tag(malformed, default_speed), None);
+        }
+    }
+
+    #[test_case]
+    fn malformed_markup_is_shown_as_text(_gba: &mut crate::Gba) {
+        let palette = PaletteVram::new(&Palette16::new([Rgb15::WHITE; 16])).unwrap();
+        let indicator = DynamicSprite::new(Size::S8x8).to_vram(palette.clone());
+
+        let area = Rect::new((0, 0).into(), (256, 32).into());
+        let mut dialogue = Dialogue::new(&FONT, Size::S8x8, palette, indicator, area);
+
+        let mut written = |text| {
+            dialogue.say(text);
+            let message = dialogue.message.as_ref().unwrap();
+            (message.total, message.markup.len())
+        };
+
+        assert_eq!(written("A{foo}B{pause:x}"), written("A{{foo}B{{pause:x}"));
+        assert_eq!(written("A{foo}B{pause:x}").1, 0);
+        assert_eq!(written("A{pause:3}B{pause"), (written("AB{{pause").0, 1));
+    }
+
+    #[test_case]
+    fn messages_are_revealed_with_pauses(_gba: &mut crate::Gba) {
+        let palette = PaletteVram::new(&Palette16::new([Rgb15::WHITE; 16])).unwrap();
+        let indicator = DynamicSprite::new(Size::S8x8).to_vram(palette.clone());
+        let input = ButtonController::new();
+
+        let area = Rect::new((0, 0).into(), (64, 32).into());
+        let mut dialogue = Dialogue::new(&FONT, Size::S8x8, palette, indicator, area);
+
+        // each letter is 6 pixels wide, so has a letter group of its own
+        dialogue.say("AB{pause:3}A {{B");
+        assert_eq!(dialogue.message.as_ref().unwrap().total, 5);
+
+        for _ in 0..2 {
+            assert_eq!(dialogue.update(&input), DialogueStatus::Showing);
+        }
+        assert_eq!(dialogue.message.as_ref().unwrap().revealed, 2);
+
+        for _ in 0..4 {
+            dialogue.update(&input);
+        }
+        assert_eq!(dialogue.message.as_ref().unwrap().revealed, 2);
+
+        for _ in 0..4 {
+            dialogue.update(&input);
+        }
+
+        let message = dialogue.message.as_ref().unwrap();
+        assert_eq!(message.revealed, 5);
+        assert_eq!(message.state, State::Finished);
+    }
+
+    #[test_case]
+    fn choices_start_on_their_own_lines(_gba: &mut crate::Gba) {
+        let palette = PaletteVram::new(&Palette16::new([Rgb15::WHITE; 16])).unwrap();
+        let indicator = DynamicSprite::new(Size::S8x8).to_vram(palette.clone());
+
+        let area = Rect::new((0, 0).into(), (64, 64).into());
+        let mut dialogue = Dialogue::new(&FONT, Size::S8x8, palette, indicator, area);
+
+        dialogue.ask("AB?", &["A", "B"]);
+
+        let message = dialogue.message.as_ref().unwrap();
+        assert_eq!(
+            message.choices.as_ref().unwrap().first_letter_groups,
+            [3, 4]
+        );
+        assert_eq!(message.total, 5);
+    }
+}
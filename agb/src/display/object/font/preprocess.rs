@@ -2,7 +2,7 @@ use alloc::collections::VecDeque;
 
 use crate::display::Font;
 
-use super::WhiteSpace;
+use super::{is_private_use, WhiteSpace};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) struct PreprocessedElementEncoded(u8);
@@ -45,6 +45,7 @@ pub(crate) struct Preprocessed {
 #[derive(Debug, Default)]
 struct Preprocessor {
     width_in_sprite: i32,
+    previous_character: Option<char>,
 }
 
 impl Preprocessor {
@@ -66,12 +67,22 @@ impl Preprocessor {
                     );
                     self.width_in_sprite = 0;
                 }
+                self.previous_character = None;
                 widths.push_back(
                     PreprocessedElement::WhiteSpace(WhiteSpace::from_char(space)).encode(),
                 );
             }
-            letter => {
-                let letter = font.letter(letter);
+            // colour and effect changes take up no space, and the renderer
+            // kerns the letters either side of them as if they weren't there
+            character if is_private_use(character) => {}
+            character => {
+                // kerning adjusts the previous letter's advance, so must be
+                // applied the same way as in the renderer before splitting
+                if let Some(previous) = self.previous_character.replace(character) {
+                    self.width_in_sprite += font.kerning(previous, character);
+                }
+
+                let letter = font.letter(character);
                 if self.width_in_sprite + letter.width as i32 > sprite_width {
                     widths.push_back(
                         PreprocessedElement::LetterGroup {
@@ -153,7 +164,22 @@ impl<'pre> Iterator for Lines<'pre> {
                         + spaces_after_last_word_count as i32 * self.minimum_space_width
                         >= self.layout_width
                     {
-                        line_idx_length = start_of_current_word;
+                        if start_of_current_word != 0 {
+                            line_idx_length = start_of_current_word;
+                            break;
+                        }
+
+                        // the word is too long for a line of its own, so split
+                        // it between letter groups, keeping at least one
+                        if length_of_current_word > 1 {
+                            length_of_current_word -= 1;
+                            length_of_current_word_pixels -= width as i32;
+                        } else {
+                            line_idx_length += 1;
+                        }
+
+                        current_line_width_pixels += length_of_current_word_pixels;
+                        number_of_letter_groups += length_of_current_word;
                         break;
                     }
                 }
@@ -208,6 +234,18 @@ impl Preprocessed {
             .add_character(font, c, sprite_width, &mut self.widths);
     }
 
+    /// The number of letter groups added so far, including the one which
+    /// letters are currently being added to.
+    pub(crate) fn number_of_letter_groups(&self) -> usize {
+        let complete = self
+            .widths
+            .iter()
+            .filter(|element| matches!(element.decode(), PreprocessedElement::LetterGroup { .. }))
+            .count();
+
+        complete + usize::from(self.preprocessor.width_in_sprite != 0)
+    }
+
     pub(crate) fn pop(&mut self, line: &Line) {
         let elements = line.number_of_text_elements();
         for _ in 0..elements {
@@ -239,3 +277,42 @@ impl Preprocessed {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::display::object::{ChangeColour, TextEffect};
+
+    const FONT: Font = crate::include_font!("examples/font/pixel-font.fnt");
+
+    fn elements(text: &[char]) -> Vec<PreprocessedElement> {
+        let mut preprocessed = Preprocessed::new();
+        for &c in text.iter().chain(&['\n']) {
+            preprocessed.add_character(&FONT, c, 32);
+        }
+
+        preprocessed
+            .widths
+            .iter()
+            .map(|element| element.decode())
+            .collect()
+    }
+
+    #[test_case]
+    fn colour_changes_keep_kerning(_gba: &mut crate::Gba) {
+        assert_eq!(FONT.kerning('A', 'B'), -1);
+
+        let plain = elements(&['A', 'B']);
+        assert_eq!(elements(&['A', ChangeColour::new(2).to_char(), 'B']), plain);
+        assert_eq!(elements(&['A', TextEffect::Wave.to_char(), 'B']), plain);
+
+        let separate = elements(&['A', ' ', 'B']);
+        let width = |element| match element {
+            PreprocessedElement::LetterGroup { width } => i32::from(width),
+            PreprocessedElement::WhiteSpace(_) => 0,
+        };
+        assert!(width(plain[0]) < width(separate[0]) + width(separate[2]));
+    }
+}
@@ -1,14 +1,15 @@
 use crate::display::{
-    object::{DynamicSprite, PaletteVram, Size, SpriteVram},
+    object::{DynamicSprite, PaletteVram, Size},
     Font,
 };
 
-use super::ChangeColour;
+use super::{ChangeColour, LetterGroup, TextEffect};
 
 struct WorkingLetter {
     dynamic: DynamicSprite,
     // where to render the letter from x_min to x_max
     x_offset: i32,
+    effect: TextEffect,
 }
 
 impl WorkingLetter {
@@ -16,6 +17,7 @@ impl WorkingLetter {
         Self {
             dynamic: DynamicSprite::new(size),
             x_offset: 0,
+            effect: TextEffect::None,
         }
     }
 
@@ -43,6 +45,8 @@ pub(crate) struct WordRender {
     working: WorkingLetter,
     config: Configuration,
     colour: usize,
+    effect: TextEffect,
+    previous_character: Option<char>,
 }
 
 impl WordRender {
@@ -56,11 +60,15 @@ impl WordRender {
             working: WorkingLetter::new(config.sprite_size),
             config,
             colour: 1,
+            effect: TextEffect::None,
+            previous_character: None,
         }
     }
 
     #[must_use]
-    pub(crate) fn finalise_letter(&mut self) -> Option<SpriteVram> {
+    pub(crate) fn finalise_letter(&mut self) -> Option<LetterGroup> {
+        self.previous_character = None;
+
         if self.working.x_offset == 0 {
             return None;
         }
@@ -68,18 +76,28 @@ impl WordRender {
         let mut new_sprite = DynamicSprite::new(self.config.sprite_size);
         core::mem::swap(&mut self.working.dynamic, &mut new_sprite);
         let sprite = new_sprite.to_vram(self.config.palette.clone());
+        let group = LetterGroup::new(sprite, self.working.effect);
         self.working.reset();
 
-        Some(sprite)
+        Some(group)
     }
 
     #[must_use]
-    pub(crate) fn render_char(&mut self, font: &Font, c: char) -> Option<SpriteVram> {
+    pub(crate) fn render_char(&mut self, font: &Font, c: char) -> Option<LetterGroup> {
         if let Some(next_colour) = ChangeColour::try_from_char(c) {
             self.colour = next_colour.0 as usize;
             return None;
         }
 
+        if let Some(effect) = TextEffect::try_from_char(c) {
+            self.effect = effect;
+            return None;
+        }
+
+        if let Some(previous) = self.previous_character {
+            self.working.x_offset += font.kerning(previous, c);
+        }
+
         let font_letter: &crate::display::FontLetter = font.letter(c);
 
         // uses more than the sprite can hold
@@ -91,7 +109,9 @@ impl WordRender {
             None
         };
 
-        if self.working.x_offset != 0 {
+        if self.working.x_offset == 0 {
+            self.working.effect = self.effect;
+        } else {
             self.working.x_offset += font_letter.xmin as i32;
         }
 
@@ -111,6 +131,7 @@ impl WordRender {
         }
 
         self.working.x_offset += font_letter.advance_width as i32;
+        self.previous_character = Some(c);
 
         group
     }
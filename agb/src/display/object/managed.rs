@@ -4,12 +4,12 @@ use agb_fixnum::Vector2D;
 
 use crate::{
     arena::{Arena, ArenaKey},
-    display::Priority,
+    display::{palette16::Palette16, Priority},
 };
 
 use super::{
-    AffineMatrixInstance, AffineMode, OamUnmanaged, ObjectUnmanaged, Sprite, SpriteLoader,
-    SpriteVram,
+    AffineMatrixInstance, AffineMode, OamUnmanaged, ObjectUnmanaged, PaletteVram, Sprite,
+    SpriteLoader, SpriteVram,
 };
 
 type ObjectKey = ArenaKey;
@@ -197,6 +197,15 @@ impl OamManaged<'_> {
         }
     }
 
+    /// Creates a palette in vram from a static palette, such as a palette
+    /// variant of a sprite from [`include_aseprite`][crate::include_aseprite].
+    pub fn palette(&self, palette: &'static Palette16) -> PaletteVram {
+        // safety: not reentrant
+        unsafe {
+            self.do_work_with_sprite_loader(|sprite_loader| sprite_loader.get_vram_palette(palette))
+        }
+    }
+
     /// Creates a sprite in vram and uses it to make an object from a static sprite from [`include_aseprite`][crate::include_aseprite].
     pub fn object_sprite(&self, sprite: &'static Sprite) -> Object<'_> {
         self.object(self.sprite(sprite))
@@ -468,6 +477,24 @@ impl Object<'_> {
 
         self
     }
+
+    /// Draws the object using the given palette rather than the sprite's own
+    /// one. This stays in place when the sprite changes until
+    /// [`reset_palette`][Object::reset_palette] is called.
+    pub fn set_palette(&mut self, palette: PaletteVram) -> &mut Self {
+        // safety: only have one of these, doesn't modify slotmap
+        unsafe { self.object().set_palette(palette) };
+
+        self
+    }
+
+    /// Goes back to drawing the object using the sprite's own palette.
+    pub fn reset_palette(&mut self) -> &mut Self {
+        // safety: only have one of these, doesn't modify slotmap
+        unsafe { self.object().reset_palette() };
+
+        self
+    }
 }
 
 #[cfg(test)]
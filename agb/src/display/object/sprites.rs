@@ -1,7 +1,13 @@
+mod animation;
+mod rotation;
+mod slice;
 mod sprite;
 mod sprite_allocator;
 
 const BYTES_PER_TILE_4BPP: usize = 32;
 
-pub use sprite::{include_aseprite, Graphics, Size, Sprite, Tag, TagMap};
+pub use animation::{AnimationPlayer, Repeat};
+pub use rotation::RotatedTag;
+pub use slice::Slice;
+pub use sprite::{include_aseprite, include_spritesheet, Graphics, Size, Sprite, Tag, TagMap};
 pub use sprite_allocator::{DynamicSprite, PaletteVram, SpriteLoader, SpriteVram};
@@ -0,0 +1,171 @@
+use super::sprite::{Sprite, Tag};
+
+/// How many times an [`AnimationPlayer`] should play through its animation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Repeat {
+    /// Keep playing the animation forever, it never finishes.
+    Forever,
+    /// Play the animation this many times and then stop on the last frame. A
+    /// count of zero is treated the same as playing it once.
+    Times(u32),
+}
+
+/// Plays back an animation from a [`Tag`], following the duration of each of
+/// the frames and the direction of the tag set in aseprite.
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// use agb::display::object::{AnimationPlayer, Graphics, OamManaged, Repeat, Tag};
+///
+/// const GRAPHICS: &Graphics = agb::include_aseprite!("examples/gfx/objects.aseprite");
+/// const EMU_WALK: &Tag = GRAPHICS.tags().get("emu-walk");
+///
+/// # fn foo(oam: &OamManaged) {
+/// let mut player = AnimationPlayer::new(EMU_WALK, Repeat::Forever);
+/// let mut emu = oam.object_sprite(player.sprite());
+///
+/// loop {
+///     player.advance(1);
+///     emu.set_sprite(oam.sprite(player.sprite()));
+///     # break;
+/// }
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct AnimationPlayer {
+    tag: &'static Tag,
+    repeat: Repeat,
+    index: usize,
+    time_in_frame: usize,
+    times_played: u32,
+    finished: bool,
+}
+
+impl AnimationPlayer {
+    #[must_use]
+    /// Creates a player starting at the first frame of the tag.
+    pub fn new(tag: &'static Tag, repeat: Repeat) -> Self {
+        Self {
+            tag,
+            repeat,
+            index: 0,
+            time_in_frame: 0,
+            times_played: 0,
+            finished: false,
+        }
+    }
+
+    /// Starts playing a different animation from its first frame. Does nothing
+    /// if the tag and repeat are the same as the ones currently playing, so this
+    /// can be called every frame with the animation you want to show.
+    pub fn play(&mut self, tag: &'static Tag, repeat: Repeat) {
+        if !core::ptr::eq(self.tag, tag) || self.repeat != repeat {
+            *self = Self::new(tag, repeat);
+        }
+    }
+
+    /// Restarts the current animation from its first frame.
+    pub fn restart(&mut self) {
+        *self = Self::new(self.tag, self.repeat);
+    }
+
+    /// Moves the animation forward by the given number of frames. Returns
+    /// `true` if the animation finished during this call, which can only
+    /// happen if it doesn't repeat forever.
+    pub fn advance(&mut self, frames: usize) -> bool {
+        if self.finished {
+            return false;
+        }
+
+        self.time_in_frame += frames;
+
+        loop {
+            let duration = self.sprite().duration() as usize;
+            if self.time_in_frame < duration {
+                return false;
+            }
+
+            self.time_in_frame -= duration;
+
+            if self.index + 1 < self.tag.animation_length() {
+                self.index += 1;
+                continue;
+            }
+
+            self.times_played = self.times_played.saturating_add(1);
+
+            match self.repeat {
+                Repeat::Times(times) if self.times_played >= times.max(1) => {
+                    self.time_in_frame = 0;
+                    self.finished = true;
+                    return true;
+                }
+                _ => self.index = 0,
+            }
+        }
+    }
+
+    #[must_use]
+    /// The sprite that should currently be displayed.
+    pub fn sprite(&self) -> &'static Sprite {
+        self.tag.animation_sprite(self.index)
+    }
+
+    #[must_use]
+    /// The tag currently being played.
+    pub fn tag(&self) -> &'static Tag {
+        self.tag
+    }
+
+    #[must_use]
+    /// The index of the current frame in the animation sequence.
+    pub fn frame_index(&self) -> usize {
+        self.index
+    }
+
+    #[must_use]
+    /// Whether the animation has played the number of times it was asked to.
+    /// Animations which repeat forever never finish.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{display::object::Graphics, include_aseprite};
+
+    use super::*;
+
+    const GRAPHICS: &Graphics = include_aseprite!(
+        "../examples/the-purple-night/gfx/objects.aseprite",
+        "../examples/the-purple-night/gfx/boss.aseprite"
+    );
+
+    const BOSS: &Tag = GRAPHICS.tags().get("Boss");
+
+    #[test_case]
+    fn one_shot_animation_finishes(_gba: &mut crate::Gba) {
+        let mut player = AnimationPlayer::new(BOSS, Repeat::Times(2));
+
+        let duration = BOSS.animation_duration();
+
+        assert!(!player.advance(duration * 2 - 1));
+        assert!(!player.is_finished());
+        assert!(player.advance(1));
+        assert!(player.is_finished());
+        assert!(!player.advance(100));
+    }
+
+    #[test_case]
+    fn looping_animation_never_finishes(_gba: &mut crate::Gba) {
+        let mut player = AnimationPlayer::new(BOSS, Repeat::Forever);
+
+        let duration = BOSS.animation_duration();
+
+        assert!(!player.advance(duration * 10));
+        assert!(!player.is_finished());
+        assert_eq!(player.frame_index(), 0);
+    }
+}
@@ -0,0 +1,139 @@
+use agb_fixnum::Num;
+
+use super::sprite::{Sprite, Tag};
+
+/// Looks up the pre-rotated sprites of a [`Tag`] by angle. Rotating sprites
+/// this way keeps the pixel art crisp and doesn't use up any of the limited
+/// affine matrices.
+///
+/// The rotations are generated at build time by passing the `rotations`
+/// setting to [`include_aseprite`](super::include_aseprite). Angles are given
+/// in turns, so `0.25` is a quarter turn, and match the direction of
+/// [`AffineMatrix::from_rotation`](crate::display::affine::AffineMatrix::from_rotation).
+/// The sprites are rotated about their centre and keep the same size, so
+/// anything outside the largest circle which fits in the sprite is clipped.
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// use agb::{
+///     display::object::{Graphics, RotatedTag},
+///     fixnum::{num, Num},
+/// };
+///
+/// const GRAPHICS: &Graphics = agb::include_aseprite!(
+///     "examples/gfx/objects.aseprite" {
+///         rotations: 16,
+///     }
+/// );
+/// const EMU_WALK: RotatedTag = RotatedTag::new(GRAPHICS.tags().get("emu-walk"));
+///
+/// # fn foo() {
+/// let quarter_turn: Num<i32, 8> = num!(0.25);
+/// let sprite = EMU_WALK.animation_sprite(0, quarter_turn);
+/// # }
+/// ```
+#[derive(Clone, Copy)]
+pub struct RotatedTag {
+    tag: &'static Tag,
+}
+
+impl RotatedTag {
+    #[must_use]
+    /// Creates a lookup for the rotations of the tag. A tag without any
+    /// pre-rotated sprites only has the unrotated one.
+    pub const fn new(tag: &'static Tag) -> Self {
+        Self { tag }
+    }
+
+    #[must_use]
+    /// The tag the rotations are taken from.
+    pub const fn tag(&self) -> &'static Tag {
+        self.tag
+    }
+
+    #[must_use]
+    /// The number of rotations of each sprite, including the unrotated one.
+    pub const fn rotations(&self) -> usize {
+        self.tag.rotations()
+    }
+
+    #[must_use]
+    /// The index of the rotation nearest to the given angle, where rotation
+    /// `i` is `i / rotations()` turns.
+    pub fn rotation_index<const N: usize>(&self, angle: Num<i32, N>) -> usize {
+        let rotations = self.rotations();
+        let half_steps = (angle.rem_euclid(1.into()) * (rotations * 2) as i32).floor() as usize;
+
+        ((half_steps + 1) / 2) % rotations
+    }
+
+    #[must_use]
+    /// The sprite at the given index in the tag, rotated to the nearest
+    /// available angle.
+    pub fn sprite<const N: usize>(&self, idx: usize, angle: Num<i32, N>) -> &'static Sprite {
+        self.tag
+            .rotated(self.tag.sprite(idx), self.rotation_index(angle))
+    }
+
+    #[must_use]
+    /// The sprite that follows the animation sequence, like
+    /// [`Tag::animation_sprite`], rotated to the nearest available angle.
+    pub fn animation_sprite<const N: usize>(
+        &self,
+        idx: usize,
+        angle: Num<i32, N>,
+    ) -> &'static Sprite {
+        self.tag
+            .rotated(self.tag.animation_sprite(idx), self.rotation_index(angle))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use agb_fixnum::num;
+
+    use crate::{display::object::Graphics, include_aseprite};
+
+    use super::*;
+
+    const GRAPHICS: &Graphics = include_aseprite!(
+        "../examples/the-purple-night/gfx/boss.aseprite" {
+            rotations: 8,
+        }
+    );
+
+    const BOSS: RotatedTag = RotatedTag::new(GRAPHICS.tags().get("Boss"));
+
+    #[test_case]
+    fn picks_nearest_rotation(_gba: &mut crate::Gba) {
+        assert_eq!(BOSS.rotations(), 8);
+
+        assert_eq!(BOSS.rotation_index::<8>(num!(0.)), 0);
+        assert_eq!(BOSS.rotation_index::<8>(num!(0.05)), 0);
+        assert_eq!(BOSS.rotation_index::<8>(num!(0.1)), 1);
+        assert_eq!(BOSS.rotation_index::<8>(num!(0.5)), 4);
+        assert_eq!(BOSS.rotation_index::<8>(num!(0.95)), 0);
+        assert_eq!(BOSS.rotation_index::<8>(num!(-0.125)), 7);
+    }
+
+    #[test_case]
+    fn rotated_sprites_follow_the_unrotated_frames(_gba: &mut crate::Gba) {
+        let sprites = GRAPHICS.sprites();
+        let frames = sprites.len() / 8;
+
+        let first = sprites
+            .iter()
+            .position(|sprite| core::ptr::eq(sprite, BOSS.tag().sprite(0)))
+            .unwrap();
+
+        assert!(core::ptr::eq(
+            BOSS.sprite::<8>(0, num!(0.)),
+            BOSS.tag().sprite(0)
+        ));
+        assert!(core::ptr::eq(
+            BOSS.sprite::<8>(0, num!(0.5)),
+            &sprites[first + frames * 4]
+        ));
+    }
+}
@@ -0,0 +1,69 @@
+use agb_fixnum::{Rect, Vector2D};
+
+/// A named rectangle on a sprite, created in aseprite using slices. These are
+/// useful for storing things like hit boxes and attachment points alongside
+/// the art. Positions are relative to the top left of the sprite.
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// # use agb::{display::object::{Graphics, Slice, Tag}, include_aseprite};
+/// const GRAPHICS: &Graphics = include_aseprite!("examples/gfx/objects.aseprite");
+/// const EMU_WALK: &Tag = GRAPHICS.tags().get("emu-walk");
+///
+/// const EMU_HURTBOX: Option<&Slice> = EMU_WALK.sprite(0).slice("hurtbox");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slice {
+    name: &'static str,
+    bounds: Rect<i32>,
+    pivot: Option<Vector2D<i32>>,
+}
+
+impl Slice {
+    #[doc(hidden)]
+    /// Creates a slice from its position and size. Used internally by
+    /// [include_aseprite][super::include_aseprite] and should generally not be
+    /// used outside it.
+    #[must_use]
+    pub const fn new(name: &'static str, x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self {
+            name,
+            bounds: Rect {
+                position: Vector2D::new(x, y),
+                size: Vector2D::new(width, height),
+            },
+            pivot: None,
+        }
+    }
+
+    #[doc(hidden)]
+    /// Sets the pivot of the slice. Used internally by
+    /// [include_aseprite][super::include_aseprite].
+    #[must_use]
+    pub const fn with_pivot(self, x: i32, y: i32) -> Self {
+        Self {
+            pivot: Some(Vector2D::new(x, y)),
+            ..self
+        }
+    }
+
+    #[must_use]
+    /// The name of the slice as given in aseprite.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    #[must_use]
+    /// The area the slice covers on the sprite.
+    pub const fn bounds(&self) -> Rect<i32> {
+        self.bounds
+    }
+
+    #[must_use]
+    /// The pivot point of the slice if one was set in aseprite. This is given
+    /// relative to the top left of the sprite rather than of the slice.
+    pub const fn pivot(&self) -> Option<Vector2D<i32>> {
+        self.pivot
+    }
+}
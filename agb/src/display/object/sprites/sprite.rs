@@ -2,13 +2,17 @@ use core::{alloc::Layout, slice};
 
 use crate::display::palette16::Palette16;
 
-use super::BYTES_PER_TILE_4BPP;
+use super::{slice::Slice, BYTES_PER_TILE_4BPP};
 
 /// Sprite data. Refers to the palette, pixel data, and the size of the sprite.
 pub struct Sprite {
     pub(crate) palette: &'static Palette16,
     pub(crate) data: &'static [u8],
     pub(crate) size: Size,
+    duration: u16,
+    slices: &'static [Slice],
+    cel_user_data: &'static [(&'static str, &'static str)],
+    palette_variants: &'static [(&'static str, &'static Palette16)],
 }
 
 impl Sprite {
@@ -24,6 +28,60 @@ impl Sprite {
             palette,
             data,
             size,
+            duration: 1,
+            slices: &[],
+            cel_user_data: &[],
+            palette_variants: &[],
+        }
+    }
+
+    #[doc(hidden)]
+    /// Sets how many frames the sprite is shown for when animated, used
+    /// internally by [include_aseprite] to carry over the frame durations set in
+    /// aseprite.
+    #[must_use]
+    pub const fn with_duration(self, duration: u16) -> Self {
+        assert!(
+            duration != 0,
+            "sprites must be shown for at least one frame"
+        );
+
+        Self { duration, ..self }
+    }
+
+    #[doc(hidden)]
+    /// Sets the slices on this sprite, used internally by [include_aseprite] to
+    /// carry over the slices set in aseprite.
+    #[must_use]
+    pub const fn with_slices(self, slices: &'static [Slice]) -> Self {
+        Self { slices, ..self }
+    }
+
+    #[doc(hidden)]
+    /// Sets the user data of the cels making up this sprite, keyed by layer
+    /// name. Used internally by [include_aseprite].
+    #[must_use]
+    pub const fn with_cel_user_data(
+        self,
+        cel_user_data: &'static [(&'static str, &'static str)],
+    ) -> Self {
+        Self {
+            cel_user_data,
+            ..self
+        }
+    }
+
+    #[doc(hidden)]
+    /// Sets the named alternative palettes for this sprite. Used internally by
+    /// [include_aseprite] to attach the `palette_variants` it generates.
+    #[must_use]
+    pub const fn with_palette_variants(
+        self,
+        palette_variants: &'static [(&'static str, &'static Palette16)],
+    ) -> Self {
+        Self {
+            palette_variants,
+            ..self
         }
     }
 
@@ -32,6 +90,82 @@ impl Sprite {
     pub fn size(&self) -> Size {
         self.size
     }
+
+    #[must_use]
+    /// The number of frames this sprite should be shown for when it is part of
+    /// an animation. For sprites from aseprite files this is the frame duration
+    /// set in aseprite, converted to frames at 60 frames per second.
+    pub const fn duration(&self) -> u16 {
+        self.duration
+    }
+
+    #[must_use]
+    /// All the slices which are on this sprite. See [Slice] for details.
+    pub const fn slices(&self) -> &'static [Slice] {
+        self.slices
+    }
+
+    #[must_use]
+    /// Gets the slice with the given name on this sprite, or `None` if there
+    /// isn't one on this frame. This can be called in a constant context so
+    /// that the lookup happens at compile time.
+    pub const fn slice(&self, name: &str) -> Option<&'static Slice> {
+        let mut i = 0;
+        while i < self.slices.len() {
+            if const_byte_compare(self.slices[i].name().as_bytes(), name.as_bytes()) {
+                return Some(&self.slices[i]);
+            }
+
+            i += 1;
+        }
+
+        None
+    }
+
+    #[must_use]
+    /// Gets the user data text set in aseprite on the cel of the given layer
+    /// in this frame, or `None` if there isn't any. This can be called in a
+    /// constant context.
+    pub const fn cel_user_data(&self, layer: &str) -> Option<&'static str> {
+        let mut i = 0;
+        while i < self.cel_user_data.len() {
+            let (layer_name, text) = self.cel_user_data[i];
+            if const_byte_compare(layer_name.as_bytes(), layer.as_bytes()) {
+                return Some(text);
+            }
+
+            i += 1;
+        }
+
+        None
+    }
+
+    #[must_use]
+    /// The palette the sprite was converted with.
+    pub const fn palette(&self) -> &'static Palette16 {
+        self.palette
+    }
+
+    #[must_use]
+    /// Gets the variant of this sprite's palette with the given name, as
+    /// generated by the `palette_variants` setting of [include_aseprite], or
+    /// `None` if there isn't one. Load it with
+    /// [SpriteLoader::get_vram_palette](super::SpriteLoader::get_vram_palette)
+    /// and give it to an object with `set_palette`. This can be called in a
+    /// constant context.
+    pub const fn palette_variant(&self, name: &str) -> Option<&'static Palette16> {
+        let mut i = 0;
+        while i < self.palette_variants.len() {
+            let (variant_name, palette) = self.palette_variants[i];
+            if const_byte_compare(variant_name.as_bytes(), name.as_bytes()) {
+                return Some(palette);
+            }
+
+            i += 1;
+        }
+
+        None
+    }
 }
 
 /// The sizes of sprite supported by the GBA.
@@ -77,6 +211,7 @@ macro_rules! align_bytes {
 /// Includes sprites found in the referenced aseprite files. Can include
 /// multiple at once and optimises palettes of all included in the single call
 /// together. See [Size] for supported sizes. Returns a reference to [Graphics].
+/// Frame durations and slices set in aseprite are available on each [Sprite].
 ///
 /// ```rust,no_run
 /// # #![no_std]
@@ -91,15 +226,112 @@ macro_rules! align_bytes {
 /// name in code. You should ensure tags are unique as this is not enforced by
 /// aseprite.
 ///
+/// Each file can be followed by a block of settings. By default, all visible
+/// layers are flattened together. You can choose which layers to include with
+/// `layers` or leave some out with `exclude_layers`. The same file can be
+/// included more than once with different layers, in which case `tag_prefix`
+/// keeps the tag names unique. Chosen layers keep their opacity, but must use
+/// the normal blend mode.
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// # use agb::{display::object::{Graphics, Tag}, include_aseprite};
+/// const GRAPHICS: &Graphics = include_aseprite!(
+///     "examples/gfx/layers.aseprite" {
+///         layers: ["body"],
+///     },
+///     "examples/gfx/layers.aseprite" {
+///         exclude_layers: ["body"],
+///         tag_prefix: "sword-",
+///     }
+/// );
+///
+/// const ATTACK: &Tag = GRAPHICS.tags().get("attack");
+/// const SWORD_ATTACK: &Tag = GRAPHICS.tags().get("sword-attack");
+/// ```
+///
+/// User data set on tags and cels in aseprite is available through
+/// [Tag::user_data] and [Sprite::cel_user_data].
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// # use agb::{display::object::{Graphics, Tag}, include_aseprite};
+/// # const GRAPHICS: &Graphics = include_aseprite!("examples/gfx/layers.aseprite");
+/// const ATTACK: &Tag = GRAPHICS.tags().get("attack");
+///
+/// // the attack tag's user data is "damage=2"
+/// const DAMAGE: Option<&str> = ATTACK.user_data();
+/// // the body layer's cel on each frame has the user data "hitbox"
+/// const HITBOX: Option<&str> = ATTACK.sprite(0).cel_user_data("body");
+/// ```
+///
+/// The `rotations` setting generates that many evenly spaced rotations of
+/// every frame at build time, which can be looked up by angle with
+/// [RotatedTag](super::RotatedTag). Slices are rotated along with the frames,
+/// becoming the smallest rectangle which covers the rotated slice.
+///
+/// The `palette_variants` setting generates recoloured versions of the
+/// palettes used by the file, for things like enemy recolours and damage
+/// flashes. Each variant is one of:
+///
+/// * `remap("file.png")`: an image 2 pixels tall, where each column replaces
+///   the colour in the top row with the colour below it.
+/// * `hue_shift(degrees)`: rotates the hue of every colour.
+/// * `tint("rrggbb")`: multiplies every colour by the tint.
+/// * `flash`: turns every colour white.
+///
+/// The variants are looked up by name with [Sprite::palette_variant], and can
+/// be swapped in on an object at runtime with `set_palette`.
+///
+/// The `quantise` setting reduces frames with too many colours to fit in the
+/// sprite palettes, using either `median_cut` or `kmeans` optionally followed
+/// by `ordered` or `floyd_steinberg` dithering, such as
+/// `quantise: median_cut ordered`. See
+/// [include_background_gfx](crate::include_background_gfx) for details.
+///
+/// The palettes can be pinned by a palette image given before the files, as in
+/// `include_aseprite!(palettes: "gfx/sprite-palettes.png", "gfx/player.aseprite")`.
+/// Each row of the image is one palette with the transparent colour first, and
+/// each sprite uses the first palette which has all of its colours. If no
+/// palette does, the build fails with an error listing the sprite's colours.
+/// Every call using the same palette image generates the same palettes with
+/// the colours in the same places, so palettes can be swapped between sprites
+/// from different calls.
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// # use agb::{display::object::{Graphics, Tag}, include_aseprite};
+/// const GRAPHICS: &Graphics = include_aseprite!(
+///     "examples/gfx/boss.aseprite" {
+///         palette_variants: {
+///             "hurt": flash,
+///             "poisoned": tint("80ff80"),
+///             "shiny": hue_shift(120),
+///         },
+///     }
+/// );
+///
+/// const BOSS: &Tag = GRAPHICS.tags().get("Boss");
+///
+/// # fn foo(gba: &mut agb::Gba) {
+/// let (_, mut loader) = gba.display.object.get_unmanaged();
+///
+/// let mut boss = agb::display::object::ObjectUnmanaged::new(loader.get_vram_sprite(BOSS.sprite(0)));
+/// boss.set_palette(loader.get_vram_palette(BOSS.sprite(0).palette_variant("hurt").unwrap()));
+/// # }
+/// ```
 #[macro_export]
 macro_rules! include_aseprite {
-    ($($aseprite_path: expr),*) => {{
+    ($($aseprite_path: tt)*) => {{
         #[allow(unused_imports)]
-        use $crate::display::object::{Size, Sprite, Tag, TagMap, Graphics};
-        use $crate::display::palette16::Palette16;
+        use $crate::display::object::{Size, Slice, Sprite, Tag, TagMap, Graphics};
+        use $crate::display::{palette16::Palette16, Rgb15};
         use $crate::align_bytes;
 
-        $crate::include_aseprite_inner!($($aseprite_path),*);
+        $crate::include_aseprite_inner!($($aseprite_path)*);
 
         &Graphics::new(SPRITES, TAGS)
     }};
@@ -107,7 +339,53 @@ macro_rules! include_aseprite {
 
 pub use include_aseprite;
 
-/// Stores sprite and tag data returned by [include_aseprite].
+/// Includes sprites from a PNG or BMP sprite sheet laid out in a grid. This
+/// generates the same [Graphics] as [include_aseprite], so the sprites and tags
+/// can be used in exactly the same way.
+///
+/// After the file name, you give the width and height of each frame, which
+/// must be a valid [Size]. Frames are read left to right and then top to
+/// bottom, starting from zero. The following optional settings are supported:
+///
+/// * `margin`: the number of pixels around the edge of the sheet to skip.
+/// * `spacing`: the number of pixels between each frame.
+/// * `transparent_colour`: a hex colour such as `"ff00ff"` which should be
+///   treated as transparent, for images without an alpha channel.
+/// * `tags`: named ranges of frames, optionally followed by one of `forward`,
+///   `reverse` or `pingpong`.
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// # use agb::{display::object::{Graphics, Tag}, include_spritesheet};
+/// const WATER: &Graphics = include_spritesheet!(
+///     "examples/water_tiles.png", 8, 8,
+///     tags: {
+///         "ripple": 0..4,
+///         "splash": 4..=7 pingpong,
+///     }
+/// );
+///
+/// const RIPPLE: &Tag = WATER.tags().get("ripple");
+/// ```
+#[macro_export]
+macro_rules! include_spritesheet {
+    ($($spritesheet: tt)*) => {{
+        #[allow(unused_imports)]
+        use $crate::display::object::{Size, Slice, Sprite, Tag, TagMap, Graphics};
+        use $crate::display::{palette16::Palette16, Rgb15};
+        use $crate::align_bytes;
+
+        $crate::include_spritesheet_inner!($($spritesheet)*);
+
+        &Graphics::new(SPRITES, TAGS)
+    }};
+}
+
+pub use include_spritesheet;
+
+/// Stores sprite and tag data returned by [include_aseprite] and
+/// [include_spritesheet].
 pub struct Graphics {
     sprites: &'static [Sprite],
     tag_map: &'static TagMap,
@@ -249,6 +527,9 @@ pub struct Tag {
     sprites: *const Sprite,
     len: usize,
     direction: Direction,
+    user_data: Option<&'static str>,
+    rotations: usize,
+    rotation_stride: usize,
 }
 
 impl Tag {
@@ -278,6 +559,10 @@ impl Tag {
     #[inline]
     #[must_use]
     pub fn animation_sprite(&self, idx: usize) -> &'static Sprite {
+        if self.len == 1 {
+            return self.sprite(0);
+        }
+
         let len_sub_1 = self.len - 1;
         match self.direction {
             Direction::Forward => self.sprite(idx % self.len),
@@ -289,6 +574,25 @@ impl Tag {
         }
     }
 
+    /// The number of frames in one full cycle of the animation. For ping pong
+    /// animations, this includes the frames going back to the start.
+    #[must_use]
+    pub const fn animation_length(&self) -> usize {
+        match self.direction {
+            Direction::PingPong if self.len > 1 => self.len * 2 - 2,
+            _ => self.len,
+        }
+    }
+
+    /// The number of frames it takes to play the animation through once,
+    /// following the durations of each of the sprites.
+    #[must_use]
+    pub fn animation_duration(&self) -> usize {
+        (0..self.animation_length())
+            .map(|idx| self.animation_sprite(idx).duration() as usize)
+            .sum()
+    }
+
     #[doc(hidden)]
     /// Creates a new sprite from it's constituent parts. Used internally by
     /// [include_aseprite] and should generally not be used elsewhere.
@@ -300,8 +604,59 @@ impl Tag {
             sprites: &sprites[from] as *const Sprite,
             len: to - from + 1,
             direction: Direction::from_usize(direction),
+            user_data: None,
+            rotations: 1,
+            rotation_stride: 0,
+        }
+    }
+
+    #[doc(hidden)]
+    /// Sets the user data text of the tag. Used internally by
+    /// [include_aseprite].
+    #[must_use]
+    pub const fn with_user_data(self, user_data: &'static str) -> Self {
+        Self {
+            user_data: Some(user_data),
+            ..self
         }
     }
+
+    /// The user data text set on the tag in aseprite, if there is any.
+    #[must_use]
+    pub const fn user_data(&self) -> Option<&'static str> {
+        self.user_data
+    }
+
+    #[doc(hidden)]
+    /// Marks the tag as having pre-rotated frames, where each rotation of a
+    /// sprite is `stride` sprites after the previous one. Used internally by
+    /// [include_aseprite].
+    #[must_use]
+    pub const fn with_rotations(self, rotations: usize, stride: usize) -> Self {
+        assert!(rotations > 0);
+        Self {
+            rotations,
+            rotation_stride: stride,
+            ..self
+        }
+    }
+
+    /// The number of pre-rotated versions of each sprite in this tag,
+    /// including the unrotated one. This is 1 unless the `rotations` setting
+    /// was given to [include_aseprite]. See [RotatedTag] for using them.
+    ///
+    /// [RotatedTag]: super::RotatedTag
+    #[must_use]
+    pub const fn rotations(&self) -> usize {
+        self.rotations
+    }
+
+    /// Gets the given rotation of a sprite in this tag.
+    pub(super) fn rotated(&self, sprite: &'static Sprite, rotation: usize) -> &'static Sprite {
+        assert!(rotation < self.rotations, "rotation out of range");
+        // SAFETY: the image converter generates every rotation of the sprites in a tag
+        unsafe { &*(sprite as *const Sprite).add(rotation * self.rotation_stride) }
+    }
 }
 
 impl Size {
@@ -376,3 +731,59 @@ impl Size {
         (wh.0 / 8, wh.1 / 8)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::display::Rgb15;
+
+    use super::*;
+
+    const WATER: &Graphics = include_spritesheet!(
+        "examples/water_tiles.png", 8, 8,
+        tags: {
+            "ripple": 0..4,
+            "splash": 4..=7 pingpong,
+        }
+    );
+
+    #[test_case]
+    fn spritesheet_is_cut_into_frames(_gba: &mut crate::Gba) {
+        assert_eq!(WATER.sprites().len(), 8);
+        assert!(WATER
+            .sprites()
+            .iter()
+            .all(|sprite| sprite.size() == Size::S8x8));
+
+        let ripple = WATER.tags().get("ripple");
+        assert_eq!(ripple.animation_length(), 4);
+        assert!(core::ptr::eq(ripple.sprite(3), &WATER.sprites()[3]));
+
+        let splash = WATER.tags().get("splash");
+        assert_eq!(splash.animation_length(), 6);
+        assert!(core::ptr::eq(
+            splash.animation_sprite(4),
+            &WATER.sprites()[6]
+        ));
+    }
+
+    #[test_case]
+    fn palette_variants_are_generated_for_each_sprite(_gba: &mut crate::Gba) {
+        const BOSS: &Graphics = include_aseprite!(
+            "../examples/the-purple-night/gfx/boss.aseprite" {
+                palette_variants: {
+                    "hurt": flash,
+                    "frozen": tint("8080ff"),
+                },
+            }
+        );
+
+        for sprite in BOSS.sprites() {
+            let hurt = sprite.palette_variant("hurt").unwrap();
+            assert_eq!(hurt.colours()[1], Rgb15::WHITE);
+            assert_eq!(hurt.colours()[0], sprite.palette().colours()[0]);
+
+            assert!(sprite.palette_variant("frozen").is_some());
+            assert!(sprite.palette_variant("poisoned").is_none());
+        }
+    }
+}
@@ -7,7 +7,8 @@ use alloc::{
 
 use crate::{
     agb_alloc::{block_allocator::BlockAllocator, bump_allocator::StartEnd, impl_zst_allocator},
-    display::palette16::Palette16,
+    display::{canvas::Canvas, palette16::Palette16},
+    fixnum::Vector2D,
     hash_map::HashMap,
 };
 
@@ -123,6 +124,10 @@ impl PaletteVram {
             }),
         })
     }
+
+    pub(crate) fn location(&self) -> u16 {
+        self.data.location.0 as u16
+    }
 }
 
 #[derive(Debug)]
@@ -194,7 +199,7 @@ impl SpriteVram {
     }
 
     pub(crate) fn palette_location(&self) -> u16 {
-        self.data.palette.data.location.0 as u16
+        self.data.palette.location()
     }
 }
 
@@ -403,3 +408,38 @@ impl DynamicSprite {
         unsafe { SpriteVram::from_location_size(data.cast(), self.size, palette) }
     }
 }
+
+impl Canvas for DynamicSprite {
+    fn size(&self) -> Vector2D<i32> {
+        let (width, height) = self.size.to_width_height();
+        (width as i32, height as i32).into()
+    }
+
+    fn pixel(&self, position: Vector2D<i32>) -> u8 {
+        let size = Canvas::size(self);
+        assert!(
+            (0..size.x).contains(&position.x) && (0..size.y).contains(&position.y),
+            "pixel outside of sprite"
+        );
+
+        let (x, y) = (position.x as usize, position.y as usize);
+        let (sprite_tile_x, _) = self.size.to_tiles_width_height();
+
+        let tile_number = x / 8 + (y / 8) * sprite_tile_x;
+        let half_word =
+            self.data[tile_number * BYTES_PER_TILE_4BPP / 2 + (x % 8) / 4 + (y % 8) * 2];
+
+        ((half_word >> ((x % 4) * 4)) & 0b1111) as u8
+    }
+
+    fn draw_pixel(&mut self, position: Vector2D<i32>, colour: u8) {
+        let size = Canvas::size(self);
+        if (0..size.x).contains(&position.x) && (0..size.y).contains(&position.y) {
+            self.set_pixel(
+                position.x as usize,
+                position.y as usize,
+                usize::from(colour),
+            );
+        }
+    }
+}
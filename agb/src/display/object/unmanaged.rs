@@ -1,5 +1,7 @@
 mod attributes;
+mod hblank;
 mod object;
 
 pub use attributes::AffineMode;
+pub use hblank::HBlankMultiplexer;
 pub use object::{OamIterator, OamSlot, OamUnmanaged, ObjectUnmanaged};
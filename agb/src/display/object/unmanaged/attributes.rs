@@ -43,11 +43,7 @@ pub enum AffineMode {
 
 impl Attributes {
     pub fn write(self, ptr: *mut u16) {
-        let mode = self.a0.object_mode();
-        let attrs = match mode {
-            ObjectMode::Normal => [self.a0.into(), self.a1s.into(), self.a2.into()],
-            _ => [self.a0.into(), self.a1a.into(), self.a2.into()],
-        };
+        let attrs = self.bits();
 
         unsafe {
             ptr.add(0).write_volatile(attrs[0]);
@@ -56,10 +52,25 @@ impl Attributes {
         }
     }
 
+    pub fn bits(self) -> [u16; 3] {
+        let mode = self.a0.object_mode();
+        match mode {
+            ObjectMode::Normal => [self.a0.into(), self.a1s.into(), self.a2.into()],
+            _ => [self.a0.into(), self.a1a.into(), self.a2.into()],
+        }
+    }
+
     pub fn is_visible(self) -> bool {
         self.a0.object_mode() != ObjectMode::Disabled
     }
 
+    pub fn is_affine(self) -> bool {
+        matches!(
+            self.a0.object_mode(),
+            ObjectMode::Affine | ObjectMode::AffineDouble
+        )
+    }
+
     pub fn show(&mut self) -> &mut Self {
         self.a0.set_object_mode(ObjectMode::Normal);
 
@@ -106,6 +117,14 @@ impl Attributes {
         self
     }
 
+    pub fn y(self) -> u16 {
+        self.a0.y() as u16
+    }
+
+    pub fn is_affine_double(self) -> bool {
+        self.a0.object_mode() == ObjectMode::AffineDouble
+    }
+
     pub fn set_y(&mut self, y: u16) -> &mut Self {
         self.a0.set_y(y as u8);
 
@@ -0,0 +1,301 @@
+use core::{cell::RefCell, pin::Pin};
+
+use alloc::{boxed::Box, vec, vec::Vec};
+use bare_metal::{CriticalSection, Mutex};
+
+use crate::{
+    display::{object::OBJECT_ATTRIBUTE_MEMORY, DISPLAY_CONTROL, VCOUNT},
+    interrupt::{add_interrupt_handler, free, Interrupt, InterruptHandler},
+};
+
+use super::object::{OamUnmanaged, ObjectUnmanaged, NUMBER_OF_SLOTS};
+
+/// The number of lines before a split that the objects for the region below
+/// it are written. This gives the rewrite time to complete before the object
+/// renderer reaches the split.
+const SPLIT_LEAD_LINES: i32 = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct SlotWrite {
+    slot: u8,
+    attributes: [u16; 3],
+}
+
+impl SlotWrite {
+    fn write(self) {
+        let ptr = unsafe { OBJECT_ATTRIBUTE_MEMORY.add(self.slot as usize * 4) };
+        unsafe {
+            ptr.add(0).write_volatile(self.attributes[0]);
+            ptr.add(1).write_volatile(self.attributes[1]);
+            ptr.add(2).write_volatile(self.attributes[2]);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Region {
+    trigger_line: u16,
+    writes: Vec<SlotWrite>,
+}
+
+#[derive(Default)]
+struct MultiplexState {
+    regions: Vec<Region>,
+    next_region: usize,
+}
+
+impl MultiplexState {
+    fn hblank(&mut self, line: u16) {
+        while let Some(region) = self.regions.get(self.next_region) {
+            if region.trigger_line > line {
+                return;
+            }
+
+            for write in region.writes.iter() {
+                write.write();
+            }
+
+            self.next_region += 1;
+        }
+    }
+}
+
+/// Displays more than 128 objects by rewriting OAM during the horizontal blank
+/// as the screen is drawn. The screen is split into regions at the lines given
+/// on creation, and slots used by objects that have finished being drawn are
+/// reused for objects lower down the screen.
+///
+/// Objects are written to the region containing their top row. A slot can only
+/// be reused if the object in it finished a few lines before the split, so
+/// objects which cross a split keep their slot for longer. Any objects which
+/// still can't be given a slot are culled.
+///
+/// Enabling this sets the *hblank interval free* bit of the display control,
+/// which reduces the number of object pixels that can be drawn on each line.
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// use agb::display::object::{HBlankMultiplexer, OamUnmanaged, ObjectUnmanaged};
+///
+/// fn draw(oam: &mut OamUnmanaged, multiplexer: &mut HBlankMultiplexer, objects: &[ObjectUnmanaged]) {
+///     let culled = multiplexer.commit(oam, objects);
+///     if culled != 0 {
+///         agb::println!("culled {} objects", culled);
+///     }
+/// }
+/// ```
+pub struct HBlankMultiplexer {
+    split_lines: Vec<u16>,
+    state: Pin<Box<Mutex<RefCell<MultiplexState>>>>,
+    next_regions: Vec<Region>,
+    _interrupt_handler: InterruptHandler,
+}
+
+impl HBlankMultiplexer {
+    #[must_use]
+    /// Creates a multiplexer which splits the screen into regions at the given
+    /// lines. The lines must be in increasing order and on screen.
+    pub fn new(split_lines: &[u16]) -> Self {
+        assert!(
+            split_lines.windows(2).all(|lines| lines[0] < lines[1]),
+            "split lines must be in increasing order"
+        );
+        assert!(
+            split_lines
+                .iter()
+                .all(|&line| (SPLIT_LEAD_LINES..crate::display::HEIGHT).contains(&i32::from(line))),
+            "split lines must be on screen and not too close to the top"
+        );
+
+        let state = Box::pin(Mutex::new(RefCell::new(MultiplexState::default())));
+
+        let state_for_interrupt_handler: &Mutex<RefCell<MultiplexState>> = &state;
+
+        // SAFETY: dropping the lifetime, sound because the interrupt handler is dropped before the state is.
+        //         In the case of the multiplexer being forgotten, both stay alive so okay
+        let state_for_interrupt_handler: &'static Mutex<RefCell<MultiplexState>> =
+            unsafe { core::mem::transmute(state_for_interrupt_handler) };
+
+        // SAFETY: the handler doesn't allocate
+        let interrupt_handler = unsafe {
+            add_interrupt_handler(Interrupt::HBlank, |cs: CriticalSection| {
+                let line = VCOUNT.get();
+                if i32::from(line) < crate::display::HEIGHT {
+                    state_for_interrupt_handler
+                        .borrow(cs)
+                        .borrow_mut()
+                        .hblank(line);
+                }
+            })
+        };
+
+        DISPLAY_CONTROL.set_bits(1, 1, 0x5);
+
+        Self {
+            split_lines: split_lines.to_vec(),
+            state,
+            next_regions: Vec::new(),
+            _interrupt_handler: interrupt_handler,
+        }
+    }
+
+    /// Writes the objects to OAM, where earlier objects are drawn on top of
+    /// later ones. Must be called during vblank. Returns the number of objects
+    /// which couldn't be given a slot and were culled, which is useful for
+    /// profiling.
+    pub fn commit(&mut self, oam: &mut OamUnmanaged, objects: &[ObjectUnmanaged]) -> usize {
+        let assignment = assign_slots(
+            objects.iter().map(ObjectUnmanaged::vertical_extent),
+            &self.split_lines,
+        );
+
+        let frame_data = oam.start_frame();
+
+        let mut top_region = [None; NUMBER_OF_SLOTS];
+
+        self.next_regions
+            .resize_with(self.split_lines.len(), Region::default);
+        for (region, &split_line) in self.next_regions.iter_mut().zip(&self.split_lines) {
+            region.trigger_line = split_line - SPLIT_LEAD_LINES as u16;
+            region.writes.clear();
+        }
+
+        for (object, placement) in objects.iter().zip(&assignment.placements) {
+            let Some(placement) = placement else {
+                continue;
+            };
+
+            let attributes = frame_data.prepare_object(object).bits();
+            if placement.region == 0 {
+                top_region[placement.slot as usize] = Some(attributes);
+            } else {
+                self.next_regions[placement.region - 1]
+                    .writes
+                    .push(SlotWrite {
+                        slot: placement.slot,
+                        attributes,
+                    });
+            }
+        }
+
+        free(|cs| {
+            let mut state = self.state.borrow(cs).borrow_mut();
+            core::mem::swap(&mut state.regions, &mut self.next_regions);
+            state.next_region = 0;
+        });
+
+        for (slot, attributes) in top_region.into_iter().enumerate() {
+            let write = SlotWrite {
+                slot: slot as u8,
+                attributes: attributes.unwrap_or([0b10 << 8, 0, 0]),
+            };
+            write.write();
+        }
+
+        frame_data.mark_all_slots_written();
+
+        assignment.culled
+    }
+}
+
+impl Drop for HBlankMultiplexer {
+    fn drop(&mut self) {
+        DISPLAY_CONTROL.set_bits(0, 1, 0x5);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Placement {
+    region: usize,
+    slot: u8,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct SlotAssignment {
+    placements: Vec<Option<Placement>>,
+    culled: usize,
+}
+
+/// Finds the region an object starts in and the region from which its slot
+/// can be reused.
+fn regions_for(extent: (i32, i32), split_lines: &[u16]) -> (usize, usize) {
+    let (top, bottom) = extent;
+
+    let start_region = split_lines
+        .iter()
+        .take_while(|&&line| i32::from(line) <= top)
+        .count();
+    let free_from_region = split_lines
+        .iter()
+        .take_while(|&&line| i32::from(line) - SPLIT_LEAD_LINES < bottom)
+        .count()
+        + 1;
+
+    (start_region, free_from_region.max(start_region + 1))
+}
+
+fn assign_slots(extents: impl Iterator<Item = (i32, i32)>, split_lines: &[u16]) -> SlotAssignment {
+    let extents: Vec<_> = extents
+        .map(|extent| regions_for(extent, split_lines))
+        .collect();
+
+    let mut placements = vec![None; extents.len()];
+    let mut slot_free_from = [0; NUMBER_OF_SLOTS];
+    let mut culled = 0;
+
+    for region in 0..=split_lines.len() {
+        for (placement, &(start_region, free_from_region)) in placements.iter_mut().zip(&extents) {
+            if start_region != region {
+                continue;
+            }
+
+            match slot_free_from
+                .iter()
+                .position(|&free_from| free_from <= region)
+            {
+                Some(slot) => {
+                    slot_free_from[slot] = free_from_region;
+                    *placement = Some(Placement {
+                        region,
+                        slot: slot as u8,
+                    });
+                }
+                None => culled += 1,
+            }
+        }
+    }
+
+    SlotAssignment { placements, culled }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn objects_in_lower_region_reuse_slots(_gba: &mut crate::Gba) {
+        let extents = (0..256).map(|i| if i < 128 { (0, 16) } else { (100, 116) });
+
+        let assignment = assign_slots(extents, &[80]);
+
+        assert_eq!(assignment.culled, 0);
+        assert_eq!(
+            assignment.placements[200],
+            Some(Placement {
+                region: 1,
+                slot: 72
+            })
+        );
+    }
+
+    #[test_case]
+    fn objects_crossing_a_split_keep_their_slot(_gba: &mut crate::Gba) {
+        let extents = (0..256).map(|i| if i < 128 { (70, 86) } else { (100, 116) });
+
+        let assignment = assign_slots(extents, &[80]);
+
+        assert_eq!(assignment.culled, 128);
+        assert_eq!(assignment.placements[200], None);
+    }
+}
@@ -4,29 +4,119 @@ use agb_fixnum::Vector2D;
 use alloc::vec::Vec;
 
 use crate::display::{
+    affine::AffineMatrixObject,
     object::{
-        affine::AffineMatrixVram, sprites::SpriteVram, AffineMatrixInstance,
-        OBJECT_ATTRIBUTE_MEMORY,
+        affine::AffineMatrixVram,
+        sprites::{PaletteVram, SpriteVram},
+        AffineMatrixInstance, AffineMatrixOverflow, AffineMatrixUsage, OBJECT_ATTRIBUTE_MEMORY,
     },
-    Priority,
+    Priority, HEIGHT,
 };
 
 use super::attributes::{AffineMode, Attributes};
 
+/// The number of object slots available in OAM.
+pub(super) const NUMBER_OF_SLOTS: usize = 128;
+
+/// The number of affine matrices available in OAM.
+const NUMBER_OF_AFFINE_MATRICES: usize = 32;
+
+/// The location given to matrices which didn't fit when the objects using them
+/// should be hidden.
+const HIDDEN_AFFINE_MATRIX: u32 = u32::MAX - 1;
+
 #[derive(Debug)]
-struct OamFrameModifyables {
+pub(super) struct OamFrameModifyables {
     this_frame_sprites: Vec<SpriteVram>,
+    this_frame_palettes: Vec<PaletteVram>,
     frame: u32,
-    affine_matrix_count: u32,
+    affine_matrices: Vec<AffineMatrixObject>,
+    affine_matrix_overflow: AffineMatrixOverflow,
+    affine_matrix_usage: AffineMatrixUsage,
     previous_index: usize,
 }
 
+impl OamFrameModifyables {
+    /// Gets the attributes to write for this object this frame. This assigns
+    /// the affine matrix a location (writing it to OAM) and keeps the sprite
+    /// and palette alive until the end of the next frame.
+    pub(super) fn prepare_object(&mut self, object: &ObjectUnmanaged) -> Attributes {
+        let mut attributes = object.attributes;
+
+        if let Some(affine_matrix) = &object.affine_matrix {
+            if attributes.is_affine() {
+                self.handle_affine(&mut attributes, affine_matrix);
+            }
+        }
+
+        self.this_frame_sprites.push(object.sprite.clone());
+        if let Some(palette) = &object.palette {
+            self.this_frame_palettes.push(palette.clone());
+        }
+
+        attributes
+    }
+
+    fn handle_affine(&mut self, attributes: &mut Attributes, affine_matrix: &AffineMatrixVram) {
+        if affine_matrix.frame_count() != self.frame {
+            affine_matrix.set_frame_count(self.frame);
+            affine_matrix.set_location(self.affine_matrix_location(affine_matrix));
+        }
+
+        if affine_matrix.location() == HIDDEN_AFFINE_MATRIX {
+            attributes.hide();
+        } else {
+            attributes.set_affine_matrix(affine_matrix.location() as u16);
+        }
+    }
+
+    /// Finds the location for a matrix not yet used this frame, sharing the
+    /// location of an identical matrix if there is one.
+    fn affine_matrix_location(&mut self, affine_matrix: &AffineMatrixVram) -> u32 {
+        let matrix = affine_matrix.matrix();
+
+        if let Some(location) = self.affine_matrices.iter().position(|&m| m == matrix) {
+            self.affine_matrix_usage.deduplicated += 1;
+            return location as u32;
+        }
+
+        if self.affine_matrices.len() < NUMBER_OF_AFFINE_MATRICES {
+            let location = self.affine_matrices.len() as u32;
+            self.affine_matrices.push(matrix);
+            self.affine_matrix_usage.matrices += 1;
+
+            affine_matrix.set_location(location);
+            affine_matrix.write_to_location(OBJECT_ATTRIBUTE_MEMORY);
+
+            return location;
+        }
+
+        self.affine_matrix_usage.overflowed += 1;
+
+        match self.affine_matrix_overflow {
+            AffineMatrixOverflow::Panic => panic!("too many affine matricies in one frame"),
+            AffineMatrixOverflow::ReuseNearest => {
+                nearest_affine_matrix(&self.affine_matrices, matrix) as u32
+            }
+            AffineMatrixOverflow::Hide => HIDDEN_AFFINE_MATRIX,
+        }
+    }
+
+    /// Marks every slot as having been written this frame, so that the next
+    /// frame hides any slots it doesn't use.
+    pub(super) fn mark_all_slots_written(&mut self) {
+        self.previous_index = NUMBER_OF_SLOTS;
+    }
+}
+
 /// This handles the unmanaged oam system which gives more control to the OAM slots.
 /// This is utilised by calling the iter function and writing objects to those slots.
 pub struct OamUnmanaged<'gba> {
     phantom: PhantomData<&'gba ()>,
     frame_data: UnsafeCell<OamFrameModifyables>,
     previous_frame_sprites: Vec<SpriteVram>,
+    previous_frame_palettes: Vec<PaletteVram>,
+    flicker_offset: usize,
 }
 
 /// The iterator over the OAM slots. Dropping this will finalise the frame. To
@@ -102,35 +192,11 @@ impl OamSlot<'_> {
     /// compiler doesn't have to copy around the slot structure while still
     /// keeping move semantics. This is slightly faster in benchmarks.
     fn set_inner(&self, object: &ObjectUnmanaged) {
-        let mut attributes = object.attributes;
         // SAFETY: This function is not reentrant and we currently hold a mutable borrow of the [UnmanagedOAM].
         let frame_data = unsafe { &mut *self.frame_data.get() };
 
-        if let Some(affine_matrix) = &object.affine_matrix {
-            Self::handle_affine(&mut attributes, frame_data, affine_matrix);
-        }
+        let attributes = frame_data.prepare_object(object);
         attributes.write(unsafe { OBJECT_ATTRIBUTE_MEMORY.add(self.slot * 4) });
-
-        frame_data.this_frame_sprites.push(object.sprite.clone());
-    }
-
-    fn handle_affine(
-        attributes: &mut Attributes,
-        frame_data: &mut OamFrameModifyables,
-        affine_matrix: &AffineMatrixVram,
-    ) {
-        if affine_matrix.frame_count() != frame_data.frame {
-            affine_matrix.set_frame_count(frame_data.frame);
-            assert!(
-                frame_data.affine_matrix_count <= 32,
-                "too many affine matricies in one frame"
-            );
-            affine_matrix.set_location(frame_data.affine_matrix_count);
-            frame_data.affine_matrix_count += 1;
-            affine_matrix.write_to_location(OBJECT_ATTRIBUTE_MEMORY);
-        }
-
-        attributes.set_affine_matrix(affine_matrix.location() as u16);
     }
 }
 
@@ -140,7 +206,7 @@ impl<'oam> Iterator for OamIterator<'oam> {
     #[inline(always)]
     fn next(&mut self) -> Option<Self::Item> {
         let idx = self.index;
-        if idx == 128 {
+        if idx == NUMBER_OF_SLOTS {
             None
         } else {
             self.index += 1;
@@ -170,9 +236,66 @@ impl Drop for OamIterator<'_> {
 impl OamUnmanaged<'_> {
     /// Returns the OamSlot iterator for this frame.
     pub fn iter(&mut self) -> OamIterator<'_> {
+        self.start_frame();
+
+        OamIterator {
+            index: 0,
+            frame_data: &self.frame_data,
+        }
+    }
+
+    /// Writes the objects given to OAM. If there are more objects than there
+    /// are slots in OAM, a different set of objects is dropped each frame so
+    /// that the resulting flicker is spread evenly between all of them rather
+    /// than the last objects never being shown.
+    ///
+    /// Returns the number of objects that were culled this frame, which is
+    /// useful for profiling.
+    pub fn commit_with_flicker(&mut self, objects: &[ObjectUnmanaged]) -> usize {
+        let number_of_objects = objects.len();
+        let culled = number_of_objects.saturating_sub(NUMBER_OF_SLOTS);
+
+        let offset = if culled != 0 {
+            self.flicker_offset % number_of_objects
+        } else {
+            0
+        };
+
+        let (before, after) = objects.split_at(offset);
+        for (object, slot) in after.iter().chain(before).zip(self.iter()) {
+            slot.set(object);
+        }
+
+        self.flicker_offset = if culled != 0 {
+            (offset + NUMBER_OF_SLOTS) % number_of_objects
+        } else {
+            0
+        };
+
+        culled
+    }
+
+    /// Sets what happens when the objects in a frame use more than 32
+    /// different affine matrices.
+    pub fn set_affine_matrix_overflow(&mut self, overflow: AffineMatrixOverflow) {
+        self.frame_data.get_mut().affine_matrix_overflow = overflow;
+    }
+
+    #[must_use]
+    /// How the affine matrices were used by the objects written so far this
+    /// frame, or in the previous frame once it has been written.
+    pub fn affine_matrix_usage(&self) -> AffineMatrixUsage {
+        // SAFETY: the frame data is only mutably borrowed by the iterator and
+        // slots, which hold a mutable borrow of self.
+        unsafe { (*self.frame_data.get()).affine_matrix_usage }
+    }
+
+    /// Advances the frame, returning the frame data ready to be written to.
+    pub(super) fn start_frame(&mut self) -> &mut OamFrameModifyables {
         let frame_data = self.frame_data.get_mut();
         frame_data.frame = frame_data.frame.wrapping_add(1);
-        frame_data.affine_matrix_count = 0;
+        frame_data.affine_matrices.clear();
+        frame_data.affine_matrix_usage = AffineMatrixUsage::default();
 
         // We drain the previous frame sprites here to reuse the Vecs allocation and remove the now unused sprites.
         // Any sprites currently being shown will now be put in the new Vec.
@@ -181,33 +304,60 @@ impl OamUnmanaged<'_> {
             &mut frame_data.this_frame_sprites,
             &mut self.previous_frame_sprites,
         );
+        self.previous_frame_palettes.clear();
+        core::mem::swap(
+            &mut frame_data.this_frame_palettes,
+            &mut self.previous_frame_palettes,
+        );
 
-        OamIterator {
-            index: 0,
-            frame_data: &self.frame_data,
-        }
+        frame_data
     }
 
     pub(crate) fn new() -> Self {
         Self {
             frame_data: UnsafeCell::new(OamFrameModifyables {
                 this_frame_sprites: Vec::new(),
+                this_frame_palettes: Vec::new(),
                 frame: 0,
-                affine_matrix_count: 0,
+                affine_matrices: Vec::with_capacity(NUMBER_OF_AFFINE_MATRICES),
+                affine_matrix_overflow: AffineMatrixOverflow::default(),
+                affine_matrix_usage: AffineMatrixUsage::default(),
                 previous_index: 0,
             }),
             phantom: PhantomData,
             previous_frame_sprites: Default::default(),
+            previous_frame_palettes: Default::default(),
+            flicker_offset: 0,
         }
     }
 }
 
+/// Finds the index of the matrix closest to the target, comparing each of the
+/// components.
+fn nearest_affine_matrix(matrices: &[AffineMatrixObject], target: AffineMatrixObject) -> usize {
+    let target = target.components();
+
+    matrices
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, matrix)| {
+            matrix
+                .components()
+                .iter()
+                .zip(target)
+                .map(|(&a, b)| (i32::from(a as i16) - i32::from(b as i16)).unsigned_abs())
+                .sum::<u32>()
+        })
+        .map_or(0, |(index, _)| index)
+}
+
 #[derive(Debug, Clone)]
 /// An object to be used by the [`OamUnmanaged`] system. Changes made here are
 /// reflected when set to an OamSlot using [`OamSlot::set`].
 pub struct ObjectUnmanaged {
     attributes: Attributes,
     sprite: SpriteVram,
+    palette: Option<PaletteVram>,
     affine_matrix: Option<AffineMatrixVram>,
 }
 
@@ -222,6 +372,7 @@ impl ObjectUnmanaged {
         let mut sprite = Self {
             attributes: Attributes::default(),
             sprite,
+            palette: None,
             affine_matrix: None,
         };
 
@@ -238,6 +389,23 @@ impl ObjectUnmanaged {
         self.attributes.is_visible()
     }
 
+    /// The rows of the screen this object covers, from the top (inclusive) to
+    /// the bottom (exclusive). Objects which wrap around from the bottom of the
+    /// screen are given a negative top.
+    pub(super) fn vertical_extent(&self) -> (i32, i32) {
+        let mut top = self.attributes.y() as i32;
+        if top >= HEIGHT {
+            top -= 256;
+        }
+
+        let mut height = self.sprite.size().to_width_height().1 as i32;
+        if self.attributes.is_affine_double() {
+            height *= 2;
+        }
+
+        (top, top + height)
+    }
+
     /// Display the sprite in Normal mode.
     pub fn show(&mut self) -> &mut Self {
         self.attributes.show();
@@ -322,12 +490,16 @@ impl ObjectUnmanaged {
         let (shape, size) = size.shape_size();
 
         self.attributes.set_sprite(sprite.location(), shape, size);
-        self.attributes.set_palette(sprite.palette_location());
+        if self.palette.is_none() {
+            self.attributes.set_palette(sprite.palette_location());
+        }
 
         self
     }
 
-    /// Sets the current sprite for the object.
+    /// Sets the current sprite for the object. If a palette has been set with
+    /// [`set_palette`][ObjectUnmanaged::set_palette] it keeps being used
+    /// instead of the sprite's own palette.
     pub fn set_sprite(&mut self, sprite: SpriteVram) -> &mut Self {
         self.set_sprite_attributes(&sprite);
 
@@ -335,12 +507,40 @@ impl ObjectUnmanaged {
 
         self
     }
+
+    /// Draws the object using the given palette rather than the sprite's own
+    /// one, such as a recoloured variant of it for a damage flash. This stays
+    /// in place when the sprite changes until
+    /// [`reset_palette`][ObjectUnmanaged::reset_palette] is called.
+    pub fn set_palette(&mut self, palette: PaletteVram) -> &mut Self {
+        self.attributes.set_palette(palette.location());
+        self.palette = Some(palette);
+
+        self
+    }
+
+    /// Goes back to drawing the object using the sprite's own palette.
+    pub fn reset_palette(&mut self) -> &mut Self {
+        self.palette = None;
+        self.attributes.set_palette(self.sprite.palette_location());
+
+        self
+    }
+
+    #[cfg(test)]
+    /// The attributes as they would be written to OAM.
+    pub(crate) fn attribute_bits(&self) -> [u16; 3] {
+        self.attributes.bits()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        display::object::{Graphics, Tag},
+        display::{
+            affine::AffineMatrix,
+            object::{Graphics, Tag},
+        },
         include_aseprite,
     };
 
@@ -371,4 +571,95 @@ mod tests {
             slot_a.set(&obj);
         }
     }
+
+    #[test_case]
+    fn flicker_culls_different_objects_each_frame(gba: &mut crate::Gba) {
+        const GRAPHICS: &Graphics = include_aseprite!(
+            "../examples/the-purple-night/gfx/objects.aseprite",
+            "../examples/the-purple-night/gfx/boss.aseprite"
+        );
+
+        const BOSS: &Tag = GRAPHICS.tags().get("Boss");
+
+        let (mut gfx, mut loader) = gba.display.object.get_unmanaged();
+
+        let mut obj = ObjectUnmanaged::new(loader.get_vram_sprite(BOSS.sprite(2)));
+        obj.show();
+
+        let objects = alloc::vec![obj; 200];
+
+        assert_eq!(gfx.commit_with_flicker(&objects), 72);
+        assert_eq!(gfx.flicker_offset, 128);
+        assert_eq!(gfx.commit_with_flicker(&objects), 72);
+        assert_eq!(gfx.flicker_offset, 56);
+
+        assert_eq!(gfx.commit_with_flicker(&objects[..100]), 0);
+        assert_eq!(gfx.flicker_offset, 0);
+    }
+
+    #[test_case]
+    fn identical_affine_matrices_are_shared(gba: &mut crate::Gba) {
+        const GRAPHICS: &Graphics = include_aseprite!(
+            "../examples/the-purple-night/gfx/objects.aseprite",
+            "../examples/the-purple-night/gfx/boss.aseprite"
+        );
+
+        const BOSS: &Tag = GRAPHICS.tags().get("Boss");
+
+        let (mut gfx, mut loader) = gba.display.object.get_unmanaged();
+        gfx.set_affine_matrix_overflow(AffineMatrixOverflow::Hide);
+
+        let sprite = loader.get_vram_sprite(BOSS.sprite(2));
+
+        let objects: Vec<_> = (0..40)
+            .map(|i| {
+                let angle: agb_fixnum::Num<i32, 8> = agb_fixnum::Num::from_raw(i % 36 * 4);
+                let matrix = AffineMatrix::from_rotation(angle).to_object_wrapping();
+
+                let mut obj = ObjectUnmanaged::new(sprite.clone());
+                obj.set_affine_matrix(AffineMatrixInstance::new(matrix))
+                    .show_affine(AffineMode::Affine);
+                obj
+            })
+            .collect();
+
+        gfx.commit_with_flicker(&objects);
+
+        assert_eq!(
+            gfx.affine_matrix_usage(),
+            AffineMatrixUsage {
+                matrices: 32,
+                deduplicated: 4,
+                overflowed: 4,
+            }
+        );
+    }
+
+    #[test_case]
+    fn palette_override_survives_sprite_changes(gba: &mut crate::Gba) {
+        const GRAPHICS: &Graphics = include_aseprite!(
+            "../examples/the-purple-night/gfx/boss.aseprite" {
+                palette_variants: { "hurt": flash },
+            }
+        );
+
+        const BOSS: &Tag = GRAPHICS.tags().get("Boss");
+
+        let (_gfx, mut loader) = gba.display.object.get_unmanaged();
+
+        let palette_bank = |obj: &ObjectUnmanaged| obj.attributes.bits()[2] >> 12;
+
+        let sprite = loader.get_vram_sprite(BOSS.sprite(0));
+        let hurt = loader.get_vram_palette(BOSS.sprite(0).palette_variant("hurt").unwrap());
+
+        let mut obj = ObjectUnmanaged::new(sprite.clone());
+        assert_eq!(palette_bank(&obj), sprite.palette_location());
+
+        obj.set_palette(hurt.clone());
+        obj.set_sprite(loader.get_vram_sprite(BOSS.sprite(1)));
+        assert_eq!(palette_bank(&obj), hurt.location());
+
+        obj.reset_palette();
+        assert_eq!(palette_bank(&obj), sprite.palette_location());
+    }
 }
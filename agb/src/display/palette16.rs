@@ -1,5 +1,7 @@
 use core::alloc::Layout;
 
+use super::Rgb15;
+
 #[repr(C)]
 #[derive(Clone)]
 pub struct Palette16 {
@@ -8,19 +10,35 @@ pub struct Palette16 {
 
 impl Palette16 {
     #[must_use]
-    pub const fn new(colours: [u16; 16]) -> Self {
-        Palette16 { colours }
+    /// Creates a palette from the given colours. Colour 0 is transparent when
+    /// used by objects or backgrounds other than the lowest one.
+    pub const fn new(colours: [Rgb15; 16]) -> Self {
+        let mut raw = [0; 16];
+
+        let mut i = 0;
+        while i < 16 {
+            raw[i] = colours[i].to_raw();
+            i += 1;
+        }
+
+        Palette16 { colours: raw }
     }
 
     // Clippy bug: claims that index is only used in recursion. I can't reproduce in
     // other examples, even just copy pasting this struct and impl into a blank project :/
-    pub fn update_colour(&mut self, index: usize, colour: u16) {
-        self.colours[index] = colour;
+    pub fn update_colour(&mut self, index: usize, colour: Rgb15) {
+        self.colours[index] = colour.to_raw();
+    }
+
+    #[must_use]
+    pub fn colour(&self, index: usize) -> Rgb15 {
+        Rgb15::new(self.colours[index])
     }
 
     #[must_use]
-    pub fn colour(&self, index: usize) -> u16 {
-        self.colours[index]
+    /// All of the colours in the palette.
+    pub fn colours(&self) -> [Rgb15; 16] {
+        self.colours.map(Rgb15::new)
     }
 
     pub(crate) const fn layout() -> Layout {
@@ -28,11 +46,14 @@ impl Palette16 {
     }
 }
 
+/// Includes the colours of every pixel of an image as an array of
+/// [Rgb15](super::Rgb15), reading left to right and then top to bottom.
 #[macro_export]
 macro_rules! include_palette {
-    ($palette:literal) => {
+    ($palette:literal) => {{
+        use $crate::display::Rgb15;
         $crate::include_colours_inner!($palette)
-    };
+    }};
 }
 
 pub use include_palette;
@@ -1,5 +1,6 @@
 mod infinite_scrolled_map;
 mod map;
+mod tile_canvas;
 mod tiled0;
 mod tiled1;
 mod tiled2;
@@ -11,6 +12,7 @@ use agb_fixnum::Vector2D;
 use core::cell::RefCell;
 pub use infinite_scrolled_map::{InfiniteScrolledMap, PartialUpdateStatus};
 pub use map::{AffineMap, MapLoan, RegularMap, TiledMap};
+pub use tile_canvas::TileCanvas;
 pub use tiled0::Tiled0;
 pub use tiled1::Tiled1;
 pub use tiled2::Tiled2;
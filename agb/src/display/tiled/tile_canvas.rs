@@ -0,0 +1,154 @@
+use alloc::vec::Vec;
+
+use crate::{display::canvas::Canvas, fixnum::Vector2D};
+
+use super::{DynamicTile, RegularMap, VRamManager};
+
+/// A canvas made up of a grid of [`DynamicTile`]s, for drawing things which
+/// are bigger than a single tile such as minimaps or health bars on a
+/// background. Draw to it using the [`Canvas`] methods, then [`commit`] it to
+/// a background to show it.
+///
+/// The tiles stay allocated until the canvas is removed with [`remove`].
+///
+/// [`commit`]: TileCanvas::commit
+/// [`remove`]: TileCanvas::remove
+pub struct TileCanvas<'a> {
+    tiles: Vec<DynamicTile<'a>>,
+    size_in_tiles: Vector2D<u16>,
+}
+
+impl<'a> TileCanvas<'a> {
+    /// Allocates the tiles for a canvas of the given size in tiles, filled
+    /// with the background colour.
+    #[must_use]
+    pub fn new(
+        vram_manager: &mut VRamManager,
+        size_in_tiles: Vector2D<u16>,
+        background_colour: u8,
+    ) -> Self {
+        let tile_count = usize::from(size_in_tiles.x) * usize::from(size_in_tiles.y);
+
+        let tiles = (0..tile_count)
+            .map(|_| vram_manager.new_dynamic_tile().fill_with(background_colour))
+            .collect();
+
+        Self {
+            tiles,
+            size_in_tiles,
+        }
+    }
+
+    /// The size of the canvas in tiles.
+    #[must_use]
+    pub fn size_in_tiles(&self) -> Vector2D<u16> {
+        self.size_in_tiles
+    }
+
+    /// Fills the whole canvas with the colour.
+    pub fn clear(&mut self, colour: u8) {
+        assert!(colour < 16);
+
+        let colour = u32::from(colour);
+        let value = (0..8).fold(0, |value, i| value | colour << (i * 4));
+
+        for tile in self.tiles.iter_mut() {
+            tile.tile_data.fill(value);
+        }
+    }
+
+    /// Puts the tiles of the canvas on the background with the top left at the
+    /// given tile position. Changes drawn to the canvas after this show up
+    /// without having to commit again.
+    pub fn commit(
+        &self,
+        bg: &mut RegularMap,
+        vram_manager: &mut VRamManager,
+        tile_pos: Vector2D<u16>,
+    ) {
+        for (i, tile) in self.tiles.iter().enumerate() {
+            let x = i as u16 % self.size_in_tiles.x;
+            let y = i as u16 / self.size_in_tiles.x;
+
+            bg.set_tile(
+                vram_manager,
+                (tile_pos.x + x, tile_pos.y + y).into(),
+                &tile.tile_set(),
+                tile.tile_setting(),
+            );
+        }
+    }
+
+    /// Frees the tiles used by the canvas.
+    pub fn remove(self, vram_manager: &mut VRamManager) {
+        for tile in self.tiles {
+            vram_manager.remove_dynamic_tile(tile);
+        }
+    }
+
+    fn tile_at(&self, position: Vector2D<i32>) -> Option<(usize, Vector2D<i32>)> {
+        let size = self.size();
+        if !(0..size.x).contains(&position.x) || !(0..size.y).contains(&position.y) {
+            return None;
+        }
+
+        let tile = (position.x / 8 + (position.y / 8) * i32::from(self.size_in_tiles.x)) as usize;
+        Some((tile, (position.x % 8, position.y % 8).into()))
+    }
+}
+
+impl Canvas for TileCanvas<'_> {
+    fn size(&self) -> Vector2D<i32> {
+        (
+            i32::from(self.size_in_tiles.x) * 8,
+            i32::from(self.size_in_tiles.y) * 8,
+        )
+            .into()
+    }
+
+    fn pixel(&self, position: Vector2D<i32>) -> u8 {
+        let (tile, position) = self.tile_at(position).expect("pixel outside of canvas");
+        self.tiles[tile].pixel(position)
+    }
+
+    fn draw_pixel(&mut self, position: Vector2D<i32>, colour: u8) {
+        if let Some((tile, position)) = self.tile_at(position) {
+            self.tiles[tile].draw_pixel(position, colour);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        display::{tiled::TileFormat, Priority},
+        fixnum::Rect,
+    };
+
+    use super::*;
+
+    #[test_case]
+    fn drawing_spans_tiles(gba: &mut crate::Gba) {
+        let (gfx, mut vram) = gba.display.video.tiled0();
+
+        let mut bg = gfx.background(
+            Priority::P0,
+            crate::display::tiled::RegularBackgroundSize::Background32x32,
+            TileFormat::FourBpp,
+        );
+
+        let mut canvas = TileCanvas::new(&mut vram, Vector2D::new(3, 2), 0);
+        assert_eq!(canvas.size(), Vector2D::new(24, 16));
+
+        canvas.fill_rect(Rect::new((6, 6).into(), (12, 4).into()), 2);
+        canvas.draw_line((0, 15).into(), (23, 15).into(), 3);
+
+        assert_eq!(canvas.pixel((5, 6).into()), 0);
+        assert_eq!(canvas.pixel((6, 6).into()), 2);
+        assert_eq!(canvas.pixel((17, 9).into()), 2);
+        assert_eq!(canvas.pixel((18, 9).into()), 0);
+        assert_eq!(canvas.pixel((23, 15).into()), 3);
+
+        canvas.commit(&mut bg, &mut vram, Vector2D::new(1, 1));
+    }
+}
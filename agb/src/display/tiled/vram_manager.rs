@@ -4,8 +4,9 @@ use alloc::{slice, vec::Vec};
 
 use crate::{
     agb_alloc::{block_allocator::BlockAllocator, bump_allocator::StartEnd},
-    display::palette16,
+    display::{canvas::Canvas, palette16, Rgb15},
     dma::dma_copy16,
+    fixnum::Vector2D,
     hash_map::{Entry, HashMap},
     memory_mapped::MemoryMapped1DArray,
 };
@@ -57,6 +58,10 @@ impl<'a> TileSet<'a> {
         self.format
     }
 
+    pub(crate) const fn tiles(&self) -> &'a [u8] {
+        self.tiles
+    }
+
     fn reference(&self) -> NonNull<[u8]> {
         self.tiles.into()
     }
@@ -174,6 +179,32 @@ impl DynamicTile<'_> {
     }
 }
 
+impl Canvas for DynamicTile<'_> {
+    fn size(&self) -> Vector2D<i32> {
+        (8, 8).into()
+    }
+
+    fn pixel(&self, position: Vector2D<i32>) -> u8 {
+        assert!(
+            (0..8).contains(&position.x) && (0..8).contains(&position.y),
+            "pixel outside of tile"
+        );
+
+        let row = self.tile_data[position.y as usize];
+        ((row >> (position.x * 4)) & 0b1111) as u8
+    }
+
+    fn draw_pixel(&mut self, position: Vector2D<i32>, colour: u8) {
+        assert!(colour < 16);
+
+        if (0..8).contains(&position.x) && (0..8).contains(&position.y) {
+            let row = &mut self.tile_data[position.y as usize];
+            let shift = position.x * 4;
+            *row = (*row & !(0b1111 << shift)) | (u32::from(colour) << shift);
+        }
+    }
+}
+
 impl DynamicTile<'_> {
     #[must_use]
     pub fn tile_set(&self) -> TileSet<'_> {
@@ -410,13 +441,28 @@ impl VRamManager {
         }
     }
 
-    /// Copies raw palettes to the background palette without any checks.
-    pub fn set_background_palette_raw(&mut self, palette: &[u16]) {
+    /// Copies colours to the background palette without any checks.
+    pub fn set_background_palette_raw(&mut self, palette: &[Rgb15]) {
         unsafe {
-            dma_copy16(palette.as_ptr(), PALETTE_BACKGROUND.as_ptr(), palette.len());
+            dma_copy16(
+                palette.as_ptr().cast(),
+                PALETTE_BACKGROUND.as_ptr(),
+                palette.len(),
+            );
         }
     }
 
+    /// Sets a single colour in one of the background palettes.
+    pub fn set_background_palette_colour(
+        &mut self,
+        pal_index: u8,
+        colour_index: usize,
+        colour: Rgb15,
+    ) {
+        assert!(colour_index < 16, "colour index out of range");
+        PALETTE_BACKGROUND.set(colour_index + 16 * pal_index as usize, colour.to_raw());
+    }
+
     fn set_background_palette(&mut self, pal_index: u8, palette: &palette16::Palette16) {
         for (colour_index, &colour) in palette.colours.iter().enumerate() {
             PALETTE_BACKGROUND.set(colour_index + 16 * pal_index as usize, colour);
@@ -3,6 +3,10 @@ use crate::fixnum::Vector2D;
 use bitflags::bitflags;
 use core::convert::From;
 
+mod command;
+
+pub use command::{Command, CommandInput, CommandMatcher};
+
 /// Tri-state enum. Allows for -1, 0 and +1.
 /// Useful if checking if the D-Pad is pointing left, right, or unpressed.
 ///
@@ -67,10 +71,20 @@ bitflags! {
 
 const BUTTON_INPUT: *mut u16 = (0x04000130) as *mut u16;
 
+/// The number of buttons in [`Button`].
+const BUTTON_COUNT: usize = 10;
+
 // const BUTTON_INTERRUPT: *mut u16 = (0x04000132) as *mut u16;
 
 /// Helper to make it easy to get the current state of the GBA's buttons.
 ///
+/// As well as which buttons are pressed, it keeps track of how long each
+/// button has been held for and how long ago it was last pressed. This allows
+/// for key repeat in menus with [`is_repeated`](ButtonController::is_repeated),
+/// and for buffering inputs with
+/// [`is_just_pressed_within`](ButtonController::is_just_pressed_within) so
+/// that a jump pressed just before landing still happens.
+///
 /// # Example
 ///
 /// ```rust,no_run
@@ -94,6 +108,12 @@ const BUTTON_INPUT: *mut u16 = (0x04000130) as *mut u16;
 pub struct ButtonController {
     previous: u16,
     current: u16,
+    /// How many frames each button has been pressed for
+    held_frames: [u16; BUTTON_COUNT],
+    /// How many frames ago each button was last just pressed
+    frames_since_pressed: [u16; BUTTON_COUNT],
+    repeat_delay: u16,
+    repeat_rate: u16,
 }
 
 impl Default for ButtonController {
@@ -108,9 +128,17 @@ impl ButtonController {
     #[must_use]
     pub fn new() -> Self {
         let pressed = !unsafe { BUTTON_INPUT.read_volatile() };
+        Self::with_state(pressed)
+    }
+
+    fn with_state(pressed: u16) -> Self {
         ButtonController {
             previous: pressed,
             current: pressed,
+            held_frames: [0; BUTTON_COUNT],
+            frames_since_pressed: [u16::MAX; BUTTON_COUNT],
+            repeat_delay: 20,
+            repeat_rate: 4,
         }
     }
 
@@ -118,8 +146,104 @@ impl ButtonController {
     /// You should call this every frame (either at the start or the end) to ensure that you have the latest state of each button press.
     /// Calls to any method won't change until you call this.
     pub fn update(&mut self) {
+        self.update_with_state(!unsafe { BUTTON_INPUT.read_volatile() });
+    }
+
+    fn update_with_state(&mut self, pressed: u16) {
         self.previous = self.current;
-        self.current = !unsafe { BUTTON_INPUT.read_volatile() };
+        self.current = pressed;
+
+        for button in 0..BUTTON_COUNT {
+            let bit = 1 << button;
+
+            self.held_frames[button] = if self.current & bit != 0 {
+                self.held_frames[button].saturating_add(1)
+            } else {
+                0
+            };
+
+            self.frames_since_pressed[button] = if self.current & !self.previous & bit != 0 {
+                0
+            } else {
+                self.frames_since_pressed[button].saturating_add(1)
+            };
+        }
+    }
+
+    /// The indices of the buttons in `keys`.
+    fn buttons(keys: Button) -> impl Iterator<Item = usize> {
+        (0..BUTTON_COUNT).filter(move |button| keys.bits() & (1 << button) != 0)
+    }
+
+    /// Sets how key repeat works for [`is_repeated`](ButtonController::is_repeated).
+    /// After a button has been held for `delay` frames, it repeats every `rate` frames.
+    /// By default the delay is 20 frames and the rate is 4 frames.
+    pub fn set_key_repeat(&mut self, delay: u16, rate: u16) {
+        assert!(rate > 0, "key repeat rate must be at least 1 frame");
+
+        self.repeat_delay = delay;
+        self.repeat_rate = rate;
+    }
+
+    /// Returns the number of frames the longest held of the buttons in `keys` has been pressed for,
+    /// which is 1 on the frame it is just pressed and 0 if it isn't pressed.
+    #[must_use]
+    pub fn held_frames(&self, keys: Button) -> u16 {
+        Self::buttons(keys)
+            .map(|button| self.held_frames[button])
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns true if any of the buttons in `keys` was just pressed, or has been held long enough to repeat this frame.
+    /// This is useful for moving through menus, where holding a direction should keep moving.
+    /// Change how quickly keys repeat with [`set_key_repeat`](ButtonController::set_key_repeat).
+    #[must_use]
+    pub fn is_repeated(&self, keys: Button) -> bool {
+        Self::buttons(keys).any(|button| {
+            let held = self.held_frames[button];
+
+            held == 1
+                || (held > self.repeat_delay
+                    && (held - self.repeat_delay - 1) % self.repeat_rate == 0)
+        })
+    }
+
+    /// Returns true if any of the buttons in `keys` was just pressed in the last `frames` frames, including this one.
+    /// Checking this rather than [`is_just_pressed`](ButtonController::is_just_pressed) lets actions which can't happen
+    /// straight away, such as jumping just before landing, still happen a few frames later.
+    /// Use [`clear_buffered`](ButtonController::clear_buffered) once the action happens so it only happens once.
+    ///
+    /// # Example
+    /// ```no_run,rust
+    /// # #![no_std]
+    /// use agb::input::{Button, ButtonController};
+    ///
+    /// # fn main() {
+    /// # let on_ground = true;
+    /// let mut button_controller = ButtonController::new();
+    ///
+    /// loop {
+    ///     button_controller.update();
+    ///
+    ///     if on_ground && button_controller.is_just_pressed_within(Button::A, 6) {
+    ///         button_controller.clear_buffered(Button::A);
+    ///         // jump
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn is_just_pressed_within(&self, keys: Button, frames: u16) -> bool {
+        Self::buttons(keys).any(|button| self.frames_since_pressed[button] < frames)
+    }
+
+    /// Forgets the last press of each of the buttons in `keys`, so that
+    /// [`is_just_pressed_within`](ButtonController::is_just_pressed_within) is false until they are pressed again.
+    pub fn clear_buffered(&mut self, keys: Button) {
+        for button in Self::buttons(keys) {
+            self.frames_since_pressed[button] = u16::MAX;
+        }
     }
 
     /// Returns [Tri::Positive] if right is pressed, [Tri::Negative] if left is pressed and [Tri::Zero] if neither or both are pressed.
@@ -186,6 +310,37 @@ impl ButtonController {
             .into()
     }
 
+    #[must_use]
+    /// Returns [Tri::Positive] if right was just pressed or is repeating, [Tri::Negative] if left was and [Tri::Zero] if neither or both are.
+    ///
+    /// See [`is_repeated`](ButtonController::is_repeated) for when a held button repeats.
+    pub fn repeated_x_tri(&self) -> Tri {
+        let left = self.is_repeated(Button::LEFT);
+        let right = self.is_repeated(Button::RIGHT);
+
+        (left, right).into()
+    }
+
+    #[must_use]
+    /// Returns [Tri::Positive] if down was just pressed or is repeating, [Tri::Negative] if up was and [Tri::Zero] if neither or both are.
+    ///
+    /// See [`is_repeated`](ButtonController::is_repeated) for when a held button repeats.
+    pub fn repeated_y_tri(&self) -> Tri {
+        let up = self.is_repeated(Button::UP);
+        let down = self.is_repeated(Button::DOWN);
+
+        (up, down).into()
+    }
+
+    #[must_use]
+    /// Returns a vector which represents the direction which was just pressed or is repeating.
+    pub fn repeated_vector<T>(&self) -> Vector2D<T>
+    where
+        T: From<i32> + crate::fixnum::FixedWidthUnsignedInteger,
+    {
+        (self.repeated_x_tri() as i32, self.repeated_y_tri() as i32).into()
+    }
+
     #[must_use]
     /// Returns `true` if the provided keys are all pressed, and `false` if not.
     pub fn is_pressed(&self, keys: Button) -> bool {
@@ -238,3 +393,55 @@ impl ButtonController {
         ((current & keys) == 0) && ((previous & keys) != 0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pressed(keys: Button) -> u16 {
+        keys.bits() as u16
+    }
+
+    #[test_case]
+    fn held_buttons_repeat(_gba: &mut crate::Gba) {
+        let mut input = ButtonController::with_state(0);
+        input.set_key_repeat(3, 2);
+
+        let repeats: [bool; 8] = core::array::from_fn(|_| {
+            input.update_with_state(pressed(Button::DOWN));
+            input.is_repeated(Button::DOWN)
+        });
+
+        assert_eq!(
+            repeats,
+            [true, false, false, true, false, true, false, true]
+        );
+        assert_eq!(input.held_frames(Button::DOWN), 8);
+        assert!(input.repeated_y_tri() == Tri::Positive);
+
+        input.update_with_state(0);
+        assert_eq!(input.held_frames(Button::DOWN), 0);
+        assert!(!input.is_repeated(Button::DOWN));
+    }
+
+    #[test_case]
+    fn presses_are_buffered(_gba: &mut crate::Gba) {
+        let mut input = ButtonController::with_state(0);
+
+        input.update_with_state(pressed(Button::A));
+        input.update_with_state(0);
+        input.update_with_state(0);
+
+        assert!(input.is_just_pressed_within(Button::A, 3));
+        assert!(!input.is_just_pressed_within(Button::A, 2));
+        assert!(!input.is_just_pressed_within(Button::B, 3));
+
+        input.clear_buffered(Button::A);
+        assert!(!input.is_just_pressed_within(Button::A, 3));
+
+        // clearing the buffer doesn't stop the press repeating
+        input.update_with_state(pressed(Button::B));
+        input.clear_buffered(Button::B);
+        assert!(input.is_repeated(Button::B));
+    }
+}
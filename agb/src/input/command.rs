@@ -0,0 +1,433 @@
+use alloc::{collections::VecDeque, vec::Vec};
+
+use super::{Button, ButtonController, Tri};
+
+/// The buttons which can be pressed as part of a command, which are all of
+/// them apart from the D-Pad.
+const COMMAND_BUTTONS: Button = Button::A
+    .union(Button::B)
+    .union(Button::L)
+    .union(Button::R)
+    .union(Button::START)
+    .union(Button::SELECT);
+
+/// One of the inputs in a [`Command`]. Forward and back are relative to the
+/// way the player is facing, which is set with [`CommandMatcher::set_facing`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandInput {
+    /// No direction is held.
+    Neutral,
+    /// Up
+    Up,
+    /// Down
+    Down,
+    /// Towards the way the player is facing
+    Forward,
+    /// Away from the way the player is facing
+    Back,
+    /// Up and forward together
+    UpForward,
+    /// Up and back together
+    UpBack,
+    /// Down and forward together
+    DownForward,
+    /// Down and back together
+    DownBack,
+    /// All of the buttons are pressed. They don't need to be pressed on
+    /// exactly the same frame, only within the leniency of the
+    /// [`CommandMatcher`].
+    Press(Button),
+}
+
+impl CommandInput {
+    /// The direction on the D-Pad for a player facing in the direction, where
+    /// negative x is left and negative y is up.
+    fn direction(self, facing: i32) -> Option<(i32, i32)> {
+        let (forward, up) = match self {
+            CommandInput::Neutral => (0, 0),
+            CommandInput::Up => (0, 1),
+            CommandInput::Down => (0, -1),
+            CommandInput::Forward => (1, 0),
+            CommandInput::Back => (-1, 0),
+            CommandInput::UpForward => (1, 1),
+            CommandInput::UpBack => (-1, 1),
+            CommandInput::DownForward => (1, -1),
+            CommandInput::DownBack => (-1, -1),
+            CommandInput::Press(_) => return None,
+        };
+
+        Some((forward * facing, -up))
+    }
+}
+
+/// A sequence of inputs which does something when the player enters it, such
+/// as a quarter circle forward followed by A. Create these with
+/// [`Command::new`] and add them to a [`CommandMatcher`].
+#[derive(Debug, Clone)]
+pub struct Command<T> {
+    inputs: Vec<CommandInput>,
+    value: T,
+    within: u16,
+    priority: i32,
+}
+
+impl<T> Command<T> {
+    /// Creates a command for the inputs, in the order the player enters them.
+    /// The value is returned by [`CommandMatcher::update`] when the command is
+    /// entered. By default the whole command must be entered within 30 frames
+    /// and it has a priority of 0.
+    #[must_use]
+    pub fn new(inputs: &[CommandInput], value: T) -> Self {
+        assert!(!inputs.is_empty(), "a command needs at least one input");
+
+        Self {
+            inputs: inputs.to_vec(),
+            value,
+            within: 30,
+            priority: 0,
+        }
+    }
+
+    /// Sets how many frames the player has to enter the whole command, from
+    /// the first input to the last.
+    #[must_use]
+    pub fn within(mut self, frames: u16) -> Self {
+        self.within = frames;
+        self
+    }
+
+    /// Sets which command wins when several are entered on the same frame,
+    /// such as a special move which ends in the same button as a normal
+    /// attack. Higher priorities win, and commands with the same priority are
+    /// decided by which has more inputs.
+    #[must_use]
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// Something which happened on a frame, stored in the history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Event {
+    /// The D-Pad changed to this direction.
+    Direction(i32, i32),
+    /// These buttons were just pressed.
+    Press(u32),
+}
+
+/// Detects when the player enters commands, such as the special moves of a
+/// fighting game. Each frame, [`update`](CommandMatcher::update) returns the
+/// value of the command the player just finished entering, if there is one.
+///
+/// Directions in commands are relative to the way the player is facing, so a
+/// command with [`CommandInput::Forward`] is entered by pressing right when
+/// facing right, and left when facing left.
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// use agb::input::{Button, ButtonController, Command, CommandInput::*, CommandMatcher, Tri};
+///
+/// #[derive(Clone, Copy)]
+/// enum Move {
+///     Punch,
+///     Fireball,
+///     Dash,
+/// }
+///
+/// # fn main() {
+/// let mut input = ButtonController::new();
+/// let mut commands = CommandMatcher::new();
+///
+/// commands.add(Command::new(&[Press(Button::A)], Move::Punch));
+/// commands.add(
+///     Command::new(&[Down, DownForward, Forward, Press(Button::A)], Move::Fireball)
+///         .within(20)
+///         .priority(1),
+/// );
+/// commands.add(Command::new(&[Forward, Neutral, Forward], Move::Dash).within(12));
+///
+/// loop {
+///     input.update();
+///
+///     commands.set_facing(Tri::Positive);
+///     match commands.update(&input) {
+///         Some(Move::Fireball) => { /* throw a fireball rather than punching */ }
+///         Some(Move::Punch) => {}
+///         Some(Move::Dash) => {}
+///         None => {}
+///     }
+/// }
+/// # }
+/// ```
+pub struct CommandMatcher<T> {
+    commands: Vec<Command<T>>,
+    /// Events and the frame they happened on, oldest first
+    history: VecDeque<(u32, Event)>,
+    frame: u32,
+    direction: (i32, i32),
+    facing: i32,
+    leniency: u16,
+}
+
+impl<T> Default for CommandMatcher<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> CommandMatcher<T> {
+    /// Creates a matcher with no commands, for a player facing right.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+            history: VecDeque::new(),
+            frame: 0,
+            direction: (0, 0),
+            facing: 1,
+            leniency: 3,
+        }
+    }
+
+    /// Adds a command to look out for.
+    pub fn add(&mut self, command: Command<T>) {
+        self.commands.push(command);
+    }
+
+    /// Sets which way the player is facing, which is [`Tri::Negative`] for
+    /// left and [`Tri::Positive`] for right. [`Tri::Zero`] leaves the facing
+    /// as it is. Commands are matched using the facing at the time they are
+    /// finished.
+    pub fn set_facing(&mut self, facing: Tri) {
+        match facing {
+            Tri::Negative => self.facing = -1,
+            Tri::Positive => self.facing = 1,
+            Tri::Zero => {}
+        }
+    }
+
+    /// Sets how many frames apart buttons in a [`CommandInput::Press`] of
+    /// several buttons can be pressed, which is 3 frames by default.
+    pub fn set_leniency(&mut self, frames: u16) {
+        self.leniency = frames;
+    }
+
+    /// Forgets everything the player has entered so far, such as when they
+    /// are hit and any half entered command should be cancelled.
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+
+    /// Records this frame's input and returns the value of the command the
+    /// player just finished entering. If several commands were finished this
+    /// frame, the one with the highest priority is returned. Call this once
+    /// per frame, after [`ButtonController::update`].
+    pub fn update(&mut self, input: &ButtonController) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.frame = self.frame.wrapping_add(1);
+
+        let mut changed = false;
+
+        let direction = (input.x_tri() as i32, input.y_tri() as i32);
+        if direction != self.direction {
+            self.direction = direction;
+            self.history
+                .push_back((self.frame, Event::Direction(direction.0, direction.1)));
+            changed = true;
+        }
+
+        let pressed = COMMAND_BUTTONS
+            .iter()
+            .filter(|&button| input.is_just_pressed(button))
+            .fold(0, |pressed, button| pressed | button.bits());
+        if pressed != 0 {
+            self.history.push_back((self.frame, Event::Press(pressed)));
+            changed = true;
+        }
+
+        let longest = self
+            .commands
+            .iter()
+            .map(|command| u32::from(command.within))
+            .max()
+            .unwrap_or(0)
+            + u32::from(self.leniency);
+        while let Some(&(frame, _)) = self.history.front() {
+            if self.frame.wrapping_sub(frame) <= longest {
+                break;
+            }
+
+            self.history.pop_front();
+        }
+
+        // commands can only be finished by something happening this frame
+        if !changed {
+            return None;
+        }
+
+        self.commands
+            .iter()
+            .filter(|command| self.is_entered(command))
+            .max_by_key(|command| (command.priority, command.inputs.len()))
+            .map(|command| command.value.clone())
+    }
+
+    /// Whether the command's inputs are in the history in order, with the
+    /// last of them this frame and the first within the command's time limit.
+    /// Other inputs can come between them.
+    fn is_entered(&self, command: &Command<T>) -> bool {
+        let mut index = self.history.len();
+
+        for (i, &input) in command.inputs.iter().rev().enumerate() {
+            loop {
+                let Some(previous) = index.checked_sub(1) else {
+                    return false;
+                };
+                index = previous;
+
+                let age = self.frame.wrapping_sub(self.history[index].0);
+                if age > u32::from(command.within) || (i == 0 && age != 0) {
+                    return false;
+                }
+
+                if self.input_matches(input, index) {
+                    break;
+                }
+            }
+        }
+
+        true
+    }
+
+    fn input_matches(&self, input: CommandInput, index: usize) -> bool {
+        let (frame, event) = self.history[index];
+
+        match (input, event) {
+            (CommandInput::Press(buttons), Event::Press(pressed)) => {
+                let buttons = buttons.bits();
+                if pressed & buttons == 0 {
+                    return false;
+                }
+
+                let pressed_together = self
+                    .history
+                    .range(..=index)
+                    .rev()
+                    .take_while(|&&(other, _)| {
+                        frame.wrapping_sub(other) <= u32::from(self.leniency)
+                    })
+                    .fold(0, |pressed, &(_, event)| match event {
+                        Event::Press(other) => pressed | other,
+                        Event::Direction(..) => pressed,
+                    });
+
+                pressed_together & buttons == buttons
+            }
+            (input, Event::Direction(x, y)) => input.direction(self.facing) == Some((x, y)),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use CommandInput::*;
+
+    fn state(x: i32, y: i32, buttons: Button) -> u16 {
+        let mut pressed = buttons;
+        pressed.set(Button::LEFT, x < 0);
+        pressed.set(Button::RIGHT, x > 0);
+        pressed.set(Button::UP, y < 0);
+        pressed.set(Button::DOWN, y > 0);
+
+        pressed.bits() as u16
+    }
+
+    fn play<T: Clone>(
+        matcher: &mut CommandMatcher<T>,
+        frames: &[(i32, i32, Button)],
+    ) -> Vec<Option<T>> {
+        let mut input = ButtonController::with_state(0);
+
+        frames
+            .iter()
+            .map(|&(x, y, buttons)| {
+                input.update_with_state(state(x, y, buttons));
+                matcher.update(&input)
+            })
+            .collect()
+    }
+
+    #[test_case]
+    fn commands_are_mirrored_by_facing(_gba: &mut crate::Gba) {
+        let mut matcher = CommandMatcher::new();
+        matcher.add(Command::new(&[Down, DownForward, Forward, Press(Button::A)], 1).within(10));
+
+        let fireball_right = [
+            (0, 1, Button::empty()),
+            (1, 1, Button::empty()),
+            (1, 0, Button::empty()),
+            (1, 0, Button::A),
+        ];
+        assert_eq!(
+            play(&mut matcher, &fireball_right),
+            [None, None, None, Some(1)]
+        );
+
+        matcher.clear();
+        matcher.set_facing(Tri::Negative);
+        assert_eq!(play(&mut matcher, &fireball_right)[3], None);
+
+        let fireball_left = [
+            (0, 1, Button::empty()),
+            (-1, 1, Button::empty()),
+            (-1, 0, Button::A),
+        ];
+        assert_eq!(play(&mut matcher, &fireball_left)[2], Some(1));
+    }
+
+    #[test_case]
+    fn commands_must_be_entered_in_time(_gba: &mut crate::Gba) {
+        let mut matcher = CommandMatcher::new();
+        matcher.add(Command::new(&[Forward, Neutral, Forward], ()).within(4));
+
+        let quick = [
+            (1, 0, Button::empty()),
+            (0, 0, Button::empty()),
+            (1, 0, Button::empty()),
+        ];
+        assert_eq!(play(&mut matcher, &quick)[2], Some(()));
+
+        matcher.clear();
+        let slow = [
+            (1, 0, Button::empty()),
+            (0, 0, Button::empty()),
+            (0, 0, Button::empty()),
+            (0, 0, Button::empty()),
+            (0, 0, Button::empty()),
+            (1, 0, Button::empty()),
+        ];
+        assert_eq!(play(&mut matcher, &slow)[5], None);
+    }
+
+    #[test_case]
+    fn higher_priorities_win(_gba: &mut crate::Gba) {
+        let mut matcher = CommandMatcher::new();
+        matcher.add(Command::new(&[Press(Button::A)], "punch"));
+        matcher.add(Command::new(&[Press(Button::A.union(Button::B))], "throw").priority(1));
+
+        let together = [
+            (0, 0, Button::A),
+            (0, 0, Button::A),
+            (0, 0, Button::A.union(Button::B)),
+        ];
+        assert_eq!(
+            play(&mut matcher, &together),
+            [Some("punch"), None, Some("throw")]
+        );
+    }
+}
@@ -57,6 +57,43 @@
 ///
 /// You can import multiple files at once, and the palette data will be combined so they can all be visible.
 ///
+/// Recoloured versions of the palettes can be generated by adding a `palette_variants` block after the
+/// transparent colour. Each variant becomes a constant named after it, with the palettes in the same order as
+/// `PALETTES`. A variant is one of `remap("file.png")`, `hue_shift(degrees)`, `tint("rrggbb")` or `flash`, as
+/// described in [`include_aseprite`][crate::include_aseprite].
+///
+/// ```rust,no_run
+/// ##![no_std]
+/// ##![no_main]
+/// agb::include_background_gfx!(water_tiles, "ff00ff", palette_variants: { night: tint("6060c0") }, tiles => "examples/water_tiles.png");
+///
+/// # fn foo(vram: &mut agb::display::tiled::VRamManager) {
+/// vram.set_background_palettes(water_tiles::NIGHT_PALETTES);
+/// # }
+/// ```
+///
+/// Images with more colours than fit in the palettes, such as photos or painted art, can be reduced automatically
+/// with the `quantise` setting. This takes the method used to pick the colours, either `median_cut` or the slower
+/// but often more accurate `kmeans`, optionally followed by `ordered` or `floyd_steinberg` dithering. If any colours
+/// had to change, a warning listing the ones which changed the most is shown during the build.
+///
+/// ```rust,ignore
+/// agb::include_background_gfx!(title, "ff00ff", quantise: kmeans floyd_steinberg, screen => "gfx/title.png");
+/// ```
+///
+/// By default, the palettes are chosen separately for each call, so they can change between builds and differ
+/// between calls. The `palettes` setting pins them using a palette image instead, where each row of up to 16 colours
+/// is one palette, starting from palette 0. The first colour of each row is the transparent colour. Each tile uses
+/// the first palette which has all of its colours, and 256 colour images can use any colour from any of the
+/// palettes. The build fails with an error naming the tile if it doesn't fit any palette. Every call which uses the
+/// same palette image generates identical `PALETTES`, so backgrounds from several calls can share the same palette
+/// slots, and palette effects know where each colour is. This can be combined with `quantise` to recolour the
+/// images using only the pinned palettes.
+///
+/// ```rust,ignore
+/// agb::include_background_gfx!(level, "ff00ff", palettes: "gfx/palettes.png", tiles => "gfx/level.png");
+/// ```
+///
 /// # Examples
 ///
 /// Assume the tiles are loaded as above
@@ -99,23 +136,183 @@
 /// ```
 pub use agb_image_converter::include_background_gfx;
 
+/// Includes every asset listed in a manifest file, so that large games don't need dozens of macro calls spread
+/// across their source files. The manifest is a TOML file with a table for each kind of asset, and the paths in it
+/// are relative to the manifest. Changing the manifest or any file it lists causes a rebuild.
+///
+/// * `[backgrounds.<name>]` generates the module `backgrounds::<name>`, in the same way as
+///   [`include_background_gfx`]. It takes an `images` table, where each image has a `file`, and optionally
+///   `colours` (16 or 256) and `deduplicate`. The optional `transparent_colour`, `palettes`, `quantise` and
+///   `palette_variants` settings work in the same way as for the macro.
+/// * `[maps.<name>]` generates the module `maps::<name>` for a single deduplicated screen from `file`, with the
+///   tiles and the map of which tile goes where in `maps::<name>::map`. It takes the same optional settings as a
+///   background, apart from `palette_variants`.
+/// * `[sprites.<name>]` generates the static `sprites::<NAME>`, in the same way as
+///   [`include_aseprite`]. `files` lists the aseprite files, either as paths or as tables with the `file` and any of
+///   the per file settings. The optional `palettes` setting pins the palettes.
+/// * `[fonts.<name>]` generates the static `fonts::<NAME>` from the font `file`, with the same `size` and
+///   settings as [`include_font`]. Ranges are written as pairs such as `ranges = [["a", "z"]]`.
+/// * `[sounds.<name>]` generates the static `sounds::<NAME>` from the wav `file`, in the same way as
+///   [`include_wav`].
+///
+/// Settings which are written as code in the macros, such as `quantise` and the palette variants, are strings with
+/// the same contents.
+///
+/// ```toml
+/// [backgrounds.title]
+/// transparent_colour = "ff00ff"
+/// quantise = "kmeans floyd_steinberg"
+/// images.screen = { file = "gfx/title.png", deduplicate = true }
+///
+/// [sprites.player]
+/// palettes = "gfx/sprite-palettes.png"
+/// files = [
+///     "gfx/player.aseprite",
+///     { file = "gfx/sword.aseprite", tag_prefix = "sword-", palette_variants = { hurt = "flash" } },
+/// ]
+///
+/// [fonts.main]
+/// file = "fonts/main.ttf"
+/// size = 12
+///
+/// [sounds.jump]
+/// file = "sfx/jump.wav"
+/// ```
+///
+/// ```rust,ignore
+/// mod assets {
+///     agb::include_assets!("assets/assets.toml");
+/// }
+///
+/// let jump = SoundChannel::new(assets::sounds::JUMP);
+/// let player_idle = assets::sprites::PLAYER.tags().get("idle");
+/// vram.set_background_palettes(assets::backgrounds::title::PALETTES);
+/// ```
+pub use agb_image_converter::include_assets;
+
 #[doc(hidden)]
 pub use agb_image_converter::include_aseprite_inner;
 
+#[doc(hidden)]
+pub use agb_image_converter::include_spritesheet_inner;
+
 #[doc(hidden)]
 pub use agb_image_converter::include_font as include_font_inner;
 
 #[doc(hidden)]
 pub use agb_image_converter::include_colours_inner;
 
+#[doc(hidden)]
+pub use agb_image_converter::include_bitmap_inner;
+
+/// Includes a font as a [`Font`](display::Font). This can be a TTF font
+/// rendered at the given pixel size, or a bitmap font drawn pixel by pixel.
+///
+/// Bitmap fonts don't take a size. They can either be a
+/// [BMFont](https://www.angelcode.com/products/bmfont/) `.fnt` file in the
+/// text format, with its page images next to it, or a PNG or BMP image with the
+/// glyphs drawn in a grid. Glyph grids need these settings:
+///
+/// * `cell_size`: the width and height of each cell of the grid, such as `(8, 8)`.
+/// * `characters`: a string with the character of each cell, left to right and
+///   then top to bottom.
+/// * `baseline` (optional): the row of each cell the glyphs sit on, which is the
+///   bottom of the cell by default.
+/// * `letter_spacing` (optional): the gap after each glyph, which is 1 pixel by
+///   default. Glyphs are trimmed to the pixels drawn in their cell, and empty
+///   cells such as space are half the cell wide.
+/// * `transparent_colour` (optional): a hex colour of the background of the
+///   image. Transparent pixels are always background.
+///
+/// By default the font has glyphs for the ASCII characters, or every glyph of
+/// a bitmap font. For other languages, the following optional settings choose
+/// which characters to include:
+///
+/// * `ranges`: a list of characters or ranges of characters, which replaces
+///   the default characters.
+/// * `characters_from`: a list of text files relative to your crate root. Every
+///   character used in them is included, in addition to the ranges.
+/// * `strings_from`: a list of strings files for [`include_strings`]. Only the
+///   characters used by the strings are included, along with any ranges, so
+///   the font has just the glyphs your game's text needs.
+///
+/// Characters the font doesn't include are drawn with the font's fallback
+/// glyph. For bitmap fonts this is `?`, or character -1 of a BMFont file.
+///
+/// Any kerning pairs in a TTF or BMFont file are included for the glyphs in
+/// the font, and are applied when the text is drawn.
+///
+/// ```rust,no_run
+/// ##![no_std]
+/// ##![no_main]
+/// #
+/// use agb::{display::Font, include_font};
+///
+/// const FONT: Font = include_font!(
+///     "examples/font/yoster.ttf", 12,
+///     ranges: [' '..='~', '\u{c0}'..='\u{ff}'],
+/// );
+///
+/// const PIXEL_FONT: Font = include_font!(
+///     "examples/font/pixel-font.png",
+///     cell_size: (8, 8),
+///     characters: "AB? ",
+///     baseline: 7,
+/// );
+/// ```
 #[macro_export]
 macro_rules! include_font {
-    ($font_path: literal, $font_size: literal) => {{
+    ($($font: tt)*) => {{
         use $crate::display;
-        $crate::include_font_inner!($font_path, $font_size)
+        $crate::include_font_inner!($($font)*)
     }};
 }
 
+/// Includes tables of strings in each language your game supports, keyed by ID. Each language is a TOML file relative
+/// to your crate root, where every value is a string. Tables are flattened into the key, so `start` in the `[menu]`
+/// table is `menu.start`. Every language needs the same keys as the first.
+///
+/// This generates a module with the given name, which contains:
+///
+/// * `Language`, with a variant for each language, such as `Language::En` for `en`. The first language is used
+///   until `set_language` is called.
+/// * `Text`, with a variant for each key, such as `Text::MenuStart` for `menu.start`. `Text::text` returns the
+///   string in the current language, and `Text::get` the string in a given language.
+/// * `set_language` and `language`, which change and return the current language.
+///
+/// `Text` implements [`Display`](core::fmt::Display) in the current language, so it can be written with `write!`
+/// to an [`ObjectTextRender`](display::object::ObjectTextRender), a [`TextRenderer`](display::font::TextRenderer)
+/// writer or a [`TextBox`](display::font::TextBox). To include only the glyphs these strings use, give the same
+/// files to the `strings_from` setting of [`include_font`].
+///
+/// ```toml
+/// # examples/strings/en.toml
+/// greeting = "Hello, World!"
+///
+/// [menu]
+/// start = "Start game"
+/// ```
+///
+/// ```rust,no_run
+/// ##![no_std]
+/// ##![no_main]
+/// #
+/// use agb::{display::Font, include_font, include_strings};
+///
+/// include_strings!(strings, en => "examples/strings/en.toml", fr => "examples/strings/fr.toml");
+///
+/// const FONT: Font = include_font!(
+///     "examples/font/yoster.ttf", 12,
+///     strings_from: ["examples/strings/en.toml", "examples/strings/fr.toml"],
+/// );
+///
+/// # fn example() {
+/// strings::set_language(strings::Language::Fr);
+/// assert_eq!(strings::Text::Greeting.text(), "Bonjour à tous !");
+/// # }
+/// ```
+pub use agb_image_converter::include_strings;
+
 /// This macro declares the entry point to your game written using `agb`.
 ///
 /// It is already included in the template, but your `main` function must be annotated with `#[agb::entry]`, takes 1 argument and never returns.
@@ -168,6 +365,7 @@ pub mod sync;
 pub mod syscall;
 /// Interactions with the internal timers
 pub mod timer;
+pub mod ui;
 
 mod no_game;
 
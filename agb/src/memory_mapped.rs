@@ -96,4 +96,7 @@ impl<T, const X: usize, const Y: usize> MemoryMapped2DArray<T, X, Y> {
     pub fn set(&self, x: usize, y: usize, val: T) {
         unsafe { (&mut (*self.array)[y][x] as *mut T).write_volatile(val) }
     }
+    pub fn as_ptr(&self) -> *mut T {
+        self.array.cast()
+    }
 }
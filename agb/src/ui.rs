@@ -0,0 +1,65 @@
+#![deny(missing_docs)]
+//! Menus and widgets for building title, settings and pause screens.
+//!
+//! A [`Menu`] keeps track of which of its items is selected as the player
+//! moves around it, and returns a [`MenuEvent`] with the value of the item
+//! they picked. [`Slider`] and [`NumberSelector`] let the player change a
+//! number with left and right, returning a [`ValueEvent`]. None of these draw
+//! anything themselves, so they can be shown however your game likes. The
+//! [`MenuRender`] draws a menu's labels with an
+//! [`ObjectTextRender`](crate::display::object::ObjectTextRender) and marks
+//! the selected item with a cursor sprite.
+//!
+//! The widgets implement [`Display`](core::fmt::Display) and the labels of
+//! menu items are strings, so they can also be written to any of the font
+//! renderers, such as a [`TextBox`](crate::display::font::TextBox).
+//!
+//! ```rust,no_run
+//! # #![no_std]
+//! # #![no_main]
+//! # extern crate alloc;
+//! use alloc::format;
+//! use agb::ui::{Menu, MenuEvent, Slider, ValueEvent};
+//! use agb::input::ButtonController;
+//!
+//! #[derive(Clone, Copy)]
+//! enum Pause {
+//!     Resume,
+//!     Volume,
+//!     Quit,
+//! }
+//!
+//! # fn foo() {
+//! let mut input = ButtonController::new();
+//! let mut volume = Slider::new(0, 10, 1, 8);
+//!
+//! let mut menu = Menu::new();
+//! menu.add("Resume", Pause::Resume);
+//! menu.add(format!("Volume {volume}"), Pause::Volume);
+//! menu.add("Quit", Pause::Quit);
+//!
+//! loop {
+//!     input.update();
+//!
+//!     if let Some(Pause::Volume) = menu.selected() {
+//!         if let Some(ValueEvent::Changed(_)) = volume.update(&input) {
+//!             menu.set_label(1, format!("Volume {volume}"));
+//!         }
+//!     }
+//!
+//!     match menu.update(&input) {
+//!         Some(MenuEvent::Selected(Pause::Resume) | MenuEvent::Cancelled) => break,
+//!         Some(MenuEvent::Selected(Pause::Quit)) => { /* back to the title screen */ }
+//!         _ => {}
+//!     }
+//! }
+//! # }
+//! ```
+
+mod menu;
+mod render;
+mod value;
+
+pub use menu::{Menu, MenuEvent, MenuItem, VisibleItem};
+pub use render::MenuRender;
+pub use value::{NumberSelector, Slider, ValueEvent};
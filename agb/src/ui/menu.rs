@@ -0,0 +1,383 @@
+use alloc::{string::String, vec::Vec};
+
+use crate::{
+    fixnum::Vector2D,
+    input::{Button, ButtonController},
+};
+
+/// A menu of items laid out in a column, or in a grid with several columns.
+/// Each item has a label and a value of your choosing, such as an enum of the
+/// options on the menu, which is returned when the player picks the item.
+///
+/// The player moves between items with the D-Pad, skipping over any which are
+/// disabled, and keeps moving while a direction is held using the key repeat
+/// of the [`ButtonController`]. By default moving off one edge of the menu
+/// wraps around to the other. A picks the selected item, and B cancels. If
+/// only some of the rows fit on screen,
+/// [`set_visible_rows`](Menu::set_visible_rows) scrolls the menu to keep the
+/// selected item visible.
+pub struct Menu<T> {
+    items: Vec<MenuItem<T>>,
+    columns: usize,
+    selected: Option<usize>,
+    wrap: bool,
+    visible_rows: Option<usize>,
+    first_visible_row: usize,
+    revision: u32,
+}
+
+/// An item of a [`Menu`].
+pub struct MenuItem<T> {
+    label: String,
+    value: T,
+    enabled: bool,
+}
+
+impl<T> MenuItem<T> {
+    /// The text shown for the item.
+    #[must_use]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// The value returned when the item is picked.
+    #[must_use]
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Whether the item can be selected.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+/// What the player did, returned by [`Menu::update`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuEvent<T> {
+    /// The player moved the cursor onto the item with this value.
+    Moved(T),
+    /// The player picked the item with this value.
+    Selected(T),
+    /// The player pressed B to leave the menu.
+    Cancelled,
+}
+
+/// An item which is on screen, returned by [`Menu::visible_items`].
+pub struct VisibleItem<'a, T> {
+    /// The index of the item in the menu.
+    pub index: usize,
+    /// The row the item is in, counting from the first visible row.
+    pub row: usize,
+    /// The column the item is in.
+    pub column: usize,
+    /// Whether this is the selected item.
+    pub is_selected: bool,
+    /// The item itself.
+    pub item: &'a MenuItem<T>,
+}
+
+impl<T> Default for Menu<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Menu<T> {
+    /// Creates an empty menu where the items are in a single column.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::grid(1)
+    }
+
+    /// Creates an empty menu where the items are laid out left to right in
+    /// rows of the given number of columns.
+    #[must_use]
+    pub fn grid(columns: usize) -> Self {
+        assert!(columns > 0, "a menu needs at least one column");
+
+        Self {
+            items: Vec::new(),
+            columns,
+            selected: None,
+            wrap: true,
+            visible_rows: None,
+            first_visible_row: 0,
+            revision: 0,
+        }
+    }
+
+    /// Adds an item to the end of the menu. If nothing is selected yet, the
+    /// new item is.
+    pub fn add(&mut self, label: impl Into<String>, value: T) -> &mut Self {
+        self.items.push(MenuItem {
+            label: label.into(),
+            value,
+            enabled: true,
+        });
+
+        if self.selected.is_none() {
+            self.select(self.items.len() - 1);
+        }
+
+        self.revision = self.revision.wrapping_add(1);
+        self
+    }
+
+    /// Changes the label of the item at the index.
+    pub fn set_label(&mut self, index: usize, label: impl Into<String>) {
+        self.items[index].label = label.into();
+        self.revision = self.revision.wrapping_add(1);
+    }
+
+    /// Enables or disables the item at the index. Disabled items can't be
+    /// selected, so if the selected item is disabled the cursor moves to the
+    /// next enabled one.
+    pub fn set_enabled(&mut self, index: usize, enabled: bool) {
+        self.items[index].enabled = enabled;
+        self.revision = self.revision.wrapping_add(1);
+
+        match self.selected {
+            Some(selected) if selected == index && !enabled => {
+                self.selected = self.next_enabled(index);
+                self.scroll_to_selected();
+            }
+            None if enabled => self.select(index),
+            _ => {}
+        }
+    }
+
+    /// Sets whether moving off one edge of the menu wraps around to the
+    /// other, which it does by default.
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+    }
+
+    /// Sets how many rows are shown at once, scrolling the menu to keep the
+    /// selected item visible. By default every row is shown.
+    pub fn set_visible_rows(&mut self, rows: usize) {
+        assert!(rows > 0, "a menu needs to show at least one row");
+
+        self.visible_rows = Some(rows);
+        self.scroll_to_selected();
+    }
+
+    /// Moves the cursor to the item at the index, if it is enabled.
+    pub fn select(&mut self, index: usize) {
+        if self.items[index].enabled {
+            self.selected = Some(index);
+            self.scroll_to_selected();
+        }
+    }
+
+    /// The index of the selected item, which is `None` if there are no
+    /// enabled items.
+    #[must_use]
+    pub fn selected_index(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// The value of the selected item.
+    #[must_use]
+    pub fn selected(&self) -> Option<&T> {
+        self.selected.map(|index| &self.items[index].value)
+    }
+
+    /// All the items of the menu.
+    #[must_use]
+    pub fn items(&self) -> &[MenuItem<T>] {
+        &self.items
+    }
+
+    /// The number of columns in each row.
+    #[must_use]
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// The index of the first row which is on screen.
+    #[must_use]
+    pub fn first_visible_row(&self) -> usize {
+        self.first_visible_row
+    }
+
+    /// Changes whenever the items or the scroll position of the menu change,
+    /// so that renderers know when to redraw the labels.
+    pub(crate) fn revision(&self) -> (u32, usize) {
+        (self.revision, self.first_visible_row)
+    }
+
+    /// The items which are on screen, in order.
+    pub fn visible_items(&self) -> impl Iterator<Item = VisibleItem<'_, T>> {
+        let rows = self.visible_rows.unwrap_or(usize::MAX);
+        let first = self.first_visible_row * self.columns;
+
+        self.items
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(rows.saturating_mul(self.columns))
+            .map(move |(index, item)| VisibleItem {
+                index,
+                row: index / self.columns - self.first_visible_row,
+                column: index % self.columns,
+                is_selected: self.selected == Some(index),
+                item,
+            })
+    }
+
+    fn rows(&self) -> usize {
+        self.items.len().div_ceil(self.columns)
+    }
+
+    /// Moves the cursor in the direction the player pressed, and picks or
+    /// cancels with A and B. Call this once per frame.
+    pub fn update(&mut self, input: &ButtonController) -> Option<MenuEvent<T>>
+    where
+        T: Clone,
+    {
+        if input.is_just_pressed(Button::B) {
+            return Some(MenuEvent::Cancelled);
+        }
+
+        let selected = self.selected?;
+
+        if input.is_just_pressed(Button::A) {
+            return Some(MenuEvent::Selected(self.items[selected].value.clone()));
+        }
+
+        let direction: Vector2D<i32> = input.repeated_vector();
+        let direction = if self.columns == 1 {
+            (0, direction.y).into()
+        } else {
+            direction
+        };
+
+        if direction == (0, 0).into() {
+            return None;
+        }
+
+        let next = self.step(selected, direction)?;
+        self.selected = Some(next);
+        self.scroll_to_selected();
+
+        Some(MenuEvent::Moved(self.items[next].value.clone()))
+    }
+
+    /// The next enabled item from the one at the index in the direction,
+    /// wrapping around the menu if it should.
+    fn step(&self, from: usize, direction: Vector2D<i32>) -> Option<usize> {
+        let (rows, columns) = (self.rows() as i32, self.columns as i32);
+        let (mut row, mut column) = ((from / self.columns) as i32, (from % self.columns) as i32);
+
+        for _ in 0..rows * columns {
+            row += direction.y;
+            column += direction.x;
+
+            if self.wrap {
+                row = row.rem_euclid(rows);
+                column = column.rem_euclid(columns);
+            } else if !(0..rows).contains(&row) || !(0..columns).contains(&column) {
+                return None;
+            }
+
+            let index = (row * columns + column) as usize;
+            if index == from {
+                return None;
+            }
+
+            if self.items.get(index).is_some_and(|item| item.enabled) {
+                return Some(index);
+            }
+        }
+
+        None
+    }
+
+    /// The first enabled item after the one at the index, going back to the
+    /// start of the menu after the end.
+    fn next_enabled(&self, from: usize) -> Option<usize> {
+        let count = self.items.len();
+
+        (1..count)
+            .map(|offset| (from + offset) % count)
+            .find(|&index| self.items[index].enabled)
+    }
+
+    fn scroll_to_selected(&mut self) {
+        let (Some(visible_rows), Some(selected)) = (self.visible_rows, self.selected) else {
+            return;
+        };
+
+        let row = selected / self.columns;
+        if row < self.first_visible_row {
+            self.first_visible_row = row;
+        } else if row >= self.first_visible_row + visible_rows {
+            self.first_visible_row = row + 1 - visible_rows;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    fn grid(count: usize, columns: usize) -> Menu<usize> {
+        let mut menu = Menu::grid(columns);
+        for i in 0..count {
+            menu.add(i.to_string(), i);
+        }
+
+        menu
+    }
+
+    #[test_case]
+    fn movement_wraps_and_skips_disabled_items(_gba: &mut crate::Gba) {
+        let mut menu = grid(4, 1);
+        menu.set_enabled(1, false);
+
+        assert_eq!(menu.step(0, (0, 1).into()), Some(2));
+        assert_eq!(menu.step(0, (0, -1).into()), Some(3));
+        assert_eq!(menu.step(3, (0, 1).into()), Some(0));
+
+        menu.set_wrap(false);
+        assert_eq!(menu.step(3, (0, 1).into()), None);
+        assert_eq!(menu.step(2, (0, -1).into()), Some(0));
+
+        menu.set_enabled(0, false);
+        assert_eq!(menu.selected_index(), Some(2));
+    }
+
+    #[test_case]
+    fn grids_move_in_two_directions(_gba: &mut crate::Gba) {
+        // 0 1 2
+        // 3 4
+        let menu = grid(5, 3);
+
+        assert_eq!(menu.step(1, (0, 1).into()), Some(4));
+        assert_eq!(menu.step(2, (0, 1).into()), None);
+        assert_eq!(menu.step(4, (1, 0).into()), Some(3));
+        assert_eq!(menu.step(0, (-1, 0).into()), Some(2));
+    }
+
+    #[test_case]
+    fn scrolling_keeps_the_selection_visible(_gba: &mut crate::Gba) {
+        let mut menu = grid(10, 2);
+        menu.set_visible_rows(2);
+
+        menu.select(7);
+        assert_eq!(menu.first_visible_row(), 2);
+        assert_eq!(
+            menu.visible_items()
+                .map(|item| (item.index, item.row, item.column))
+                .collect::<Vec<_>>(),
+            [(4, 0, 0), (5, 0, 1), (6, 1, 0), (7, 1, 1)]
+        );
+
+        menu.select(1);
+        assert_eq!(menu.first_visible_row(), 0);
+    }
+}
@@ -0,0 +1,238 @@
+use core::fmt::Write;
+
+use alloc::vec::Vec;
+
+use crate::{
+    display::{
+        object::{
+            ChangeColour, OamIterator, ObjectTextRender, ObjectUnmanaged, PaletteVram, Size,
+            SpriteVram, TextAlignment,
+        },
+        Font,
+    },
+    fixnum::Vector2D,
+};
+
+use super::Menu;
+
+/// Draws the visible items of a [`Menu`] with objects, writing each label
+/// with an [`ObjectTextRender`] and showing the cursor sprite to the left of
+/// the selected item.
+///
+/// Each item is given a cell of `item_size` pixels, with the cells of a grid
+/// menu side by side. The cursor takes up the left of the cell, and the label
+/// is laid out in the rest of it.
+///
+/// ```rust,no_run
+/// # #![no_std]
+/// # #![no_main]
+/// use agb::display::{
+///     object::{OamUnmanaged, PaletteVram, Size, SpriteVram},
+///     Font,
+/// };
+/// use agb::input::ButtonController;
+/// use agb::ui::{Menu, MenuRender};
+///
+/// const FONT: Font = agb::include_font!("examples/font/yoster.ttf", 12);
+///
+/// # fn foo(mut oam: OamUnmanaged, palette: PaletteVram, cursor: SpriteVram) {
+/// let vblank = agb::interrupt::VBlank::get();
+/// let mut input = ButtonController::new();
+///
+/// let mut menu = Menu::new();
+/// menu.add("New game", 0).add("Continue", 1).add("Options", 2);
+/// menu.set_enabled(1, false);
+///
+/// let mut render = MenuRender::new(
+///     &FONT,
+///     Size::S16x16,
+///     palette,
+///     cursor,
+///     (80, 60).into(),
+///     (96, 16).into(),
+/// );
+///
+/// loop {
+///     input.update();
+///     menu.update(&input);
+///     render.update(&menu);
+///
+///     vblank.wait_for_vblank();
+///     render.commit(&mut oam.iter());
+/// }
+/// # }
+/// ```
+pub struct MenuRender<'font> {
+    font: &'font Font,
+    sprite_size: Size,
+    palette: PaletteVram,
+    cursor: SpriteVram,
+    position: Vector2D<i32>,
+    item_size: Vector2D<i32>,
+    disabled_colour: Option<ChangeColour>,
+    labels: Vec<Label<'font>>,
+    revision: Option<(u32, usize)>,
+    cursor_position: Option<Vector2D<i32>>,
+}
+
+struct Label<'font> {
+    text: ObjectTextRender<'font>,
+    position: Vector2D<i32>,
+}
+
+impl<'font> MenuRender<'font> {
+    /// Creates a renderer which draws the labels with the font, using sprites
+    /// of the given size and palette. The top left of the first item is at
+    /// `position`, and each item takes up `item_size` pixels.
+    #[must_use]
+    pub fn new(
+        font: &'font Font,
+        sprite_size: Size,
+        palette: PaletteVram,
+        cursor: SpriteVram,
+        position: Vector2D<i32>,
+        item_size: Vector2D<i32>,
+    ) -> Self {
+        Self {
+            font,
+            sprite_size,
+            palette,
+            cursor,
+            position,
+            item_size,
+            disabled_colour: None,
+            labels: Vec::new(),
+            revision: None,
+            cursor_position: None,
+        }
+    }
+
+    /// Draws the labels of disabled items in another colour of the palette,
+    /// in the same way as [`ChangeColour`].
+    pub fn set_disabled_colour(&mut self, colour: ChangeColour) {
+        self.disabled_colour = Some(colour);
+        self.revision = None;
+    }
+
+    /// Moves the menu so that the top left of the first visible item is at
+    /// the position.
+    pub fn set_position(&mut self, position: Vector2D<i32>) {
+        self.position = position;
+        self.revision = None;
+    }
+
+    /// Lays out any labels which have changed and moves the cursor to the
+    /// selected item. Call this once per frame, after
+    /// [`Menu::update`](super::Menu::update) and before
+    /// [`commit`](MenuRender::commit).
+    pub fn update<T>(&mut self, menu: &Menu<T>) {
+        if self.revision != Some(menu.revision()) {
+            self.revision = Some(menu.revision());
+            self.lay_out(menu);
+        }
+
+        for label in &mut self.labels {
+            label.text.update(label.position);
+        }
+
+        let (_, cursor_height) = self.cursor.size().to_width_height();
+        self.cursor_position = menu
+            .visible_items()
+            .find(|item| item.is_selected)
+            .map(|item| {
+                self.cell_position(item.row, item.column)
+                    + (0, (self.font.line_height() - cursor_height as i32) / 2).into()
+            });
+    }
+
+    fn cell_position(&self, row: usize, column: usize) -> Vector2D<i32> {
+        self.position
+            + (
+                column as i32 * self.item_size.x,
+                row as i32 * self.item_size.y,
+            )
+                .into()
+    }
+
+    fn lay_out<T>(&mut self, menu: &Menu<T>) {
+        let (cursor_width, _) = self.cursor.size().to_width_height();
+        let space_width = i32::from(self.font.letter(' ').advance_width);
+        let indent = cursor_width as i32 + space_width;
+        let area = self.item_size - (indent, 0).into();
+
+        self.labels = menu
+            .visible_items()
+            .map(|item| {
+                let mut text =
+                    ObjectTextRender::new(self.font, self.sprite_size, self.palette.clone());
+
+                match self.disabled_colour {
+                    Some(colour) if !item.item.is_enabled() => {
+                        let _ = write!(text, "{colour}");
+                    }
+                    _ => {}
+                }
+
+                // the last word is only laid out once something follows it
+                let _ = writeln!(text, "{}", item.item.label());
+                text.layout(area, TextAlignment::Left, 0);
+                while text.next_letter_group() {}
+
+                Label {
+                    text,
+                    position: self.cell_position(item.row, item.column) + (indent, 0).into(),
+                }
+            })
+            .collect();
+    }
+
+    /// Shows the labels and cursor using the slots of the iterator.
+    pub fn commit(&mut self, oam: &mut OamIterator) {
+        for label in &mut self.labels {
+            label.text.commit(oam);
+        }
+
+        if let (Some(position), Some(slot)) = (self.cursor_position, oam.next()) {
+            let mut object = ObjectUnmanaged::new(self.cursor.clone());
+            object.show().set_position(position);
+            slot.set(&object);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::{object::DynamicSprite, palette16::Palette16, Rgb15};
+
+    const FONT: Font = crate::include_font!("examples/font/pixel-font.fnt");
+
+    #[test_case]
+    fn labels_and_cursor_follow_the_menu(_gba: &mut crate::Gba) {
+        let palette = PaletteVram::new(&Palette16::new([Rgb15::WHITE; 16])).unwrap();
+        let cursor = DynamicSprite::new(Size::S8x8).to_vram(palette.clone());
+
+        let mut menu = Menu::grid(2);
+        menu.add("A", 'a').add("B", 'b').add("AB", 'c');
+        menu.set_visible_rows(1);
+
+        let mut render = MenuRender::new(
+            &FONT,
+            Size::S8x8,
+            palette,
+            cursor,
+            (10, 20).into(),
+            (40, 8).into(),
+        );
+
+        render.update(&menu);
+        assert_eq!(render.labels.len(), 2);
+        assert_eq!(render.labels[1].position.x, 10 + 40 + 8 + 4);
+        assert_eq!(render.cursor_position.map(|p| p.x), Some(10));
+
+        menu.select(2);
+        render.update(&menu);
+        assert_eq!(render.labels.len(), 1);
+        assert_eq!(render.cursor_position, Some((10, 20).into()));
+    }
+}
@@ -0,0 +1,232 @@
+use core::fmt::{Display, Write};
+
+use crate::{
+    fixnum::Num,
+    input::{Button, ButtonController},
+};
+
+/// What the player did to a [`Slider`] or [`NumberSelector`], returned by
+/// their `update` methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueEvent {
+    /// The player changed the value to this.
+    Changed(i32),
+    /// The player pressed A to accept this value.
+    Confirmed(i32),
+    /// The player pressed B.
+    Cancelled,
+}
+
+/// A value between a minimum and maximum which changes in steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Range {
+    value: i32,
+    min: i32,
+    max: i32,
+    step: i32,
+}
+
+impl Range {
+    fn new(min: i32, max: i32, step: i32, value: i32) -> Self {
+        assert!(min <= max, "the minimum must not be more than the maximum");
+        assert!(step > 0, "the step must be positive");
+
+        Self {
+            value: value.clamp(min, max),
+            min,
+            max,
+            step,
+        }
+    }
+
+    /// Moves the value a step in the direction, returning whether it changed.
+    fn change(&mut self, direction: i32, wrap: bool) -> bool {
+        let previous = self.value;
+        let value = self.value + direction * self.step;
+
+        self.value = match (value, wrap) {
+            (value, true) if value > self.max => self.min,
+            (value, true) if value < self.min => self.max,
+            (value, _) => value.clamp(self.min, self.max),
+        };
+
+        self.value != previous
+    }
+
+    fn update(&mut self, input: &ButtonController, wrap: bool) -> Option<ValueEvent> {
+        if input.is_just_pressed(Button::A) {
+            Some(ValueEvent::Confirmed(self.value))
+        } else if input.is_just_pressed(Button::B) {
+            Some(ValueEvent::Cancelled)
+        } else if self.change(input.repeated_x_tri() as i32, wrap) {
+            Some(ValueEvent::Changed(self.value))
+        } else {
+            None
+        }
+    }
+}
+
+/// A bar which the player fills or empties with left and right, such as for
+/// the volume on a settings screen. It is written as a bar of `=` and `-`
+/// characters, such as `[======----]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Slider {
+    range: Range,
+    segments: usize,
+}
+
+impl Slider {
+    /// Creates a slider between `min` and `max` inclusive, which moves by
+    /// `step` each time left or right is pressed and starts at `value`.
+    #[must_use]
+    pub fn new(min: i32, max: i32, step: i32, value: i32) -> Self {
+        Self {
+            range: Range::new(min, max, step, value),
+            segments: 10,
+        }
+    }
+
+    /// The current value.
+    #[must_use]
+    pub fn value(&self) -> i32 {
+        self.range.value
+    }
+
+    /// Changes the value, keeping it between the minimum and maximum.
+    pub fn set_value(&mut self, value: i32) {
+        self.range.value = value.clamp(self.range.min, self.range.max);
+    }
+
+    /// How far along the slider the value is, from 0 at the minimum to 1 at
+    /// the maximum.
+    #[must_use]
+    pub fn fraction(&self) -> Num<i32, 8> {
+        if self.range.max == self.range.min {
+            return 1.into();
+        }
+
+        Num::new(self.range.value - self.range.min) / (self.range.max - self.range.min)
+    }
+
+    /// Sets how many characters long the bar is when it is written, which is
+    /// 10 by default.
+    pub fn set_segments(&mut self, segments: usize) {
+        self.segments = segments;
+    }
+
+    /// Changes the value when left or right is pressed or held. Call this
+    /// once per frame while the slider is selected.
+    pub fn update(&mut self, input: &ButtonController) -> Option<ValueEvent> {
+        self.range.update(input, false)
+    }
+}
+
+impl Display for Slider {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let filled = (self.fraction() * self.segments as i32 + Num::new(1) / 2).floor() as usize;
+
+        f.write_char('[')?;
+        for segment in 0..self.segments {
+            f.write_char(if segment < filled { '=' } else { '-' })?;
+        }
+        f.write_char(']')
+    }
+}
+
+/// A number which the player changes with left and right, such as how many of
+/// an item to buy. By default, going past one end wraps around to the other.
+/// It is written as the number between arrows, such as `< 5 >`, where an arrow
+/// is left out if the number can't go any further that way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberSelector {
+    range: Range,
+    wrap: bool,
+}
+
+impl NumberSelector {
+    /// Creates a number selector between `min` and `max` inclusive, which
+    /// starts at `value`.
+    #[must_use]
+    pub fn new(min: i32, max: i32, value: i32) -> Self {
+        Self {
+            range: Range::new(min, max, 1, value),
+            wrap: true,
+        }
+    }
+
+    /// The current value.
+    #[must_use]
+    pub fn value(&self) -> i32 {
+        self.range.value
+    }
+
+    /// Changes the value, keeping it between the minimum and maximum.
+    pub fn set_value(&mut self, value: i32) {
+        self.range.value = value.clamp(self.range.min, self.range.max);
+    }
+
+    /// Sets how much the value changes each time left or right is pressed,
+    /// which is 1 by default.
+    pub fn set_step(&mut self, step: i32) {
+        assert!(step > 0, "the step must be positive");
+        self.range.step = step;
+    }
+
+    /// Sets whether going past one end wraps around to the other.
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+    }
+
+    /// Changes the value when left or right is pressed or held. Call this
+    /// once per frame while the number selector is selected.
+    pub fn update(&mut self, input: &ButtonController) -> Option<ValueEvent> {
+        self.range.update(input, self.wrap)
+    }
+}
+
+impl Display for NumberSelector {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let left = self.wrap || self.range.value > self.range.min;
+        let right = self.wrap || self.range.value < self.range.max;
+
+        write!(
+            f,
+            "{} {} {}",
+            if left { '<' } else { ' ' },
+            self.range.value,
+            if right { '>' } else { ' ' }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test_case]
+    fn ranges_clamp_or_wrap(_gba: &mut crate::Gba) {
+        let mut range = Range::new(0, 10, 4, 6);
+
+        assert!(range.change(1, false));
+        assert_eq!(range.value, 10);
+        assert!(!range.change(1, false));
+
+        assert!(range.change(1, true));
+        assert_eq!(range.value, 0);
+        assert!(range.change(-1, true));
+        assert_eq!(range.value, 10);
+    }
+
+    #[test_case]
+    fn widgets_are_written_as_text(_gba: &mut crate::Gba) {
+        let mut slider = Slider::new(0, 100, 10, 60);
+        slider.set_segments(5);
+        assert_eq!(slider.to_string(), "[===--]");
+
+        let mut selector = NumberSelector::new(1, 9, 1);
+        selector.set_wrap(false);
+        assert_eq!(selector.to_string(), "  1 >");
+    }
+}
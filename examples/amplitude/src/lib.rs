@@ -18,6 +18,7 @@ use agb::{
             SpriteLoader, SpriteVram, Tag,
         },
         palette16::Palette16,
+        Rgb15,
     },
     fixnum::{num, Num, Vector2D},
     include_aseprite,
@@ -391,7 +392,7 @@ pub fn main(mut gba: agb::Gba) -> ! {
 
     let (_background, mut vram) = gba.display.video.tiled0();
 
-    vram.set_background_palettes(&[Palette16::new([u16::MAX; 16])]);
+    vram.set_background_palettes(&[Palette16::new([Rgb15::WHITE; 16])]);
 
     let vblank = agb::interrupt::VBlank::get();
 
@@ -16,7 +16,7 @@ use agb::{
     display::{
         object::{Graphics, OamManaged, Object, Sprite, Tag, TagMap},
         tiled::{InfiniteScrolledMap, RegularBackgroundSize, TileFormat, VRamManager},
-        Priority, HEIGHT, WIDTH,
+        Priority, Rgb15, HEIGHT, WIDTH,
     },
     fixnum::{num, FixedNum, Rect, Vector2D},
     input::{Button, ButtonController, Tri},
@@ -2127,8 +2127,8 @@ impl<'a> Game<'a> {
         let a = modified_palette.colour(0);
         let b = modified_palette.colour(1);
 
-        modified_palette.update_colour(0, interpolate_colour(a, 17982, time, 120));
-        modified_palette.update_colour(1, interpolate_colour(b, 22427, time, 120));
+        modified_palette.update_colour(0, interpolate_colour(a, SUNRISE_SKY, time, 120));
+        modified_palette.update_colour(1, interpolate_colour(b, SUNRISE_HORIZON, time, 120));
 
         let modified_palettes = [modified_palette];
 
@@ -2140,9 +2140,12 @@ impl<'a> Game<'a> {
 
         let c = modified_palette.colour(2);
 
-        modified_palette.update_colour(0, interpolate_colour(17982, 0x7FFF, time, 600));
-        modified_palette.update_colour(1, interpolate_colour(22427, 0x7FFF, time, 600));
-        modified_palette.update_colour(2, interpolate_colour(c, 0x7FFF, time, 600));
+        modified_palette.update_colour(0, interpolate_colour(SUNRISE_SKY, Rgb15::WHITE, time, 600));
+        modified_palette.update_colour(
+            1,
+            interpolate_colour(SUNRISE_HORIZON, Rgb15::WHITE, time, 600),
+        );
+        modified_palette.update_colour(2, interpolate_colour(c, Rgb15::WHITE, time, 600));
 
         let modified_palettes = [modified_palette];
 
@@ -2302,23 +2305,15 @@ fn ping_pong(i: u16, n: u16) -> u16 {
     }
 }
 
-fn interpolate_colour(initial: u16, destination: u16, time_so_far: u16, total_time: u16) -> u16 {
-    const MASK: u16 = 0b11111;
-    fn to_components(c: u16) -> [u16; 3] {
-        [c & MASK, (c >> 5) & MASK, (c >> 10) & MASK]
-    }
-
-    let initial_rgb = to_components(initial);
-    let destination_rgb = to_components(destination);
-    let mut colour = 0;
-
-    for (i, c) in initial_rgb
-        .iter()
-        .zip(destination_rgb)
-        .map(|(a, b)| (b - a) * time_so_far / total_time + a)
-        .enumerate()
-    {
-        colour |= (c & MASK) << (i * 5);
-    }
-    colour
+const SUNRISE_SKY: Rgb15 = Rgb15::new(17982);
+const SUNRISE_HORIZON: Rgb15 = Rgb15::new(22427);
+
+fn interpolate_colour(
+    initial: Rgb15,
+    destination: Rgb15,
+    time_so_far: u16,
+    total_time: u16,
+) -> Rgb15 {
+    let amount: FixedNum<8> = FixedNum::new(time_so_far.into()) / i32::from(total_time);
+    initial.lerp(destination, amount)
 }
@@ -46,6 +46,13 @@ pub fn load_module_from_file(xm_path: &Path) -> Result<Module, Box<dyn Error>> {
 }
 
 pub fn parse_module(module: &Module) -> TokenStream {
+    with_track(module, |track| quote!(#track))
+}
+
+/// Converts the module to a track and calls `f` with it. The track borrows its
+/// samples and patterns from buffers which only live for the duration of the
+/// call.
+pub fn with_track<R>(module: &Module, f: impl FnOnce(&agb_tracker_interop::Track<'_>) -> R) -> R {
     let instruments = &module.instrument;
     let mut instruments_map = HashMap::new();
 
@@ -454,7 +461,7 @@ pub fn parse_module(module: &Module) -> TokenStream {
         repeat: module.restart_position as usize,
     };
 
-    quote!(#interop)
+    f(&interop)
 }
 
 fn bpm_to_frames_per_tick(bpm: u32) -> Num<u32, 8> {